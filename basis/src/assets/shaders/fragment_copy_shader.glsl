@@ -0,0 +1,11 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screen_texture;
+
+void main()
+{
+    FragColor = vec4(texture(screen_texture, TexCoord).rgb, 1.0);
+}
@@ -0,0 +1,67 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screen_texture;
+// 1.0 / resolution
+uniform vec2 texel_size;
+
+const float FXAA_SPAN_MAX = 8.0;
+const float FXAA_REDUCE_MUL = 1.0 / 8.0;
+const float FXAA_REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 color)
+{
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+// Fast approximate anti-aliasing, blurs the pixels along the edges found with the luma
+void main()
+{
+    vec3 rgb_nw = texture(screen_texture, TexCoord + vec2(-1.0, -1.0) * texel_size).rgb;
+    vec3 rgb_ne = texture(screen_texture, TexCoord + vec2(1.0, -1.0) * texel_size).rgb;
+    vec3 rgb_sw = texture(screen_texture, TexCoord + vec2(-1.0, 1.0) * texel_size).rgb;
+    vec3 rgb_se = texture(screen_texture, TexCoord + vec2(1.0, 1.0) * texel_size).rgb;
+    vec3 rgb_m = texture(screen_texture, TexCoord).rgb;
+
+    float luma_nw = luma(rgb_nw);
+    float luma_ne = luma(rgb_ne);
+    float luma_sw = luma(rgb_sw);
+    float luma_se = luma(rgb_se);
+    float luma_m = luma(rgb_m);
+
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    vec2 direction = vec2(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        ((luma_nw + luma_sw) - (luma_ne + luma_se))
+    );
+
+    float direction_reduce = max(
+        (luma_nw + luma_ne + luma_sw + luma_se) * (0.25 * FXAA_REDUCE_MUL),
+        FXAA_REDUCE_MIN
+    );
+    float direction_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(
+        direction * direction_min,
+        vec2(-FXAA_SPAN_MAX),
+        vec2(FXAA_SPAN_MAX)
+    ) * texel_size;
+
+    vec3 rgb_a = 0.5 * (
+        texture(screen_texture, TexCoord + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(screen_texture, TexCoord + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 rgb_b = rgb_a * 0.5 + 0.25 * (
+        texture(screen_texture, TexCoord + direction * -0.5).rgb +
+        texture(screen_texture, TexCoord + direction * 0.5).rgb
+    );
+
+    float luma_b = luma(rgb_b);
+    if (luma_b < luma_min || luma_b > luma_max)
+        FragColor = vec4(rgb_a, 1.0);
+    else
+        FragColor = vec4(rgb_b, 1.0);
+}
@@ -5,8 +5,15 @@ in vec3 Color;
 in vec2 TexCoord;
 in float FaceId;
 in float MaxFaceId;
+in vec4 LightSpacePos;
+in float ViewDistance;
 
 uniform sampler2D object_texture;
+uniform vec3 material_diffuse;
+uniform float material_dissolve;
+
+#include "include/shadow.glsl"
+#include "include/fog.glsl"
 
 void main()
 {
@@ -17,6 +24,9 @@ void main()
     float g = FaceId / MaxFaceId;
     float b = FaceId / MaxFaceId;
     vec4 color = vec4(r, g, b, 1.0);
+    vec4 material_color = vec4(material_diffuse, material_dissolve);
 
-    FragColor = texture_color * color * color_percentage;
+    FragColor = texture_color * material_color * color * color_percentage;
+    FragColor.rgb = apply_shadow(FragColor.rgb, LightSpacePos);
+    FragColor.rgb = apply_fog(FragColor.rgb, ViewDistance);
 }
@@ -0,0 +1,14 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec3 TexCoord;
+
+uniform samplerCube skybox;
+
+#include "include/fog.glsl"
+
+void main()
+{
+    vec4 color = texture(skybox, TexCoord);
+    FragColor = vec4(apply_sky_fog(color.rgb, TexCoord), color.a);
+}
@@ -0,0 +1,42 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screen_texture;
+// from 0.0 to 1.0
+uniform float speed;
+// seconds, animates the lines
+uniform float time;
+
+const int BLUR_SAMPLES = 8;
+const float LINES_COUNT = 90.0;
+
+float hash(float n)
+{
+    return fract(sin(n) * 43758.5453);
+}
+
+void main()
+{
+    vec2 from_center = TexCoord - 0.5;
+
+    // radial motion blur, stronger far from the center of the screen
+    vec3 color = vec3(0.0);
+    for (int i = 0; i < BLUR_SAMPLES; i++)
+    {
+        float scale = 1.0 - speed * 0.08 * (float(i) / float(BLUR_SAMPLES));
+        color += texture(screen_texture, 0.5 + from_center * scale).rgb;
+    }
+    color /= float(BLUR_SAMPLES);
+
+    // thin white lines coming from the center, only on the edges of the screen
+    float angle = atan(from_center.y, from_center.x);
+    float line = floor((angle / 6.28318530 + 0.5) * LINES_COUNT);
+    float flicker = floor(time * 15.0);
+    float is_line = step(1.0 - 0.35 * speed, hash(line + flicker * 17.0));
+    float edges = smoothstep(0.25, 0.7, length(from_center));
+    color = mix(color, vec3(1.0), is_line * edges * speed * 0.6);
+
+    FragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screen_texture;
+uniform float exposure;
+
+void main()
+{
+    vec3 color = texture(screen_texture, TexCoord).rgb;
+
+    // exposure tonemapping, maps [0, inf) to [0, 1)
+    FragColor = vec4(vec3(1.0) - exp(-color * exposure), 1.0);
+}
@@ -0,0 +1,18 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screen_texture;
+uniform float strength;
+
+void main()
+{
+    vec3 color = texture(screen_texture, TexCoord).rgb;
+
+    // 0.0 at the center of the screen, 1.0 at the corners
+    float distance_to_center = length(TexCoord - 0.5) * 1.41421356;
+    float vignette = 1.0 - strength * smoothstep(0.4, 1.0, distance_to_center);
+
+    FragColor = vec4(color * vignette, 1.0);
+}
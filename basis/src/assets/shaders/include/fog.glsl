@@ -0,0 +1,40 @@
+// Distance fog, see the `Fog` resource
+
+// 0 without fog, then linear, exponential and exponential squared, see `FogMode`
+uniform int fog_mode;
+uniform vec3 fog_color;
+uniform float fog_density;
+uniform float fog_start;
+uniform float fog_end;
+
+// Part of the fragment color kept at its distance from the camera, must match `Fog::visibility`
+float compute_fog_visibility(float distance)
+{
+    float visibility = 1.0;
+    if (fog_mode == 1)
+        visibility = fog_end > fog_start
+            ? (fog_end - distance) / (fog_end - fog_start)
+            : 1.0 - step(fog_start, distance);
+    else if (fog_mode == 2)
+        visibility = exp(-fog_density * distance);
+    else if (fog_mode == 3)
+        visibility = exp(-pow(fog_density * distance, 2.0));
+    return clamp(visibility, 0.0, 1.0);
+}
+
+vec3 apply_fog(vec3 color, float distance)
+{
+    return mix(fog_color, color, compute_fog_visibility(distance));
+}
+
+// Height above the horizon, in the y of a unit direction, from which the sky is clear of fog
+const float SKY_FOG_HEIGHT = 0.3;
+
+// The sky is infinitely far, it fades into the fog color toward the horizon only, where the
+// fogged geometry meets it
+vec3 apply_sky_fog(vec3 color, vec3 direction)
+{
+    if (fog_mode == 0)
+        return color;
+    return mix(fog_color, color, smoothstep(0.0, SKY_FOG_HEIGHT, normalize(direction).y));
+}
@@ -0,0 +1,10 @@
+// Camera and light data uploaded once per frame, see `FrameData`
+
+layout(std140) uniform Frame
+{
+    mat4 view;
+    mat4 projection;
+    mat4 light_space;
+    vec3 camera_position;
+    bool shadows_enabled;
+};
@@ -0,0 +1,38 @@
+// Shadow of the directional light, needs `LightSpacePos`, the fragment position in light space
+
+#include "frame.glsl"
+
+uniform sampler2D shadow_map;
+
+// how dark a fully shadowed fragment is
+const float SHADOW_STRENGTH = 0.5;
+// avoids shadow acne, surfaces shadowing themselves
+const float SHADOW_BIAS = 0.005;
+
+// Percentage of the fragment in shadow, averaged over the 3x3 neighbour texels (PCF)
+float compute_shadow(vec4 light_space_pos)
+{
+    vec3 coords = light_space_pos.xyz / light_space_pos.w * 0.5 + 0.5;
+    if (coords.z > 1.0)
+        return 0.0;
+
+    vec2 texel_size = 1.0 / vec2(textureSize(shadow_map, 0));
+    float shadow = 0.0;
+    for (int x = -1; x <= 1; x++)
+    {
+        for (int y = -1; y <= 1; y++)
+        {
+            float depth = texture(shadow_map, coords.xy + vec2(x, y) * texel_size).r;
+            shadow += coords.z - SHADOW_BIAS > depth ? 1.0 : 0.0;
+        }
+    }
+    return shadow / 9.0;
+}
+
+// Color of the fragment once darkened by the shadow
+vec3 apply_shadow(vec3 color, vec4 light_space_pos)
+{
+    if (!shadows_enabled)
+        return color;
+    return color * (1.0 - SHADOW_STRENGTH * compute_shadow(light_space_pos));
+}
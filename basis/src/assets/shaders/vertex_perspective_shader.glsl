@@ -4,22 +4,26 @@ layout(location = 1) in vec3 aColor;
 layout(location = 2) in vec2 aTexCoord;
 layout(location = 3) in float aFaceId;
 layout(location = 4) in float aMaxFaceId;
+layout(location = 5) in mat4 aModel; // per instance, takes locations 5 to 8
 
-uniform mat4 model;
-uniform mat4 view;
-uniform mat4 projection;
+#include "include/frame.glsl"
 
 out vec3 Color;
 out vec2 TexCoord;
 out float FaceId;
 out float MaxFaceId;
+out vec4 LightSpacePos;
+out float ViewDistance;
 
 void main()
 {
     vec4 pos = aPos;
-    gl_Position = projection * view * model * pos;
+    vec4 view_pos = view * aModel * pos;
+    gl_Position = projection * view_pos;
+    ViewDistance = length(view_pos.xyz);
     Color = aColor;
     TexCoord = aTexCoord;
     FaceId = aFaceId;
     MaxFaceId = aMaxFaceId;
+    LightSpacePos = light_space * aModel * pos;
 }
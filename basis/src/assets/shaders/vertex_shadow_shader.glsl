@@ -0,0 +1,10 @@
+#version 330 core
+layout(location = 0) in vec4 aPos;
+layout(location = 5) in mat4 aModel; // per instance, takes locations 5 to 8
+
+uniform mat4 light_space;
+
+void main()
+{
+    gl_Position = light_space * aModel * aPos;
+}
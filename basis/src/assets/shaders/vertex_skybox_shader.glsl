@@ -0,0 +1,15 @@
+#version 330 core
+layout(location = 0) in vec3 aPos;
+
+#include "include/frame.glsl"
+
+out vec3 TexCoord;
+
+void main()
+{
+    TexCoord = aPos;
+    // without the translation the sky follows the camera
+    vec4 pos = projection * mat4(mat3(view)) * vec4(aPos, 1.0);
+    // z = w puts the sky on the far plane, behind everything else
+    gl_Position = pos.xyww;
+}
@@ -7,4 +7,5 @@ use crate::prelude::*;
 ///
 pub trait Camerable {
     fn get_view_matrix(&self) -> Mat4;
+    fn get_position(&self) -> Vec3;
 }
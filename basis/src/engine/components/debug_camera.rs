@@ -1,23 +1,37 @@
 use crate::engine::behaviours::Controllable;
 use crate::prelude::*;
 
+/// Keeps the camera from flipping over when looking straight up or down
+const MAX_PITCH: f32 = 89.0;
+
 #[derive(Debug)]
 pub struct DebugCamera {
     pub position: Vec3,
     front: Vec3,
     up: Vec3,
     speed: f32,
+    /// Angle in degrees of `front` around `up`, 0 looks towards +X
+    yaw: f32,
+    /// Angle in degrees of `front` above the horizon
+    pitch: f32,
 }
 
 impl DebugCamera {
     pub fn new(position: Vec3, front: Vec3, up: Vec3, speed: f32) -> Self {
+        let front = front.normalize();
         Self {
             position,
             front,
             up,
             speed,
+            yaw: front.z.atan2(front.x).to_degrees(),
+            pitch: front.y.clamp(-1.0, 1.0).asin().to_degrees(),
         }
     }
+
+    pub fn front(&self) -> Vec3 {
+        self.front
+    }
 }
 
 impl Component for DebugCamera {}
@@ -26,6 +40,10 @@ impl Camerable for DebugCamera {
     fn get_view_matrix(&self) -> Mat4 {
         Mat4::look_at(self.position, self.position + self.front, self.up)
     }
+
+    fn get_position(&self) -> Vec3 {
+        self.position
+    }
 }
 
 impl Controllable for DebugCamera {
@@ -67,15 +85,69 @@ impl Controllable for DebugCamera {
         self.position = self.position - self.up.scale(self.get_speed(deltatime));
     }
 
-    #[warn(dead_code)]
+    ///
+    /// Turns the camera by `yaw` and `pitch` degrees, the mouse deltas already depend on the
+    /// frame duration so `deltatime` is not used
+    ///
     fn rotate(&mut self, _deltatime: f32, yaw: f32, pitch: f32) {
-        let yawr = yaw.to_radians();
-        let pitchr = pitch.to_radians();
-        let mut direction = Vec3::splat(0.);
-        direction.x = yawr.cos() * pitchr.sin();
-        direction.y = pitchr.sin();
-        direction.z = yawr.sin() * pitchr.sin();
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        self.front = Vec3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        )
+        .normalize();
     }
 
     fn rotateq(&mut self, _deltatime: f32, _quaternion: Quaternion) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(result: Vec3, expected: Vec3) {
+        assert!(
+            (result - expected).length() < 1e-4,
+            "{:?} != {:?}",
+            result,
+            expected
+        );
+    }
+
+    #[test]
+    fn it_should_turn_the_camera_with_yaw_and_pitch() {
+        let mut camera = DebugCamera::new(
+            Vec3::splat(0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+        );
+
+        camera.rotate(0.0, 90.0, 0.0);
+        assert_close(camera.front(), Vec3::new(1.0, 0.0, 0.0));
+
+        camera.rotate(0.0, 0.0, 45.0);
+        let diagonal = 0.5_f32.sqrt();
+        assert_close(camera.front(), Vec3::new(diagonal, diagonal, 0.0));
+    }
+
+    #[test]
+    fn it_should_not_look_past_straight_up() {
+        let mut camera = DebugCamera::new(
+            Vec3::splat(0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+        );
+
+        camera.rotate(0.0, 0.0, 120.0);
+
+        assert!(camera.front().y < 1.0);
+        assert!((camera.front().y - MAX_PITCH.to_radians().sin()).abs() < 1e-4);
+        assert!(camera.front().x > 0.0);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::prelude::*;
+
+///
+/// Directional light, like the sun every ray has the same direction so only the direction
+/// matters, not the entity position.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    /// Direction the light travels in
+    pub direction: Vec3,
+    /// Whether the objects lit by this light cast shadows
+    pub casts_shadows: bool,
+    /// Half of the width of the area around the camera where shadows are computed
+    pub shadow_extent: f32,
+}
+impl Component for Light {}
+
+impl Light {
+    pub fn directional(direction: Vec3) -> Self {
+        Self {
+            direction: direction.normalize(),
+            casts_shadows: true,
+            shadow_extent: 50.,
+        }
+    }
+
+    ///
+    /// Projection and view from the light point of view, covering a box of `shadow_extent`
+    /// around `center`. Used to render the shadow map and to look it up afterwards.
+    ///
+    pub fn light_space_matrix(&self, center: Vec3) -> Mat4 {
+        let direction = self.direction.normalize();
+        // the up vector can not be parallel to the direction
+        let up = if direction.x == 0.0 && direction.z == 0.0 {
+            Vec3::new(0.0, 0.0, -1.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+
+        let position = center - direction.scale(self.shadow_extent * 2.);
+        let projection = Mat4::ortho(
+            -self.shadow_extent,
+            self.shadow_extent,
+            -self.shadow_extent,
+            self.shadow_extent,
+            0.0,
+            self.shadow_extent * 4.,
+        );
+
+        projection * Mat4::look_at(position, center, up)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_light_space(light: &Light, center: Vec3, point: Vec3) -> Vec4 {
+        light.light_space_matrix(center) * Vec4::new(point.x, point.y, point.z, 1.0)
+    }
+
+    #[test]
+    fn it_should_project_center_to_the_middle_of_the_shadow_map() {
+        let light = Light::directional(Vec3::new(-1.0, -1.0, -0.5));
+        let center = Vec3::new(3.0, 0.0, -7.0);
+
+        let result = to_light_space(&light, center, center);
+
+        assert!(result.x.abs() < 1e-4);
+        assert!(result.y.abs() < 1e-4);
+        assert!(result.z.abs() < 1e-4);
+    }
+
+    #[test]
+    fn it_should_project_points_closer_to_the_light_with_less_depth() {
+        let light = Light::directional(Vec3::new(0.0, -1.0, 0.0));
+        let center = Vec3::splat(0.0);
+
+        let roof = to_light_space(&light, center, Vec3::new(0.0, 10.0, 0.0));
+        let floor = to_light_space(&light, center, Vec3::new(0.0, 0.0, 0.0));
+
+        assert!(roof.z < floor.z);
+        assert!(roof.x.abs() < 1e-4 && roof.y.abs() < 1e-4);
+    }
+}
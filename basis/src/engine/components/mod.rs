@@ -1,9 +1,11 @@
 mod cube;
 mod debug_camera;
+mod light;
 mod object;
 mod transform;
 
 pub use cube::*;
 pub use debug_camera::*;
+pub use light::*;
 pub use object::*;
 pub use transform::*;
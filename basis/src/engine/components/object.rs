@@ -1,36 +1,168 @@
-use std::{ffi::c_void, mem, ptr};
+use std::{ffi::c_void, mem, ops::Deref, ptr, rc::Rc};
 
-use crate::graphics::{glw, wavefront};
+use crate::graphics::{aabb::AABB, glw, image::Image, wavefront};
 use crate::prelude::*;
 
+/// Amount of floats each vertex takes in the vertex buffer
+const VERTEX_LENGTH: usize = 12;
+
+/// First attribute location of the per-instance model matrix, a mat4 takes four locations
+const INSTANCE_MODEL_LOCATION: u32 = 5;
+
+///
+/// Faces of an `Object` sharing the same material, drawn with their own vertex buffer,
+/// texture, diffuse color and transparency.
+///
+#[derive(Debug, PartialEq)]
+pub struct SubMesh {
+    vao: glw::Vao,
+    /// Read through `vao`, owned here so it lives as long as the submesh
+    vertex_buffer: glw::BufferObject,
+    texture: glw::Texture,
+    vertex_count: i32,
+    /// Multiplied with the texture color
+    pub diffuse: Vec3,
+    /// Opacity of the submesh, "d" of the material
+    pub dissolve: f32,
+    /// Center of the bounding box of the submesh, in model space
+    pub center: Vec3,
+}
+
+impl SubMesh {
+    pub fn is_transparent(&self) -> bool {
+        self.dissolve < 1.0
+    }
+
+    ///
+    /// Draws the submesh `instance_count` times, the model matrices must already be in the
+    /// instance buffer. The blend state is left to the caller.
+    ///
+    pub fn draw_instanced(&self, shader: &glw::Shader, instance_count: i32) {
+        shader.get_uniform_location("material_diffuse").uniform3f(
+            self.diffuse.x,
+            self.diffuse.y,
+            self.diffuse.z,
+        );
+        shader
+            .get_uniform_location("material_dissolve")
+            .uniform1f(self.dissolve);
+
+        self.vao.bind();
+        self.texture.active(gl::TEXTURE0);
+        self.texture.bind();
+        glw::draw_arrays_instanced(gl::TRIANGLES, 0, self.vertex_count, instance_count);
+        self.vao.unbind();
+    }
+}
+
+///
+/// GPU resources an `Object` is drawn with, deleted once the last `MeshHandle` to them is
+/// dropped
+///
+#[derive(Debug)]
+pub struct Mesh {
+    instance_buffer: glw::BufferObject,
+    submeshes: Vec<SubMesh>,
+}
+
+///
+/// Shared handle to the `Mesh` of an `Object`. Clones of the same `Object` share their mesh,
+/// which lets the renderer draw all of them with a single instanced call per submesh. Two
+/// handles are equal when they point to the same mesh.
+///
+#[derive(Debug, Clone)]
+pub struct MeshHandle(Rc<Mesh>);
+
+impl PartialEq for MeshHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for MeshHandle {
+    type Target = Mesh;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Mesh {
+    pub fn submeshes(&self) -> &[SubMesh] {
+        &self.submeshes
+    }
+
+    fn upload_instances(&self, models: &[Mat4]) {
+        self.instance_buffer.bind();
+        self.instance_buffer.store_mat4(models);
+        self.instance_buffer.unbind();
+    }
+
+    ///
+    /// Draws every submesh once per model matrix, the matrices are uploaded to the instance
+    /// buffer and read by the shader as a vertex attribute.
+    ///
+    pub fn draw_instanced(&self, shader: &glw::Shader, models: &[Mat4]) {
+        if models.is_empty() {
+            return;
+        }
+
+        self.upload_instances(models);
+        for submesh in &self.submeshes {
+            submesh.draw_instanced(shader, models.len() as i32);
+        }
+    }
+
+    ///
+    /// Same as `draw_instanced` but skips the transparent submeshes, they are drawn one by one
+    /// after every opaque submesh
+    ///
+    pub fn draw_opaque_instanced(&self, shader: &glw::Shader, models: &[Mat4]) {
+        if models.is_empty() || self.submeshes.iter().all(SubMesh::is_transparent) {
+            return;
+        }
+
+        self.upload_instances(models);
+        for submesh in self
+            .submeshes
+            .iter()
+            .filter(|submesh| !submesh.is_transparent())
+        {
+            submesh.draw_instanced(shader, models.len() as i32);
+        }
+    }
+
+    ///
+    /// Draws a single submesh once with the given model matrix
+    ///
+    pub fn draw_submesh(&self, shader: &glw::Shader, submesh: usize, model: &Mat4) {
+        self.upload_instances(std::slice::from_ref(model));
+        self.submeshes[submesh].draw_instanced(shader, 1);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Object {
     pub rgb: Vec3,
 
     pub model: wavefront::obj::OBJ,
 
-    vao: glw::Vao,
-    texture: glw::Texture,
+    mesh: MeshHandle,
     cached_center: Vec3,
-    cached_vertices: Vec<f32>,
-    cached_indices: Vec<u32>,
 }
 
 impl Component for Object {}
 
 impl Object {
     pub fn new(model: wavefront::obj::OBJ) -> Object {
+        let rgb = Vec3::default();
         let mut object = Object {
-            rgb: Vec3::default(),
+            rgb,
 
-            model,
-            vao: glw::Vao::default(),
-            texture: glw::Texture::new(gl::TEXTURE_2D),
+            mesh: compute_mesh(&model, rgb),
             cached_center: Vec3::default(),
-            cached_vertices: Vec::default(),
-            cached_indices: Vec::default(),
+            model,
         };
-        object.recompute();
         object.compute_center();
         object
     }
@@ -39,107 +171,29 @@ impl Object {
         self.rgb = new_color;
     }
 
-    pub fn set_texture(&mut self, texture: (u32, u32, Vec<u8>)) {
-        self.model.texture = texture;
+    ///
+    /// Textures every submesh with `texture`, replacing the material textures
+    ///
+    pub fn set_texture(&mut self, texture: Image) {
+        self.model.texture = Some(texture);
         self.recompute();
     }
 
-    pub fn draw(&self) {
-        self.vao.bind();
-        glw::draw_arrays(gl::TRIANGLES, 0, self.cached_vertices.len() as i32);
-        // glw::draw_elements(
-        //     gl::TRIANGLES,
-        //     self.cached_indices.len() as i32,
-        //     gl::UNSIGNED_INT,
-        //     ptr::null(),
-        // );
-        self.vao.unbind();
+    pub fn mesh(&self) -> MeshHandle {
+        self.mesh.clone()
+    }
+
+    pub fn draw(&self, shader: &glw::Shader, model: &Mat4) {
+        self.mesh()
+            .draw_instanced(shader, std::slice::from_ref(model));
     }
 
     ///
-    /// Refetch all raw vertices and raw indices used by draw call
-    /// and setup the VAO and EBO
+    /// Uploads the vertices of the model again, one VAO per material. The previous mesh is
+    /// released once no clone of this `Object` uses it anymore.
     ///
     pub fn recompute(&mut self) {
-        let model_vertices = self.model.get_raw_vertices(self.rgb);
-        let vertices = model_vertices;
-
-        self.cached_vertices = vertices;
-        self.cached_indices = self.model.get_raw_indices();
-
-        self.vao.bind();
-        let vbo = glw::BufferObject::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
-        // let ebo = glw::BufferObject::new(gl::ELEMENT_ARRAY_BUFFER, gl::STATIC_DRAW);
-        vbo.bind();
-        // ebo.bind();
-        vbo.store_f32(&self.cached_vertices);
-        // ebo.store_u32(&self.cached_indices);
-
-        let stride_length = 12 * mem::size_of::<gl::types::GLfloat>() as gl::types::GLsizei;
-        let start_pointer = ptr::null::<gl::types::GLfloat>();
-        let position_attribute = glw::VertexAttribute::new(
-            0,
-            4,
-            gl::FLOAT,
-            gl::FALSE,
-            stride_length,
-            start_pointer as *const c_void,
-        );
-        position_attribute.enable();
-
-        let color_attribute =
-            glw::VertexAttribute::new(1, 3, gl::FLOAT, gl::FALSE, stride_length, unsafe {
-                start_pointer.add(4) as *const c_void
-            });
-        color_attribute.enable();
-
-        let texture_coordinate_attribute =
-            glw::VertexAttribute::new(2, 3, gl::FLOAT, gl::FALSE, stride_length, unsafe {
-                start_pointer.add(7) as *const c_void
-            });
-        texture_coordinate_attribute.enable();
-
-        let face_id_attribute =
-            glw::VertexAttribute::new(3, 1, gl::FLOAT, gl::FALSE, stride_length, unsafe {
-                start_pointer.add(10) as *const c_void
-            });
-        face_id_attribute.enable();
-
-        let max_face_id_attribute =
-            glw::VertexAttribute::new(4, 1, gl::FLOAT, gl::FALSE, stride_length, unsafe {
-                start_pointer.add(11) as *const c_void
-            });
-        max_face_id_attribute.enable();
-
-        self.recompute_texture();
-
-        self.vao.unbind();
-    }
-
-    fn recompute_texture(&mut self) {
-        self.texture.bind();
-        self.texture
-            .tex_parameteri(gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-        self.texture
-            .tex_parameteri(gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-
-        self.texture
-            .tex_parameteri(gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-        self.texture
-            .tex_parameteri(gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
-        self.texture.tex_image2d(
-            0,
-            gl::RGBA8 as i32,
-            self.model.texture.0 as i32,
-            self.model.texture.1 as i32,
-            0,
-            gl::RGBA,
-            gl::UNSIGNED_BYTE,
-            self.model.texture.2.as_ptr() as *const std::ffi::c_void,
-        );
-
-        self.texture.generate_mipmap();
+        self.mesh = compute_mesh(&self.model, self.rgb);
     }
 
     fn compute_center(&mut self) {
@@ -154,3 +208,171 @@ impl Object {
         self.cached_center
     }
 }
+
+///
+/// Uploads one submesh per material of `model`
+///
+fn compute_mesh(model: &wavefront::obj::OBJ, rgb: Vec3) -> MeshHandle {
+    let instance_buffer = glw::BufferObject::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
+    let submeshes = model
+        .get_material_groups()
+        .iter()
+        .map(|group| compute_submesh(model, rgb, group, &instance_buffer))
+        .collect();
+
+    MeshHandle(Rc::new(Mesh {
+        instance_buffer,
+        submeshes,
+    }))
+}
+
+fn compute_submesh(
+    model: &wavefront::obj::OBJ,
+    rgb: Vec3,
+    group: &wavefront::obj::MaterialGroup,
+    instance_buffer: &glw::BufferObject,
+) -> SubMesh {
+    let vertices = model.get_raw_vertices_of(&group.faces, rgb);
+
+    let vao = glw::Vao::default();
+    vao.bind();
+    let vbo = glw::BufferObject::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
+    vbo.bind();
+    vbo.store_f32(&vertices);
+
+    let stride_length =
+        (VERTEX_LENGTH * mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizei;
+    let start_pointer = ptr::null::<gl::types::GLfloat>();
+    let position_attribute = glw::VertexAttribute::new(
+        0,
+        4,
+        gl::FLOAT,
+        gl::FALSE,
+        stride_length,
+        start_pointer as *const c_void,
+    );
+    position_attribute.enable();
+
+    let color_attribute =
+        glw::VertexAttribute::new(1, 3, gl::FLOAT, gl::FALSE, stride_length, unsafe {
+            start_pointer.add(4) as *const c_void
+        });
+    color_attribute.enable();
+
+    let texture_coordinate_attribute =
+        glw::VertexAttribute::new(2, 3, gl::FLOAT, gl::FALSE, stride_length, unsafe {
+            start_pointer.add(7) as *const c_void
+        });
+    texture_coordinate_attribute.enable();
+
+    let face_id_attribute =
+        glw::VertexAttribute::new(3, 1, gl::FLOAT, gl::FALSE, stride_length, unsafe {
+            start_pointer.add(10) as *const c_void
+        });
+    face_id_attribute.enable();
+
+    let max_face_id_attribute =
+        glw::VertexAttribute::new(4, 1, gl::FLOAT, gl::FALSE, stride_length, unsafe {
+            start_pointer.add(11) as *const c_void
+        });
+    max_face_id_attribute.enable();
+
+    compute_instance_attributes(instance_buffer);
+    vao.unbind();
+
+    let (diffuse, dissolve) = match group.material {
+        Some(material) => (
+            Vec3::new(
+                material.diffuse_reflectivity.r,
+                material.diffuse_reflectivity.g,
+                material.diffuse_reflectivity.b,
+            ),
+            material.dissolve_factor.factor,
+        ),
+        None => (Vec3::splat(1.0), 1.0),
+    };
+
+    let image = model.texture.as_ref().or(group
+        .material
+        .and_then(|material| model.textures.get(&material.name)));
+
+    SubMesh {
+        vao,
+        vertex_buffer: vbo,
+        texture: compute_texture(image),
+        vertex_count: (vertices.len() / VERTEX_LENGTH) as i32,
+        diffuse,
+        dissolve,
+        center: compute_center(&vertices),
+    }
+}
+
+///
+/// Center of the bounding box of the positions of the raw vertices
+///
+fn compute_center(vertices: &[f32]) -> Vec3 {
+    let positions = vertices
+        .chunks(VERTEX_LENGTH)
+        .map(|vertex| Vec4::new(vertex[0], vertex[1], vertex[2], vertex[3]))
+        .collect::<Vec<Vec4>>();
+    if positions.is_empty() {
+        return Vec3::default();
+    }
+
+    let aabb = AABB::from(&positions);
+    (aabb.min + aabb.max).scale(0.5)
+}
+
+///
+/// The model matrix is sent per instance, a mat4 attribute is read as four vec4 columns
+///
+fn compute_instance_attributes(instance_buffer: &glw::BufferObject) {
+    instance_buffer.bind();
+
+    let stride_length = mem::size_of::<Mat4>() as gl::types::GLsizei;
+    let start_pointer = ptr::null::<gl::types::GLfloat>();
+    for column in 0..4 {
+        let model_column_attribute = glw::VertexAttribute::new(
+            INSTANCE_MODEL_LOCATION + column,
+            4,
+            gl::FLOAT,
+            gl::FALSE,
+            stride_length,
+            unsafe { start_pointer.add(column as usize * 4) as *const c_void },
+        );
+        model_column_attribute.enable();
+        model_column_attribute.divisor(1);
+    }
+
+    instance_buffer.unbind();
+}
+
+///
+/// Uploads `image` to a new texture, without an image the color is left untouched by the shader
+///
+fn compute_texture(image: Option<&Image>) -> glw::Texture {
+    let texture = glw::Texture::new(gl::TEXTURE_2D);
+    texture.bind();
+    texture.tex_parameteri(gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+    texture.tex_parameteri(gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+
+    texture.tex_parameteri(gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+    texture.tex_parameteri(gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+    let mut image = image.cloned().unwrap_or(Image::splat([255, 255, 255, 255]));
+    image.flip_vertically();
+
+    texture.tex_image2d(
+        0,
+        gl::RGBA8 as i32,
+        image.width as i32,
+        image.height as i32,
+        0,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        image.pixels.as_ptr() as *const std::ffi::c_void,
+    );
+
+    texture.generate_mipmap();
+    texture
+}
@@ -20,6 +20,18 @@ impl Transform {
     pub fn center(&self, object_center: Vec3) -> Vec3 {
         object_center * self.scale // scale by the object's scale
     }
+
+    ///
+    /// Builds the matrix that places an object with the given center in the world
+    ///
+    pub fn model_matrix(&self, object_center: Vec3) -> Mat4 {
+        let mut model_mat = Mat4::identity();
+
+        model_mat.scale(self.scale);
+        model_mat.rotate_around_center(self.center(object_center).negate(), self.rotation);
+        model_mat.translate(self.position);
+        model_mat
+    }
 }
 
 impl Controllable for Transform {
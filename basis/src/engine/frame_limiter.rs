@@ -0,0 +1,97 @@
+use std::{
+    hint, thread,
+    time::{Duration, Instant},
+};
+
+/// The sleeps of the OS overshoot by up to a millisecond or two, the end of a wait spins instead
+const SPIN_DURATION: Duration = Duration::from_millis(2);
+
+///
+/// Waits between frames to hold a target frame rate. The frames are scheduled at a fixed
+/// interval rather than a fixed duration after the previous one, so the time spent outside of
+/// `wait` does not make the rate drift.
+///
+#[derive(Debug, Default)]
+pub struct FrameLimiter {
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn wait(&mut self, target_fps: Option<u32>) {
+        let Some(target_fps) = target_fps.filter(|target_fps| *target_fps > 0) else {
+            self.next_frame = None;
+            return;
+        };
+        let interval = Duration::from_secs_f64(1.0 / target_fps as f64);
+
+        let deadline = schedule(self.next_frame, Instant::now(), interval);
+        wait_until(deadline);
+        self.next_frame = Some(deadline + interval);
+    }
+}
+
+///
+/// When the next frame is due. A frame late by more than an interval starts a new schedule,
+/// the frames it missed are not rushed to catch up.
+///
+fn schedule(next_frame: Option<Instant>, now: Instant, interval: Duration) -> Instant {
+    match next_frame {
+        Some(next_frame) if next_frame + interval >= now => next_frame,
+        _ => now,
+    }
+}
+
+fn wait_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now + SPIN_DURATION {
+        thread::sleep(deadline - now - SPIN_DURATION);
+    }
+    while Instant::now() < deadline {
+        hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_schedule_frames_at_a_fixed_interval() {
+        let interval = Duration::from_millis(10);
+        let start = Instant::now();
+
+        assert_eq!(schedule(None, start, interval), start);
+        // the frame ended early, it waits for its turn
+        let next_frame = start + interval;
+        assert_eq!(
+            schedule(Some(next_frame), start + Duration::from_millis(4), interval),
+            next_frame
+        );
+        // slightly late, the next one is still due on time
+        assert_eq!(
+            schedule(
+                Some(next_frame),
+                start + Duration::from_millis(15),
+                interval
+            ),
+            next_frame
+        );
+        // far behind, the schedule restarts
+        let now = start + Duration::from_millis(50);
+        assert_eq!(schedule(Some(next_frame), now, interval), now);
+    }
+
+    #[test]
+    fn it_should_hold_the_target_frame_rate() {
+        let mut frame_limiter = FrameLimiter::default();
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            frame_limiter.wait(Some(200));
+        }
+
+        // the first frame is due at once, the 4 others every 5 ms
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(20), "{:?}", elapsed);
+    }
+}
@@ -1,19 +1,39 @@
-use std::error::Error;
+use std::{error::Error, fs::File, io::BufReader};
 
-use crate::graphics::{glw, window::Window};
+use crate::graphics::{window::Window, window_backend::WindowBackend};
 mod behaviours;
 mod components;
 mod ecs;
+mod frame_limiter;
 pub mod prelude;
+pub mod recording;
+mod renderer;
 mod resources;
 mod world;
 use crate::prelude::*;
+use frame_limiter::FrameLimiter;
+use recording::{FrameClipboard, RecordedFrame, Recorder, Replayer};
+
+///
+/// Where the input of a run comes from, and whether it is saved to be played again
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Replay {
+    #[default]
+    Off,
+    /// Saves the input of each frame to the file
+    Record(String),
+    /// Feeds the input saved in the file instead of the window's, then goes back to the window
+    /// once the recording is over
+    Play(String),
+}
 
 pub struct Engine {
     width: u32,
     height: u32,
     title: &'static str,
     world: World,
+    replay: Replay,
 }
 
 impl Engine {
@@ -23,19 +43,67 @@ impl Engine {
             width,
             height,
             title,
+            replay: Replay::Off,
         }
     }
 
+    pub fn with_replay(mut self, replay: Replay) -> Self {
+        self.replay = replay;
+        self
+    }
+
     pub fn run(&mut self, systems: &mut Vec<Box<dyn System>>) -> Result<(), Box<dyn Error>> {
-        let mut window = Window::new(self.width, self.height, self.title);
+        let window = Window::create(self.width, self.height, self.title);
+        self.run_with(window, systems)
+    }
+
+    ///
+    /// Runs the systems in another window than the GLFW one, e.g. a `NullWindow`. Nothing is
+    /// rendered when the window has no GL context.
+    ///
+    pub fn run_with<B: WindowBackend>(
+        &mut self,
+        mut window: B,
+        systems: &mut Vec<Box<dyn System>>,
+    ) -> Result<(), Box<dyn Error>> {
         let mut resources = ResourcesManager::default();
 
-        window.init_gl();
-        glw::enable(gl::DEPTH_TEST);
+        let has_gl_context = window.init_gl();
+
+        let mut replayer = match &self.replay {
+            Replay::Play(path) => Some(Replayer::open(path)?),
+            _ => None,
+        };
+        let random = replayer
+            .as_ref()
+            .map_or_else(Random::default, |replayer| Random::new(replayer.seed()));
+        let mut recorder = match &self.replay {
+            Replay::Record(path) => Some(Recorder::create(path, random.seed())?),
+            _ => None,
+        };
 
-        resources.add::<Deltatime>(Deltatime(window.deltatime));
+        resources.add::<Random>(random);
+        resources.add::<Deltatime>(Deltatime(0.0));
         resources.add::<EventHandler>(EventHandler::new());
-        resources.add::<RenderShader>(RenderShader::default());
+        resources.add::<FrameCapture>(FrameCapture::default());
+        resources.add::<Cursor>(Cursor::default());
+        resources.add::<Gamepads>(Gamepads::default());
+        resources.add::<Gestures>(Gestures::default());
+        resources.add::<WindowSettings>(WindowSettings::default());
+        resources.add::<FrameStats>(FrameStats::default());
+        resources.add::<TextInput>(TextInput::default());
+        let mut screen = Screen::default();
+        screen.update(
+            window.get_size(),
+            window.get_framebuffer_size(),
+            window.get_content_scale(),
+        );
+        screen.set_monitors(window.monitors());
+        renderer::init_scene(&mut resources);
+        if has_gl_context {
+            renderer::init(&mut resources, screen.framebuffer_size());
+        }
+        resources.add::<Screen>(screen);
 
         // TODO: Improve this:
         systems
@@ -43,10 +111,25 @@ impl Engine {
             .filter(|system| Schedule::Setup == system.get_schedule())
             .for_each(|system| system.run(&mut self.world, &mut resources));
 
+        let mut vsync = resources.get::<WindowSettings>().vsync;
+        window.set_vsync(vsync);
+        let mut frame_limiter = FrameLimiter::default();
+
+        let mut last_time = None;
         while !window.should_close() {
-            window.compute_deltatime();
-            let deltatime = resources.get_mut::<Deltatime>();
-            *deltatime = Deltatime(window.deltatime);
+            let now = window.get_time();
+            let live_deltatime = last_time.map_or(0.0, |last_time| (now - last_time) as f32);
+            if last_time.is_some() {
+                resources.get_mut::<FrameStats>().record(live_deltatime);
+            }
+            last_time = Some(now);
+
+            let replayed_frame = next_replayed_frame(&mut replayer);
+            let (deltatime, time) = match &replayed_frame {
+                Some(frame) => (frame.deltatime, frame.time),
+                None => (live_deltatime, now),
+            };
+            *resources.get_mut::<Deltatime>() = Deltatime(deltatime);
 
             // TODO: Improve this:
             systems
@@ -54,58 +137,271 @@ impl Engine {
                 .filter(|system| Schedule::Loop == system.get_schedule())
                 .for_each(|system| system.run(&mut self.world, &mut resources));
 
-            render(&mut self.world, &mut resources, &window);
+            // a minimized window has nothing to draw into
+            let screen = resources.get::<Screen>();
+            if has_gl_context && !screen.is_minimized() {
+                let (framebuffer_width, framebuffer_height) = screen.framebuffer_size();
+                renderer::reload_shaders(&mut resources, time as f32);
+                renderer::render(
+                    &mut self.world,
+                    &mut resources,
+                    (framebuffer_width, framebuffer_height),
+                    time as f32,
+                );
+
+                if let Err(err) = renderer::capture_frame(
+                    resources.get_mut::<FrameCapture>(),
+                    framebuffer_width,
+                    framebuffer_height,
+                ) {
+                    eprintln!("Failed to capture frame: {}", err);
+                }
+            }
+
+            let cursor_mode = resources.get::<Cursor>().mode;
+            if cursor_mode != window.cursor_mode() {
+                window.set_cursor_mode(cursor_mode);
+                // the cursor jumps when it gets locked or unlocked, that is not a move
+                resources.get_mut::<EventHandler>().forget_cursor_position();
+            }
+
+            let settings = *resources.get::<WindowSettings>();
+            if (settings.mode, settings.monitor) != (window.display_mode(), window.monitor()) {
+                window.set_display_mode(settings.mode, settings.monitor);
+                resources
+                    .get_mut::<Screen>()
+                    .set_monitors(window.monitors());
+            }
+            if settings.vsync != vsync {
+                vsync = settings.vsync;
+                window.set_vsync(vsync);
+            }
+
+            frame_limiter.wait(settings.target_fps);
+            window.swap_buffers();
+            // the window is still polled while replaying so it keeps responding
+            let events = window.poll_events();
+            let gamepads = window.poll_gamepads();
+            let replayed = replayed_frame.is_some();
+            let mut frame = replayed_frame.unwrap_or_else(|| RecordedFrame {
+                deltatime,
+                time,
+                events,
+                gamepads,
+                size: window.get_size(),
+                framebuffer_size: window.get_framebuffer_size(),
+                clipboard: None,
+            });
 
-            let events = window.update();
             let event_handler = resources.get_mut::<EventHandler>();
-            event_handler.update(events);
+            event_handler.update(frame.events.clone());
             event_handler.process();
+            resources
+                .get_mut::<Gamepads>()
+                .update(frame.gamepads.clone());
+            let events = resources.get::<EventHandler>().events.clone();
+            resources.get_mut::<Gestures>().update(&events, time);
+            let mut clipboard = if replayed {
+                FrameClipboard::replay(&mut window, frame.clipboard.take())
+            } else {
+                FrameClipboard::new(&mut window)
+            };
+            resources
+                .get_mut::<TextInput>()
+                .update(&events, &mut clipboard);
+            frame.clipboard = clipboard.pasted();
+
+            if let Some(Err(err)) = recorder.as_mut().map(|recorder| recorder.record(&frame)) {
+                eprintln!("Failed to record the input, the recording stops: {}", err);
+                recorder = None;
+            }
+
+            let screen = resources.get_mut::<Screen>();
+            screen.update(
+                frame.size,
+                frame.framebuffer_size,
+                window.get_content_scale(),
+            );
+            let resized = screen
+                .resized()
+                .filter(|_| has_gl_context && !screen.is_minimized());
+            if let Some(resized) = resized {
+                renderer::resize(
+                    &mut resources,
+                    (resized.framebuffer_width, resized.framebuffer_height),
+                );
+            }
         }
 
+        // the GL objects of the entities are deleted while the context still exists
+        self.world = World::default();
         Ok(())
     }
 }
 
-fn render(world: &mut World, resources: &mut ResourcesManager, window: &Window) {
-    glw::clear_color(0.2, 0.3, 0.3, 1.0);
-    glw::clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-
-    let shader = resources.get::<RenderShader>();
-    let (window_width, window_height) = window.get_size();
-
-    shader.bind();
-    for entity in world.entity_manager.active_entities() {
-        world.with_components_mut_2::<Object, Transform, _>(entity, |object, transform| {
-            if let (Some(obj), Some(transform)) = (object, transform) {
-                transform.rotation = transform.rotation.normalize();
-
-                let mut model_mat = Mat4::identity();
-                let projection_mat = Mat4::symmetric_perspective(
-                    45.0_f32.to_radians(),
-                    window_width as f32 / window_height as f32,
-                    0.1,
-                    1000.,
-                );
+///
+/// The next frame of the replay, the replay stops at the end of the recording or on an error
+///
+fn next_replayed_frame(replayer: &mut Option<Replayer<BufReader<File>>>) -> Option<RecordedFrame> {
+    match replayer.as_mut()?.next_frame() {
+        Ok(Some(frame)) => return Some(frame),
+        Ok(None) => eprintln!("The replay is over, the input comes from the window again"),
+        Err(err) => eprintln!("Failed to replay the input, the replay stops: {}", err),
+    }
+    *replayer = None;
+    None
+}
 
-                model_mat.scale(transform.scale);
-                model_mat.rotate_around_center(
-                    transform.center(obj.center()).negate(),
-                    transform.rotation,
-                );
-                model_mat.translate(transform.position);
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
 
-                shader
-                    .get_uniform_location("projection")
-                    .uniform_matrix4fv(&projection_mat);
-                shader
-                    .get_uniform_location("model")
-                    .uniform_matrix4fv(&model_mat);
+    use glfw::{Action, GamepadButton, Key, Modifiers, WindowEvent};
 
-                shader.get_uniform_location("object_texture").uniform1i(0);
+    use super::*;
+    use crate::graphics::{
+        headless::HeadlessContext, image, null_window::NullWindow, window::GamepadInput,
+    };
 
-                obj.draw();
-            }
-        });
+    /// Deltatime and whether Space was pressed, for each frame
+    type Frames = Rc<RefCell<Vec<(f32, bool)>>>;
+
+    /// Camera position and background color read at each frame, before they are changed
+    type Scenes = Rc<RefCell<Vec<(Vec3, Vec3)>>>;
+
+    struct SystemRecordFrames(Frames);
+    impl System for SystemRecordFrames {
+        fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+            let pressed = resources
+                .get::<EventHandler>()
+                .pressed(Key::Space, Modifiers::empty());
+            self.0
+                .borrow_mut()
+                .push((**resources.get::<Deltatime>(), pressed));
+        }
+    }
+
+    struct SystemMoveCamera(Scenes);
+    impl System for SystemMoveCamera {
+        fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+            let camera = resources.get_mut::<Camera>();
+            let position = camera.position;
+            camera.position.z += 1.0;
+            let background = resources.get_mut::<Background>();
+            let color = background.clear_color;
+            background.clear_color = Vec3::splat(position.z);
+            self.0.borrow_mut().push((position, color));
+        }
+    }
+
+    #[test]
+    fn it_should_run_without_a_display_server() {
+        let frames = Frames::default();
+        let scenes = Scenes::default();
+        let press = WindowEvent::Key(Key::Space, 0, Action::Press, Modifiers::empty());
+        let window = NullWindow::create(320, 240, "test")
+            .close_after(3)
+            .with_events(vec![vec![press]]);
+
+        Engine::new(320, 240, "test")
+            .run_with(
+                window,
+                &mut vec![
+                    Box::new(SystemRecordFrames(frames.clone())),
+                    Box::new(SystemMoveCamera(scenes.clone())),
+                ],
+            )
+            .expect("This should work");
+
+        // the scene resources are there without a GL context, and kept between frames
+        let scenes = scenes.borrow();
+        assert_eq!(
+            scenes
+                .iter()
+                .map(|(position, _)| position.z)
+                .collect::<Vec<f32>>(),
+            vec![0.0, 1.0, 2.0]
+        );
+        assert_eq!(scenes[1].1, Vec3::splat(0.0));
+        assert_eq!(scenes[2].1, Vec3::splat(1.0));
+
+        let frames = frames.borrow();
+        assert_eq!(frames.len(), 3);
+        // the events polled at the end of a frame are read by the next one
+        assert_eq!(
+            frames
+                .iter()
+                .map(|(_, pressed)| *pressed)
+                .collect::<Vec<bool>>(),
+            vec![false, true, false]
+        );
+        assert_eq!(frames[0].0, 0.0);
+        assert!((frames[1].0 - 1.0 / 60.0).abs() < 1e-6);
+    }
+
+    struct SystemReadGamepads(Rc<RefCell<Vec<bool>>>);
+    impl System for SystemReadGamepads {
+        fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+            let held = resources.get::<Gamepads>().held(GamepadButton::ButtonA);
+            self.0.borrow_mut().push(held);
+        }
+    }
+
+    #[test]
+    fn it_should_read_the_gamepads_of_the_window() {
+        let held = Rc::new(RefCell::new(Vec::new()));
+        let gamepad = GamepadInput {
+            id: 0,
+            name: "Pad".to_string(),
+            buttons: vec![GamepadButton::ButtonA],
+            axes: [0.0, 0.0, 0.0, 0.0, -1.0, -1.0],
+        };
+        let window = NullWindow::create(320, 240, "test")
+            .close_after(2)
+            .with_gamepads(vec![gamepad]);
+
+        Engine::new(320, 240, "test")
+            .run_with(
+                window,
+                &mut vec![Box::new(SystemReadGamepads(held.clone()))],
+            )
+            .expect("This should work");
+
+        // like the events, the gamepads polled at the end of a frame are read by the next one
+        assert_eq!(*held.borrow(), vec![false, true]);
+    }
+
+    struct SystemScreenshot(String);
+    impl System for SystemScreenshot {
+        fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+            resources.get_mut::<Background>().clear_color = Vec3::new(1.0, 0.0, 0.0);
+            resources.get_mut::<FrameCapture>().screenshot(&self.0);
+        }
+    }
+
+    #[test]
+    fn it_should_render_into_the_current_gl_context() {
+        let Some(_context) = HeadlessContext::for_test(64, 64) else {
+            return;
+        };
+        // the shaders are loaded relative to the workspace root, like the game does
+        std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+            .expect("Workspace root to exist");
+        let path = std::env::temp_dir()
+            .join(format!("basis_engine_{}.png", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let window = NullWindow::create(64, 64, "test")
+            .close_after(1)
+            .with_current_gl_context();
+
+        Engine::new(64, 64, "test")
+            .run_with(window, &mut vec![Box::new(SystemScreenshot(path.clone()))])
+            .expect("This should work");
+
+        let image = image::load(&path).expect("This should work");
+        std::fs::remove_file(&path).expect("This should work");
+        let [red, green, blue, _] = image.pixel(32, 32);
+        assert!(red > 128 && green < 64 && blue < 64);
     }
-    shader.unbind();
 }
@@ -0,0 +1,591 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+};
+
+use glfw::{Action, Key, Modifiers, MouseButton, WindowEvent};
+
+use crate::{graphics::window::GAMEPAD_BUTTONS, prelude::*};
+
+const MAGIC: &[u8; 4] = b"BREC";
+const VERSION: u8 = 2;
+
+/// Written instead of the gamepads when they did not change since the previous frame
+const SAME_GAMEPADS: u8 = u8::MAX;
+
+const KEY_EVENT: u8 = 0;
+const MOUSE_BUTTON_EVENT: u8 = 1;
+const CURSOR_POS_EVENT: u8 = 2;
+const SCROLL_EVENT: u8 = 3;
+const FOCUS_EVENT: u8 = 4;
+const CHAR_EVENT: u8 = 5;
+
+///
+/// Input of one frame of the `Engine`, everything needed to play it again exactly
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub deltatime: f32,
+    /// Seconds since the window was created, given to the renderer and the `Gestures`
+    pub time: f64,
+    /// The window events read by the input resources, the others are not recorded
+    pub events: Vec<WindowEvent>,
+    pub gamepads: Vec<GamepadInput>,
+    /// Size of the window given to the `Screen`, in screen coordinates
+    pub size: (i32, i32),
+    /// Size of the framebuffer given to the `Screen`, in pixels
+    pub framebuffer_size: (i32, i32),
+    /// Text read from the clipboard by the `TextInput`, when something was pasted
+    pub clipboard: Option<String>,
+}
+
+///
+/// Clipboard given to the `TextInput` while recording or replaying. It keeps the text pasted
+/// during the frame so it is recorded, and gives back the recorded text instead of reading the
+/// window clipboard when replaying.
+///
+pub struct FrameClipboard<'a, C: Clipboard> {
+    clipboard: &'a mut C,
+    replayed: bool,
+    pasted: Option<String>,
+}
+
+impl<'a, C: Clipboard> FrameClipboard<'a, C> {
+    pub fn new(clipboard: &'a mut C) -> Self {
+        Self {
+            clipboard,
+            replayed: false,
+            pasted: None,
+        }
+    }
+
+    ///
+    /// Pastes `pasted` instead of the content of `clipboard`
+    ///
+    pub fn replay(clipboard: &'a mut C, pasted: Option<String>) -> Self {
+        Self {
+            clipboard,
+            replayed: true,
+            pasted,
+        }
+    }
+
+    ///
+    /// The text pasted during the frame
+    ///
+    pub fn pasted(self) -> Option<String> {
+        self.pasted
+    }
+}
+
+impl<C: Clipboard> Clipboard for FrameClipboard<'_, C> {
+    fn get(&mut self) -> Option<String> {
+        if !self.replayed {
+            self.pasted = self.clipboard.get();
+        }
+        self.pasted.clone()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.clipboard.set(text);
+    }
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    Format(String),
+}
+impl std::error::Error for RecordingError {}
+
+impl From<io::Error> for RecordingError {
+    fn from(err: io::Error) -> Self {
+        RecordingError::Io(err)
+    }
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Io(err) => write!(f, "Failed to access the recording: {}", err),
+            RecordingError::Format(message) => write!(f, "Invalid recording: {}", message),
+        }
+    }
+}
+
+///
+/// Writes the frames of a run, in a binary format: a header with the seed of the `Random`
+/// resource, then for each frame its deltatime, time, events, gamepads, window sizes and
+/// pasted text. The frames are flushed as they are recorded, so a crash keeps everything up to
+/// the frame that crashed.
+///
+pub struct Recorder<W: Write> {
+    writer: W,
+    previous_gamepads: Vec<GamepadInput>,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create(path: &str, seed: u64) -> io::Result<Self> {
+        Recorder::new(BufWriter::new(File::create(path)?), seed)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(mut writer: W, seed: u64) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&seed.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            previous_gamepads: Vec::new(),
+        })
+    }
+
+    pub fn record(&mut self, frame: &RecordedFrame) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        bytes.extend(frame.deltatime.to_le_bytes());
+        bytes.extend(frame.time.to_le_bytes());
+
+        let events = frame
+            .events
+            .iter()
+            .filter_map(encode_event)
+            .collect::<Vec<Vec<u8>>>();
+        bytes.extend((events.len() as u32).to_le_bytes());
+        bytes.extend(events.concat());
+
+        if frame.gamepads == self.previous_gamepads {
+            bytes.push(SAME_GAMEPADS);
+        } else {
+            bytes.push(frame.gamepads.len() as u8);
+            for gamepad in &frame.gamepads {
+                encode_gamepad(&mut bytes, gamepad);
+            }
+            self.previous_gamepads = frame.gamepads.clone();
+        }
+
+        for value in [
+            frame.size.0,
+            frame.size.1,
+            frame.framebuffer_size.0,
+            frame.framebuffer_size.1,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        match &frame.clipboard {
+            Some(text) => {
+                bytes.push(1);
+                bytes.extend((text.len() as u32).to_le_bytes());
+                bytes.extend(text.bytes());
+            }
+            None => bytes.push(0),
+        }
+
+        self.writer.write_all(&bytes)?;
+        self.writer.flush()
+    }
+}
+
+///
+/// Reads back the frames written by a `Recorder`
+///
+pub struct Replayer<R: Read> {
+    reader: R,
+    seed: u64,
+    previous_gamepads: Vec<GamepadInput>,
+}
+
+impl Replayer<BufReader<File>> {
+    pub fn open(path: &str) -> Result<Self, RecordingError> {
+        Replayer::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> Replayer<R> {
+    pub fn new(mut reader: R) -> Result<Self, RecordingError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(RecordingError::Format("not a recording".to_string()));
+        }
+        let version = read_u8(&mut reader)?;
+        if version != VERSION {
+            return Err(RecordingError::Format(format!(
+                "unsupported version {}",
+                version
+            )));
+        }
+        let seed = u64::from_le_bytes(read_array(&mut reader)?);
+
+        Ok(Self {
+            reader,
+            seed,
+            previous_gamepads: Vec::new(),
+        })
+    }
+
+    ///
+    /// Seed of the `Random` resource during the recorded run
+    ///
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    ///
+    /// The next frame, `None` once the recording is over
+    ///
+    pub fn next_frame(&mut self) -> Result<Option<RecordedFrame>, RecordingError> {
+        let mut deltatime = [0; 4];
+        match self.reader.read(&mut deltatime)? {
+            0 => return Ok(None),
+            4 => {}
+            read => self.reader.read_exact(&mut deltatime[read..])?,
+        }
+        let deltatime = f32::from_le_bytes(deltatime);
+        let time = f64::from_le_bytes(read_array(&mut self.reader)?);
+
+        let count = u32::from_le_bytes(read_array(&mut self.reader)?);
+        let events = (0..count)
+            .map(|_| decode_event(&mut self.reader))
+            .collect::<Result<Vec<WindowEvent>, RecordingError>>()?;
+
+        let count = read_u8(&mut self.reader)?;
+        if count != SAME_GAMEPADS {
+            self.previous_gamepads = (0..count)
+                .map(|_| decode_gamepad(&mut self.reader))
+                .collect::<Result<Vec<GamepadInput>, RecordingError>>()?;
+        }
+
+        let size = (read_i32(&mut self.reader)?, read_i32(&mut self.reader)?);
+        let framebuffer_size = (read_i32(&mut self.reader)?, read_i32(&mut self.reader)?);
+
+        let clipboard = match read_u8(&mut self.reader)? {
+            0 => None,
+            _ => {
+                let mut text = vec![0; u32::from_le_bytes(read_array(&mut self.reader)?) as usize];
+                self.reader.read_exact(&mut text)?;
+                Some(
+                    String::from_utf8(text).map_err(|_| {
+                        RecordingError::Format("invalid clipboard text".to_string())
+                    })?,
+                )
+            }
+        };
+
+        Ok(Some(RecordedFrame {
+            deltatime,
+            time,
+            events,
+            gamepads: self.previous_gamepads.clone(),
+            size,
+            framebuffer_size,
+            clipboard,
+        }))
+    }
+}
+
+fn encode_event(event: &WindowEvent) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    match *event {
+        WindowEvent::Key(key, scancode, action, modifiers) => {
+            bytes.push(KEY_EVENT);
+            bytes.extend((key as i16).to_le_bytes());
+            bytes.extend(scancode.to_le_bytes());
+            bytes.push(encode_action(action));
+            bytes.push(modifiers.bits() as u8);
+        }
+        WindowEvent::MouseButton(button, action, modifiers) => {
+            bytes.push(MOUSE_BUTTON_EVENT);
+            bytes.push(button as u8);
+            bytes.push(encode_action(action));
+            bytes.push(modifiers.bits() as u8);
+        }
+        WindowEvent::CursorPos(x, y) => {
+            bytes.push(CURSOR_POS_EVENT);
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+        }
+        WindowEvent::Scroll(x, y) => {
+            bytes.push(SCROLL_EVENT);
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+        }
+        WindowEvent::Focus(focused) => {
+            bytes.push(FOCUS_EVENT);
+            bytes.push(focused as u8);
+        }
+        WindowEvent::Char(c) => {
+            bytes.push(CHAR_EVENT);
+            bytes.extend((c as u32).to_le_bytes());
+        }
+        _ => return None,
+    }
+    Some(bytes)
+}
+
+fn decode_event(reader: &mut impl Read) -> Result<WindowEvent, RecordingError> {
+    let event = match read_u8(reader)? {
+        KEY_EVENT => {
+            let key = i16::from_le_bytes(read_array(reader)?) as i32;
+            let key = KEYS
+                .iter()
+                .map(|(key, _)| *key)
+                .find(|candidate| *candidate as i32 == key)
+                .unwrap_or(Key::Unknown);
+            let scancode = i32::from_le_bytes(read_array(reader)?);
+            WindowEvent::Key(
+                key,
+                scancode,
+                decode_action(reader)?,
+                decode_modifiers(reader)?,
+            )
+        }
+        MOUSE_BUTTON_EVENT => {
+            let button = read_u8(reader)?;
+            let button = MouseButton::from_i32(button as i32).ok_or_else(|| {
+                RecordingError::Format(format!("unknown mouse button {}", button))
+            })?;
+            WindowEvent::MouseButton(button, decode_action(reader)?, decode_modifiers(reader)?)
+        }
+        CURSOR_POS_EVENT => WindowEvent::CursorPos(
+            f64::from_le_bytes(read_array(reader)?),
+            f64::from_le_bytes(read_array(reader)?),
+        ),
+        SCROLL_EVENT => WindowEvent::Scroll(
+            f64::from_le_bytes(read_array(reader)?),
+            f64::from_le_bytes(read_array(reader)?),
+        ),
+        FOCUS_EVENT => WindowEvent::Focus(read_u8(reader)? != 0),
+        CHAR_EVENT => {
+            let c = u32::from_le_bytes(read_array(reader)?);
+            WindowEvent::Char(
+                char::from_u32(c)
+                    .ok_or_else(|| RecordingError::Format(format!("invalid char {}", c)))?,
+            )
+        }
+        tag => return Err(RecordingError::Format(format!("unknown event {}", tag))),
+    };
+    Ok(event)
+}
+
+fn encode_action(action: Action) -> u8 {
+    match action {
+        Action::Release => 0,
+        Action::Press => 1,
+        Action::Repeat => 2,
+    }
+}
+
+fn decode_action(reader: &mut impl Read) -> Result<Action, RecordingError> {
+    match read_u8(reader)? {
+        0 => Ok(Action::Release),
+        1 => Ok(Action::Press),
+        2 => Ok(Action::Repeat),
+        action => Err(RecordingError::Format(format!("unknown action {}", action))),
+    }
+}
+
+fn decode_modifiers(reader: &mut impl Read) -> Result<Modifiers, RecordingError> {
+    Ok(Modifiers::from_bits_truncate(read_u8(reader)? as i32))
+}
+
+///
+/// The buttons are a bit mask in the order of `GAMEPAD_BUTTONS`
+///
+fn encode_gamepad(bytes: &mut Vec<u8>, gamepad: &GamepadInput) {
+    bytes.push(gamepad.id as u8);
+    bytes.push(gamepad.name.len().min(u8::MAX as usize) as u8);
+    bytes.extend(gamepad.name.bytes().take(u8::MAX as usize));
+
+    let mut buttons = 0u16;
+    for (index, button) in GAMEPAD_BUTTONS.iter().enumerate() {
+        if gamepad.buttons.contains(button) {
+            buttons |= 1 << index;
+        }
+    }
+    bytes.extend(buttons.to_le_bytes());
+
+    for axis in gamepad.axes {
+        bytes.extend(axis.to_le_bytes());
+    }
+}
+
+fn decode_gamepad(reader: &mut impl Read) -> Result<GamepadInput, RecordingError> {
+    let id = read_u8(reader)? as usize;
+    let mut name = vec![0; read_u8(reader)? as usize];
+    reader.read_exact(&mut name)?;
+
+    let buttons = u16::from_le_bytes(read_array(reader)?);
+    let buttons = GAMEPAD_BUTTONS
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| buttons & (1 << index) != 0)
+        .map(|(_, button)| button)
+        .collect();
+
+    let mut axes = [0.0; 6];
+    for axis in axes.iter_mut() {
+        *axis = f32::from_le_bytes(read_array(reader)?);
+    }
+
+    Ok(GamepadInput {
+        id,
+        name: String::from_utf8_lossy(&name).into_owned(),
+        buttons,
+        axes,
+    })
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    Ok(read_array::<1>(reader)?[0])
+}
+
+fn read_i32(reader: &mut impl Read) -> io::Result<i32> {
+    Ok(i32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use glfw::GamepadButton;
+
+    use super::*;
+
+    fn frame(
+        deltatime: f32,
+        events: Vec<WindowEvent>,
+        gamepads: Vec<GamepadInput>,
+    ) -> RecordedFrame {
+        RecordedFrame {
+            deltatime,
+            time: deltatime as f64 * 10.0,
+            events,
+            gamepads,
+            size: (800, 600),
+            framebuffer_size: (1600, 1200),
+            clipboard: None,
+        }
+    }
+
+    #[test]
+    fn it_should_replay_the_recorded_frames() {
+        let pad = GamepadInput {
+            id: 1,
+            name: "Pad".to_string(),
+            buttons: vec![GamepadButton::ButtonA, GamepadButton::ButtonDpadLeft],
+            axes: [0.5, -0.25, 0.0, 0.0, -1.0, 1.0],
+        };
+        let frames = vec![
+            frame(
+                0.016,
+                vec![
+                    WindowEvent::Key(Key::W, 17, Action::Press, Modifiers::Control),
+                    WindowEvent::MouseButton(
+                        MouseButton::Button2,
+                        Action::Release,
+                        Modifiers::empty(),
+                    ),
+                    WindowEvent::CursorPos(12.5, 300.0),
+                    WindowEvent::Scroll(0.0, -1.0),
+                    WindowEvent::Focus(false),
+                    WindowEvent::Char('é'),
+                ],
+                vec![],
+            ),
+            frame(0.017, vec![], vec![pad.clone()]),
+            RecordedFrame {
+                size: (1024, 768),
+                framebuffer_size: (1024, 768),
+                clipboard: Some("Zoé".to_string()),
+                ..frame(0.015, vec![], vec![pad])
+            },
+            frame(0.016, vec![], vec![]),
+        ];
+
+        let mut bytes = Vec::new();
+        let mut recorder = Recorder::new(&mut bytes, 42).expect("This should work");
+        for frame in &frames {
+            recorder.record(frame).expect("This should work");
+        }
+
+        let mut replayer = Replayer::new(bytes.as_slice()).expect("This should work");
+        assert_eq!(replayer.seed(), 42);
+        for frame in frames {
+            assert_eq!(
+                replayer.next_frame().expect("This should work"),
+                Some(frame)
+            );
+        }
+        assert_eq!(replayer.next_frame().expect("This should work"), None);
+    }
+
+    #[test]
+    fn it_should_not_record_the_other_events() {
+        let mut bytes = Vec::new();
+        let mut recorder = Recorder::new(&mut bytes, 0).expect("This should work");
+        recorder
+            .record(&frame(
+                0.016,
+                vec![
+                    WindowEvent::FramebufferSize(800, 600),
+                    WindowEvent::Focus(true),
+                ],
+                vec![],
+            ))
+            .expect("This should work");
+
+        let mut replayer = Replayer::new(bytes.as_slice()).expect("This should work");
+        let frame = replayer.next_frame().expect("This should work").unwrap();
+        assert_eq!(frame.events, vec![WindowEvent::Focus(true)]);
+    }
+
+    #[test]
+    fn it_should_paste_the_recorded_clipboard_text() {
+        struct TestClipboard(Option<String>);
+        impl Clipboard for TestClipboard {
+            fn get(&mut self) -> Option<String> {
+                self.0.clone()
+            }
+
+            fn set(&mut self, text: &str) {
+                self.0 = Some(text.to_string());
+            }
+        }
+
+        let mut window_clipboard = TestClipboard(Some("live".to_string()));
+        let mut clipboard = FrameClipboard::new(&mut window_clipboard);
+        assert_eq!(clipboard.get().as_deref(), Some("live"));
+        assert_eq!(clipboard.pasted().as_deref(), Some("live"));
+
+        let mut clipboard =
+            FrameClipboard::replay(&mut window_clipboard, Some("recorded".to_string()));
+        assert_eq!(clipboard.get().as_deref(), Some("recorded"));
+        clipboard.set("copied");
+        assert_eq!(window_clipboard.0.as_deref(), Some("copied"));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_recordings() {
+        assert!(matches!(
+            Replayer::new(&b"PNG\0\x01"[..]),
+            Err(RecordingError::Format(_))
+        ));
+
+        let mut bytes = Vec::new();
+        let mut recorder = Recorder::new(&mut bytes, 0).expect("This should work");
+        recorder
+            .record(&frame(0.016, vec![WindowEvent::Focus(true)], vec![]))
+            .expect("This should work");
+        bytes.truncate(bytes.len() - 2);
+
+        let mut replayer = Replayer::new(bytes.as_slice()).expect("This should work");
+        assert!(matches!(replayer.next_frame(), Err(RecordingError::Io(_))));
+    }
+}
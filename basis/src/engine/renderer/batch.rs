@@ -0,0 +1,163 @@
+use crate::graphics::glw;
+use crate::prelude::*;
+
+///
+/// All the entities that share the same mesh, collected so they can be drawn with a single
+/// instanced draw call.
+///
+#[derive(Debug)]
+pub struct RenderBatch {
+    pub mesh: MeshHandle,
+    pub models: Vec<Mat4>,
+}
+
+impl RenderBatch {
+    pub fn draw_opaque(&self, shader: &glw::Shader) {
+        self.mesh.draw_opaque_instanced(shader, &self.models);
+    }
+}
+
+///
+/// A transparent submesh of one instance, transparent geometry is blended with what is behind
+/// it so it has to be drawn after the opaque geometry and from the farthest to the nearest.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransparentDraw {
+    pub batch: usize,
+    pub submesh: usize,
+    pub instance: usize,
+    /// Distance from the center of the submesh instance to the camera
+    pub distance: f32,
+}
+
+impl TransparentDraw {
+    pub fn draw(&self, shader: &glw::Shader, batches: &[RenderBatch]) {
+        let batch = &batches[self.batch];
+        batch
+            .mesh
+            .draw_submesh(shader, self.submesh, &batch.models[self.instance]);
+    }
+}
+
+///
+/// Groups every entity with an `Object` and a `Transform` by mesh, keeping the order in which
+/// each mesh was first found.
+///
+pub fn collect_batches(world: &World) -> Vec<RenderBatch> {
+    let mut batches: Vec<RenderBatch> = Vec::new();
+
+    for entity in world.entity_manager.active_entities() {
+        world.with_components_mut_2::<Object, Transform, _>(entity, |object, transform| {
+            if let (Some(obj), Some(transform)) = (object, transform) {
+                transform.rotation = transform.rotation.normalize();
+
+                let mesh = obj.mesh();
+                let model_mat = transform.model_matrix(obj.center());
+
+                match batches.iter_mut().find(|batch| batch.mesh == mesh) {
+                    Some(batch) => batch.models.push(model_mat),
+                    None => batches.push(RenderBatch {
+                        mesh,
+                        models: vec![model_mat],
+                    }),
+                }
+            }
+        });
+    }
+
+    batches
+}
+
+///
+/// Every transparent submesh instance, sorted back to front from `camera_position`. The
+/// distance is taken from the center of the bounds of each submesh, so the parts of a model
+/// are sorted between themselves too.
+///
+pub fn collect_transparent_draws(
+    batches: &[RenderBatch],
+    camera_position: Vec3,
+) -> Vec<TransparentDraw> {
+    let mut draws = Vec::new();
+
+    for (batch_index, batch) in batches.iter().enumerate() {
+        for (submesh_index, submesh) in batch.mesh.submeshes().iter().enumerate() {
+            if !submesh.is_transparent() {
+                continue;
+            }
+            for (instance, model) in batch.models.iter().enumerate() {
+                draws.push(TransparentDraw {
+                    batch: batch_index,
+                    submesh: submesh_index,
+                    instance,
+                    distance: world_position(model, submesh.center).distance(camera_position),
+                });
+            }
+        }
+    }
+
+    sort_back_to_front(&mut draws);
+    draws
+}
+
+fn world_position(model: &Mat4, position: Vec3) -> Vec3 {
+    let world = *model * Vec4::new(position.x, position.y, position.z, 1.0);
+    Vec3::new(world.x, world.y, world.z)
+}
+
+pub fn sort_back_to_front(draws: &mut [TransparentDraw]) {
+    draws.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transparent_draw(batch: usize, distance: f32) -> TransparentDraw {
+        TransparentDraw {
+            batch,
+            submesh: 0,
+            instance: 0,
+            distance,
+        }
+    }
+
+    #[test]
+    fn it_should_sort_transparent_draws_back_to_front() {
+        let mut draws = vec![
+            transparent_draw(0, 2.0),
+            transparent_draw(1, 10.0),
+            transparent_draw(2, 0.5),
+            transparent_draw(3, 5.0),
+        ];
+
+        sort_back_to_front(&mut draws);
+
+        let order = draws.iter().map(|draw| draw.batch).collect::<Vec<usize>>();
+        assert_eq!(order, vec![1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn it_should_move_the_submesh_center_with_the_model() {
+        let mut model = Mat4::identity();
+        model.scale(Vec3::splat(2.0));
+        model.translate(Vec3::new(0.0, 0.0, -10.0));
+
+        let center = world_position(&model, Vec3::new(1.0, 0.5, 0.0));
+
+        assert_eq!(center, Vec3::new(2.0, 1.0, -10.0));
+    }
+
+    #[test]
+    fn it_should_keep_order_of_equally_distant_draws() {
+        let mut draws = vec![
+            transparent_draw(0, 1.0),
+            transparent_draw(1, 3.0),
+            transparent_draw(2, 1.0),
+        ];
+
+        sort_back_to_front(&mut draws);
+
+        let order = draws.iter().map(|draw| draw.batch).collect::<Vec<usize>>();
+        assert_eq!(order, vec![1, 0, 2]);
+    }
+}
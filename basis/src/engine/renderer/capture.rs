@@ -0,0 +1,50 @@
+use crate::graphics::{
+    glw,
+    image::{Image, SaveImageError},
+};
+use crate::prelude::*;
+
+///
+/// Reads the pixels of the bound framebuffer, the first row of the image is the top one
+///
+pub fn read_framebuffer(width: i32, height: i32) -> Image {
+    let mut pixels = vec![0u8; (width.max(0) * height.max(0) * 4) as usize];
+
+    glw::pixel_storei(gl::PACK_ALIGNMENT, 1);
+    glw::read_pixels(
+        0,
+        0,
+        width,
+        height,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        pixels.as_mut_ptr() as *mut std::ffi::c_void,
+    );
+
+    let mut image = Image::new(width as u32, height as u32, pixels);
+    // OpenGL reads from the bottom row
+    image.flip_vertically();
+    image
+}
+
+///
+/// Saves the frame rendered in the window to every path requested for it
+///
+pub fn capture_frame(
+    frame_capture: &mut FrameCapture,
+    width: i32,
+    height: i32,
+) -> Result<(), SaveImageError> {
+    if !frame_capture.has_pending_captures() {
+        return Ok(());
+    }
+
+    let image = read_framebuffer(width, height);
+    for path in frame_capture.next_frame_paths() {
+        if let Some(directory) = std::path::Path::new(&path).parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        image.save(&path)?;
+    }
+    Ok(())
+}
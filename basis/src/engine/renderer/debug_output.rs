@@ -0,0 +1,15 @@
+use crate::graphics::glw;
+
+///
+/// Prints the messages of the GL debug output, notifications are skipped as some drivers send
+/// one for every buffer allocation. Does nothing when the driver lacks `KHR_debug`.
+///
+pub fn enable_debug_output() {
+    if !glw::debug_message_callback(|message| {
+        if !message.is_notification() {
+            eprintln!("{}", message);
+        }
+    }) {
+        eprintln!("GL debug output is not supported, only glGetError checks are available");
+    }
+}
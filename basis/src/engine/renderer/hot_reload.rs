@@ -0,0 +1,36 @@
+use crate::graphics::shader_program::ShaderProgram;
+use crate::prelude::*;
+
+///
+/// Rebuilds the programs whose sources changed since the last poll of the `ShaderWatcher`.
+/// Errors are printed with the GL info log, the previous program keeps being used.
+///
+pub fn reload_shaders(resources: &mut ResourcesManager, time: f32) {
+    if !resources.get_mut::<ShaderWatcher>().should_poll(time) {
+        return;
+    }
+
+    reload(resources.get_mut::<RenderShader>());
+    reload(&mut resources.get_mut::<ShadowMap>().shader);
+    if let Some(skybox) = resources.get_mut::<Background>().skybox.as_mut() {
+        reload(skybox.shader_mut());
+    }
+    for shader in resources.get_mut::<PostProcessStack>().shaders_mut() {
+        reload(shader);
+    }
+}
+
+fn reload(program: &mut ShaderProgram) {
+    let paths = program
+        .stages()
+        .iter()
+        .map(|stage| stage.path.as_str())
+        .collect::<Vec<&str>>()
+        .join("', '");
+
+    match program.reload_if_changed() {
+        Ok(true) => println!("Reloaded shader '{}'", paths),
+        Ok(false) => {}
+        Err(err) => eprintln!("Failed to reload shader, keeping the previous one: {}", err),
+    }
+}
@@ -0,0 +1,447 @@
+mod batch;
+mod capture;
+mod debug_output;
+mod hot_reload;
+mod post_process;
+mod shadow;
+
+use crate::graphics::glw;
+use crate::prelude::*;
+
+pub use batch::*;
+pub use capture::*;
+pub use debug_output::*;
+pub use hot_reload::*;
+pub use post_process::*;
+pub use shadow::*;
+
+/// Far plane of the projection when the fog does not hide the geometry sooner
+const FAR_PLANE: f32 = 1000.0;
+
+///
+/// Adds the resources describing what `render` draws, they hold no GL object so the systems
+/// can use them without a GL context
+///
+pub fn init_scene(resources: &mut ResourcesManager) {
+    resources.add::<Camera>(Camera::default());
+    resources.add::<Background>(Background::default());
+    resources.add::<Fog>(Fog::default());
+    resources.add::<PostProcessConfig>(PostProcessConfig::default());
+}
+
+///
+/// Sets up the GL state and adds the GL objects used by `render`, a GL context must be current
+/// and `init_scene` called before
+///
+pub fn init(resources: &mut ResourcesManager, framebuffer_size: (i32, i32)) {
+    if cfg!(debug_assertions) {
+        enable_debug_output();
+    }
+    glw::enable(gl::DEPTH_TEST);
+
+    resources.add::<RenderShader>(RenderShader::default());
+    resources.add::<ShaderWatcher>(ShaderWatcher::default());
+    resources.add::<FrameUniforms>(FrameUniforms::default());
+    resources.add::<ShadowMap>(ShadowMap::default());
+    resources.add::<PostProcessStack>(PostProcessStack::new(
+        framebuffer_size.0,
+        framebuffer_size.1,
+    ));
+    resize(resources, framebuffer_size);
+}
+
+///
+/// Makes the viewport and the post-processing render targets match the framebuffer of the
+/// window, after it was resized
+///
+pub fn resize(resources: &mut ResourcesManager, framebuffer_size: (i32, i32)) {
+    let (framebuffer_width, framebuffer_height) = framebuffer_size;
+    glw::viewport(0, 0, framebuffer_width, framebuffer_height);
+    resources
+        .get_mut::<PostProcessStack>()
+        .resize(framebuffer_width, framebuffer_height);
+}
+
+///
+/// Draws the world into the default framebuffer
+///
+/// * `framebuffer_size` - Size in pixels of the default framebuffer, given to `resize` before
+/// * `time` - Seconds since the start, used by the animated effects
+///
+pub fn render(
+    world: &mut World,
+    resources: &mut ResourcesManager,
+    framebuffer_size: (i32, i32),
+    time: f32,
+) {
+    let (framebuffer_width, framebuffer_height) = framebuffer_size;
+    let shader = resources.get::<RenderShader>();
+    let camera = resources.get::<Camera>();
+    let shadow_map = resources.get::<ShadowMap>();
+    let post_process = resources.get::<PostProcessStack>();
+    let post_process_config = resources.get::<PostProcessConfig>();
+    let background = resources.get::<Background>();
+    let fog = resources.get::<Fog>();
+    let frame_uniforms = resources.get::<FrameUniforms>();
+
+    let projection_mat = Mat4::symmetric_perspective(
+        45.0_f32.to_radians(),
+        framebuffer_width as f32 / framebuffer_height as f32,
+        0.1,
+        fog.far_plane(FAR_PLANE),
+    );
+
+    let batches = collect_batches(world);
+    let transparent_draws = collect_transparent_draws(&batches, camera.position);
+
+    let light_space = find_shadow_light(world).map(|light| {
+        let light_space = light.light_space_matrix(camera.position);
+        render_shadow_map(&batches, shadow_map, &light_space);
+        light_space
+    });
+
+    post_process.scene.bind();
+    // far geometry fades into the fog, the background must be of the same color
+    let clear_color = if fog.enabled {
+        fog.color
+    } else {
+        background.clear_color
+    };
+    glw::clear_color(clear_color.x, clear_color.y, clear_color.z, 1.0);
+    glw::clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+    // shared by every program reading the `Frame` block
+    frame_uniforms.0.upload(&FrameData {
+        view: camera.view,
+        projection: projection_mat,
+        light_space: light_space.unwrap_or(Mat4::identity()),
+        camera_position: camera.position,
+        shadows_enabled: light_space.is_some(),
+    });
+
+    shader.bind();
+    shader.get_uniform_location("object_texture").uniform1i(0);
+    shader.get_uniform_location("shadow_map").uniform1i(1);
+    fog.set_uniforms(shader);
+    shadow_map.depth_texture.active(gl::TEXTURE1);
+    shadow_map.depth_texture.bind();
+
+    for batch in batches.iter() {
+        batch.draw_opaque(shader);
+    }
+
+    // drawn after the opaque geometry so only the visible part of the sky is shaded
+    if let Some(skybox) = background.skybox.as_ref() {
+        skybox.draw(fog);
+        shader.bind();
+    }
+
+    // transparent geometry is tested against the depth buffer but does not write into it, so
+    // surfaces behind it are still blended when drawn back to front
+    glw::enable(gl::BLEND);
+    glw::blend_func(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+    glw::depth_mask(false);
+    for draw in transparent_draws.iter() {
+        draw.draw(shader, &batches);
+    }
+    glw::depth_mask(true);
+    glw::disable(gl::BLEND);
+
+    shader.unbind();
+    post_process.scene.unbind();
+
+    apply_post_process(post_process, post_process_config, time);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Once;
+
+    use super::*;
+    use crate::graphics::{
+        headless::HeadlessContext,
+        image::{self, Image},
+        wavefront,
+    };
+
+    const SIZE: i32 = 128;
+    /// Biggest difference allowed between the channels of a pixel and its reference, the
+    /// rasterization slightly differs between drivers
+    const CHANNEL_TOLERANCE: u8 = 8;
+    /// Ratio of the pixels allowed to differ more than `CHANNEL_TOLERANCE`
+    const MISMATCH_TOLERANCE: f32 = 0.01;
+
+    static WORKSPACE_ROOT: Once = Once::new();
+
+    fn golden_path(name: &str) -> String {
+        format!(
+            "{}/src/assets/golden/{}.png",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        )
+    }
+
+    fn model_path(name: &str) -> String {
+        format!("{}/src/assets/models/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn mismatch_ratio(actual: &Image, expected: &Image) -> f32 {
+        if (actual.width, actual.height) != (expected.width, expected.height) {
+            return 1.0;
+        }
+
+        let mismatches = actual
+            .pixels
+            .chunks(4)
+            .zip(expected.pixels.chunks(4))
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+            })
+            .count();
+
+        mismatches as f32 / (actual.width * actual.height) as f32
+    }
+
+    ///
+    /// The shaders are loaded relative to the workspace root, like the game does
+    ///
+    fn enter_workspace_root() {
+        WORKSPACE_ROOT.call_once(|| {
+            std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+                .expect("Workspace root to exist")
+        });
+    }
+
+    ///
+    /// Renders a single frame of the world built by `setup`, `None` when the OpenGL tests
+    /// are skipped
+    ///
+    fn render_scene(setup: impl FnOnce(&mut World, &mut ResourcesManager)) -> Option<Image> {
+        let context = HeadlessContext::for_test(SIZE, SIZE)?;
+        enter_workspace_root();
+
+        let mut world = World::default();
+        let mut resources = ResourcesManager::default();
+        init_scene(&mut resources);
+        init(&mut resources, (SIZE, SIZE));
+        setup(&mut world, &mut resources);
+
+        render(&mut world, &mut resources, (SIZE, SIZE), 0.0);
+        let image = read_framebuffer(SIZE, SIZE);
+
+        // the GL objects are deleted on drop, the context must still be current
+        drop(world);
+        drop(resources);
+        drop(context);
+        Some(image)
+    }
+
+    ///
+    /// Compares the image with its reference, run the tests with `UPDATE_GOLDEN=1` to write
+    /// the references instead
+    ///
+    fn assert_golden(name: &str, image: &Image) {
+        let path = golden_path(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            image.save(&path).expect("Reference image to be written");
+            return;
+        }
+
+        let expected = image::load(&path).unwrap_or_else(|err| {
+            panic!(
+                "Failed to load '{}' ({}), run with UPDATE_GOLDEN=1 to create it",
+                path, err
+            )
+        });
+        let ratio = mismatch_ratio(image, &expected);
+        if ratio > MISMATCH_TOLERANCE {
+            let actual_path = std::env::temp_dir().join(format!("{}.actual.png", name));
+            let actual_path = actual_path.to_string_lossy().to_string();
+            image
+                .save(&actual_path)
+                .expect("Actual image to be written");
+            panic!(
+                "{:.2}% of the pixels differ from '{}', the rendered image is '{}'",
+                ratio * 100.0,
+                path,
+                actual_path
+            );
+        }
+    }
+
+    fn spawn_model(world: &mut World, file: &str, transform: Transform) {
+        let model = wavefront::obj::load(&model_path(file)).expect("This should work");
+        let entity = world.spawn();
+        world.add_entity_component(entity, Object::new(model));
+        world.add_entity_component(entity, transform);
+    }
+
+    ///
+    /// Unrotated and unscaled transform
+    ///
+    fn transform_at(position: Vec3) -> Transform {
+        Transform {
+            position,
+            rotation: Quaternion::default(),
+            scale: Vec3::splat(1.0),
+        }
+    }
+
+    fn look_at(resources: &mut ResourcesManager, position: Vec3, target: Vec3) {
+        *resources.get_mut::<Camera>() = Camera {
+            view: Mat4::look_at(position, target, Vec3::new(0.0, 1.0, 0.0)),
+            position,
+        };
+    }
+
+    #[test]
+    fn it_should_compare_images_with_tolerance() {
+        let reference = Image::new(2, 1, vec![100, 100, 100, 255, 0, 0, 0, 255]);
+        let close = Image::new(2, 1, vec![108, 92, 100, 255, 0, 0, 0, 255]);
+        let different = Image::new(2, 1, vec![109, 100, 100, 255, 0, 0, 0, 255]);
+
+        assert_eq!(mismatch_ratio(&close, &reference), 0.0);
+        assert_eq!(mismatch_ratio(&different, &reference), 0.5);
+        assert_eq!(mismatch_ratio(&Image::splat([0; 4]), &reference), 1.0);
+    }
+
+    #[test]
+    fn it_should_release_every_gl_object() {
+        let Some(_context) = HeadlessContext::for_test(SIZE, SIZE) else {
+            return;
+        };
+        enter_workspace_root();
+        let before = glw::live_objects();
+
+        {
+            let mut world = World::default();
+            let mut resources = ResourcesManager::default();
+            init_scene(&mut resources);
+            init(&mut resources, (SIZE, SIZE));
+            let transform = Transform::default();
+            spawn_model(&mut world, "cube_textured/cube.obj", transform);
+            resources.get_mut::<Background>().skybox =
+                Some(Skybox::load(DEFAULT_SKYBOX).expect("This should work"));
+            render(&mut world, &mut resources, (SIZE, SIZE), 0.0);
+
+            // the previous mesh is released by `recompute` as no clone uses it
+            let live = glw::live_objects();
+            for entity in world.entity_manager.active_entities() {
+                world.with_components_mut_1::<Object, _>(entity, |object| {
+                    if let Some(object) = object {
+                        object.recompute();
+                    }
+                });
+            }
+            assert_eq!(glw::live_objects(), live);
+            assert!(live.total() > before.total());
+        }
+
+        assert_eq!(glw::live_objects(), before);
+    }
+
+    #[test]
+    fn it_should_render_cube() {
+        let image = render_scene(|world, resources| {
+            spawn_model(world, "cube.obj", transform_at(Vec3::splat(0.0)));
+            look_at(resources, Vec3::new(2.0, 2.0, 3.0), Vec3::splat(0.0));
+        });
+
+        if let Some(image) = image {
+            assert_golden("cube", &image);
+        }
+    }
+
+    #[test]
+    fn it_should_render_textured_cube() {
+        let image = render_scene(|world, resources| {
+            let transform = Transform {
+                rotation: Quaternion::from_euler_angles(
+                    Vec3::new(0.0, 1.0, 0.0),
+                    30_f32.to_radians(),
+                ),
+                ..transform_at(Vec3::splat(0.0))
+            };
+            spawn_model(world, "cube_textured/cube.obj", transform);
+            look_at(resources, Vec3::new(0.0, 2.5, 4.5), Vec3::splat(0.0));
+        });
+
+        if let Some(image) = image {
+            assert_golden("cube_textured", &image);
+        }
+    }
+
+    #[test]
+    fn it_should_render_teapot_with_shadows() {
+        let image = render_scene(|world, resources| {
+            spawn_model(world, "teapot.obj", transform_at(Vec3::splat(0.0)));
+            // a flat cube right under the teapot, its base is 1.72 below its center
+            let floor = Transform {
+                scale: Vec3::new(12.0, 0.2, 12.0),
+                ..transform_at(Vec3::new(0.0, -1.82, 0.0))
+            };
+            spawn_model(world, "cube.obj", floor);
+            world.add_component(Light::directional(Vec3::new(-0.5, -1.0, -0.3)));
+            look_at(resources, Vec3::new(0.0, 4.0, 9.0), Vec3::splat(0.0));
+        });
+
+        if let Some(image) = image {
+            assert_golden("teapot", &image);
+        }
+    }
+
+    #[test]
+    fn it_should_render_skybox_behind_cube() {
+        let image = render_scene(|world, resources| {
+            spawn_model(world, "cube.obj", transform_at(Vec3::splat(0.0)));
+            resources.get_mut::<Background>().skybox =
+                Some(Skybox::load(DEFAULT_SKYBOX).expect("This should work"));
+            look_at(resources, Vec3::new(2.0, 0.5, 4.0), Vec3::splat(0.0));
+        });
+
+        if let Some(image) = image {
+            assert_golden("skybox", &image);
+        }
+    }
+
+    #[test]
+    fn it_should_fade_far_cubes_into_fog() {
+        let image = render_scene(|world, resources| {
+            for i in 0..5 {
+                let transform = transform_at(Vec3::new(0.0, 0.0, i as f32 * -6.0));
+                spawn_model(world, "cube.obj", transform);
+            }
+            *resources.get_mut::<Fog>() = Fog::linear(Vec3::new(0.6, 0.6, 0.7), 5.0, 25.0);
+            look_at(
+                resources,
+                Vec3::new(2.0, 1.5, 4.0),
+                Vec3::new(0.0, 0.0, -8.0),
+            );
+        });
+
+        if let Some(image) = image {
+            assert_golden("fog", &image);
+        }
+    }
+
+    #[test]
+    fn it_should_fade_skybox_into_fog_at_the_horizon() {
+        let image = render_scene(|world, resources| {
+            spawn_model(world, "cube.obj", transform_at(Vec3::new(0.0, 0.0, -8.0)));
+            resources.get_mut::<Background>().skybox =
+                Some(Skybox::load(DEFAULT_SKYBOX).expect("This should work"));
+            *resources.get_mut::<Fog>() = Fog::linear(Vec3::new(0.6, 0.6, 0.7), 5.0, 25.0);
+            look_at(
+                resources,
+                Vec3::new(0.0, 0.5, 4.0),
+                Vec3::new(0.0, 1.5, -8.0),
+            );
+        });
+
+        if let Some(image) = image {
+            assert_golden("fog_skybox", &image);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::graphics::glw;
+use crate::prelude::*;
+
+///
+/// Applies the enabled effects to the scene rendered in `stack.scene` and draws the result in
+/// the window. Without any effect the scene is copied as it is.
+///
+pub fn apply_post_process(stack: &PostProcessStack, config: &PostProcessConfig, time: f32) {
+    let effects = config.enabled_effects();
+
+    // the fullscreen quad must be filled even while debugging in wireframe
+    let mut polygon_mode = [gl::FILL as i32; 2];
+    glw::get_integerv(gl::POLYGON_MODE, polygon_mode.as_mut_ptr());
+    glw::polygon_mode(gl::FRONT_AND_BACK, gl::FILL);
+    glw::disable(gl::DEPTH_TEST);
+    stack.quad.bind();
+
+    let mut source = &stack.scene;
+    if effects.is_empty() {
+        draw_pass(&stack.copy_shader, source, None);
+    }
+    for (index, effect) in effects.iter().enumerate() {
+        let target = if index + 1 == effects.len() {
+            None
+        } else {
+            Some(&stack.ping_pong[index % 2])
+        };
+
+        let shader = stack.shader(*effect);
+        shader.bind();
+        set_effect_uniforms(shader, *effect, config, source, time);
+        draw_pass(shader, source, target);
+
+        if let Some(target) = target {
+            source = target;
+        }
+    }
+
+    stack.quad.unbind();
+    glw::enable(gl::DEPTH_TEST);
+    glw::polygon_mode(gl::FRONT_AND_BACK, polygon_mode[0] as u32);
+}
+
+fn set_effect_uniforms(
+    shader: &glw::Shader,
+    effect: PostEffect,
+    config: &PostProcessConfig,
+    source: &glw::RenderTarget,
+    time: f32,
+) {
+    match effect {
+        PostEffect::Tonemapping => {
+            shader
+                .get_uniform_location("exposure")
+                .uniform1f(config.exposure);
+        }
+        PostEffect::SpeedLines => {
+            shader
+                .get_uniform_location("speed")
+                .uniform1f(config.speed.clamp(0.0, 1.0));
+            shader.get_uniform_location("time").uniform1f(time);
+        }
+        PostEffect::Vignette => {
+            shader
+                .get_uniform_location("strength")
+                .uniform1f(config.vignette_strength);
+        }
+        PostEffect::Fxaa => {
+            shader
+                .get_uniform_location("texel_size")
+                .uniform2f(1.0 / source.width as f32, 1.0 / source.height as f32);
+        }
+    }
+}
+
+///
+/// Draws the fullscreen quad sampling `source`, into `target` or the window when `None`
+///
+fn draw_pass(shader: &glw::Shader, source: &glw::RenderTarget, target: Option<&glw::RenderTarget>) {
+    match target {
+        Some(target) => target.bind(),
+        None => {
+            source.unbind();
+            glw::viewport(0, 0, source.width, source.height);
+        }
+    }
+
+    shader.bind();
+    shader.get_uniform_location("screen_texture").uniform1i(0);
+    source.color.active(gl::TEXTURE0);
+    source.color.bind();
+    glw::draw_arrays(gl::TRIANGLES, 0, 6);
+    shader.unbind();
+}
@@ -0,0 +1,45 @@
+use crate::graphics::glw;
+use crate::prelude::*;
+
+use super::RenderBatch;
+
+///
+/// The first light casting shadows, only one shadow map is rendered per frame
+///
+pub fn find_shadow_light(world: &World) -> Option<Light> {
+    let mut shadow_light = None;
+
+    for entity in world.entity_manager.active_entities() {
+        world.with_components_1::<Light, _>(entity, |light| {
+            if let Some(light) = light {
+                if shadow_light.is_none() && light.casts_shadows {
+                    shadow_light = Some(*light);
+                }
+            }
+        });
+    }
+
+    shadow_light
+}
+
+///
+/// Renders the depth of the opaque geometry from the light into the shadow map
+///
+pub fn render_shadow_map(batches: &[RenderBatch], shadow_map: &ShadowMap, light_space: &Mat4) {
+    shadow_map.framebuffer.bind();
+    glw::viewport(0, 0, shadow_map.size, shadow_map.size);
+    glw::clear(gl::DEPTH_BUFFER_BIT);
+
+    shadow_map.shader.bind();
+    shadow_map
+        .shader
+        .get_uniform_location("light_space")
+        .uniform_matrix4fv(light_space);
+
+    for batch in batches.iter() {
+        batch.draw_opaque(&shadow_map.shader);
+    }
+
+    shadow_map.shader.unbind();
+    shadow_map.framebuffer.unbind();
+}
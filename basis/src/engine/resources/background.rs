@@ -0,0 +1,238 @@
+use std::{ffi::c_void, mem, ptr};
+
+use crate::graphics::{
+    glw,
+    image::{self, DecodeError, Image, LoadImageError},
+    shader_program::ShaderProgram,
+};
+use crate::prelude::*;
+
+///
+/// Unit cube drawn from the inside, 36 vertices of 3 floats
+///
+#[rustfmt::skip]
+const SKYBOX_VERTICES: [f32; 108] = [
+    -1.0,  1.0, -1.0,   -1.0, -1.0, -1.0,    1.0, -1.0, -1.0,
+     1.0, -1.0, -1.0,    1.0,  1.0, -1.0,   -1.0,  1.0, -1.0,
+
+    -1.0, -1.0,  1.0,   -1.0, -1.0, -1.0,   -1.0,  1.0, -1.0,
+    -1.0,  1.0, -1.0,   -1.0,  1.0,  1.0,   -1.0, -1.0,  1.0,
+
+     1.0, -1.0, -1.0,    1.0, -1.0,  1.0,    1.0,  1.0,  1.0,
+     1.0,  1.0,  1.0,    1.0,  1.0, -1.0,    1.0, -1.0, -1.0,
+
+    -1.0, -1.0,  1.0,   -1.0,  1.0,  1.0,    1.0,  1.0,  1.0,
+     1.0,  1.0,  1.0,    1.0, -1.0,  1.0,   -1.0, -1.0,  1.0,
+
+    -1.0,  1.0, -1.0,    1.0,  1.0, -1.0,    1.0,  1.0,  1.0,
+     1.0,  1.0,  1.0,   -1.0,  1.0,  1.0,   -1.0,  1.0, -1.0,
+
+    -1.0, -1.0, -1.0,   -1.0, -1.0,  1.0,    1.0, -1.0, -1.0,
+     1.0, -1.0, -1.0,   -1.0, -1.0,  1.0,    1.0, -1.0,  1.0,
+];
+
+///
+/// Faces of the skybox bundled with the engine, in the order expected by `Skybox::load`
+///
+pub const DEFAULT_SKYBOX: [&str; 6] = [
+    "basis/src/assets/skybox/right.png",
+    "basis/src/assets/skybox/left.png",
+    "basis/src/assets/skybox/top.png",
+    "basis/src/assets/skybox/bottom.png",
+    "basis/src/assets/skybox/front.png",
+    "basis/src/assets/skybox/back.png",
+];
+
+///
+/// Environment drawn around the whole scene, sampled from a cubemap texture
+///
+#[derive(Debug)]
+pub struct Skybox {
+    texture: glw::Texture,
+    vao: glw::Vao,
+    /// Vertices read through `vao`
+    #[allow(dead_code)]
+    vertices: glw::BufferObject,
+    shader: ShaderProgram,
+}
+
+impl Skybox {
+    ///
+    /// Loads the six faces of the cubemap, in the order +X (right), -X (left), +Y (top),
+    /// -Y (bottom), +Z (front) and -Z (back)
+    ///
+    pub fn load(faces: [&str; 6]) -> Result<Skybox, LoadImageError> {
+        let faces = load_faces(faces)?;
+
+        let texture = glw::Texture::new(gl::TEXTURE_CUBE_MAP);
+        texture.bind();
+        for (index, face) in faces.iter().enumerate() {
+            texture.tex_image_cube_face(
+                index as u32,
+                0,
+                gl::RGBA8 as i32,
+                face.width as i32,
+                face.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                face.pixels.as_ptr() as *const c_void,
+            );
+        }
+        texture.tex_parameteri(gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        texture.tex_parameteri(gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        texture.tex_parameteri(gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        texture.tex_parameteri(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        texture.tex_parameteri(gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+        texture.unbind();
+
+        let shader = ShaderProgram::new(
+            "basis/src/assets/shaders/vertex_skybox_shader.glsl",
+            "basis/src/assets/shaders/fragment_skybox_shader.glsl",
+        )
+        .unwrap_or_else(|err| panic!("{}", err))
+        .with_uniform_block(FRAME_BLOCK, FRAME_BLOCK_BINDING);
+        let (vao, vertices) = skybox_vao();
+
+        Ok(Skybox {
+            texture,
+            vao,
+            vertices,
+            shader,
+        })
+    }
+
+    pub fn shader_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.shader
+    }
+
+    ///
+    /// Draws the sky where nothing else was drawn, must be called after the opaque geometry.
+    /// The camera is read from the `Frame` uniform block, the sky fades into the `fog` toward
+    /// the horizon.
+    ///
+    pub fn draw(&self, fog: &Fog) {
+        // the sky is exactly on the far plane, where the depth buffer was cleared to
+        glw::depth_func(gl::LEQUAL);
+        glw::depth_mask(false);
+
+        self.shader.bind();
+        self.shader.get_uniform_location("skybox").uniform1i(0);
+        fog.set_uniforms(&self.shader);
+
+        self.vao.bind();
+        self.texture.active(gl::TEXTURE0);
+        self.texture.bind();
+        glw::draw_arrays(gl::TRIANGLES, 0, 36);
+        self.vao.unbind();
+        self.shader.unbind();
+
+        glw::depth_mask(true);
+        glw::depth_func(gl::LESS);
+    }
+}
+
+///
+/// Cubemap faces must all be squares of the same size
+///
+fn load_faces(faces: [&str; 6]) -> Result<Vec<Image>, LoadImageError> {
+    let images = faces
+        .iter()
+        .map(|face| image::load(face))
+        .collect::<Result<Vec<Image>, LoadImageError>>()?;
+
+    let size = images[0].width;
+    if images
+        .iter()
+        .any(|image| image.width != size || image.height != size)
+    {
+        return Err(LoadImageError::Decode(DecodeError::InvalidData(
+            "Cubemap faces must be squares of the same size".to_string(),
+        )));
+    }
+    Ok(images)
+}
+
+fn skybox_vao() -> (glw::Vao, glw::BufferObject) {
+    let vao = glw::Vao::default();
+    vao.bind();
+    let vbo = glw::BufferObject::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
+    vbo.bind();
+    vbo.store_f32(&SKYBOX_VERTICES);
+
+    let position_attribute = glw::VertexAttribute::new(
+        0,
+        3,
+        gl::FLOAT,
+        gl::FALSE,
+        (3 * mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizei,
+        ptr::null(),
+    );
+    position_attribute.enable();
+
+    vao.unbind();
+    (vao, vbo)
+}
+
+///
+/// What is drawn behind the scene, the clear color is only visible without a skybox and is
+/// replaced by the color of the `Fog` when it is enabled. The skybox is fogged near the horizon.
+///
+#[derive(Debug)]
+pub struct Background {
+    pub clear_color: Vec3,
+    pub skybox: Option<Skybox>,
+}
+impl Resource for Background {}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            clear_color: Vec3::new(0.2, 0.3, 0.3),
+            skybox: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face_path(name: &str) -> String {
+        format!(
+            "{}/src/assets/skybox/{}.png",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        )
+    }
+
+    #[test]
+    fn it_should_load_skybox_faces() {
+        let paths = ["right", "left", "top", "bottom", "front", "back"].map(face_path);
+
+        let faces =
+            load_faces(paths.each_ref().map(|path| path.as_str())).expect("This should work");
+
+        assert_eq!(faces.len(), 6);
+        assert!(faces
+            .iter()
+            .all(|face| (face.width, face.height) == (64, 64)));
+    }
+
+    #[test]
+    fn it_should_fail_to_load_faces_of_different_sizes() {
+        let paths = ["right", "left", "top", "bottom", "front", "back"].map(face_path);
+        let texture = format!(
+            "{}/src/assets/models/cube_textured/cube.tga",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut faces = paths.each_ref().map(|path| path.as_str());
+        faces[3] = texture.as_str();
+
+        let result = load_faces(faces);
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Cubemap faces must be squares of the same size"));
+    }
+}
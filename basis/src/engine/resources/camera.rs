@@ -0,0 +1,29 @@
+use crate::prelude::*;
+
+///
+/// Point of view used by the renderer, camera systems update it every frame
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    pub view: Mat4,
+    pub position: Vec3,
+}
+impl Resource for Camera {}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            view: Mat4::identity(),
+            position: Vec3::default(),
+        }
+    }
+}
+
+impl Camera {
+    pub fn from_camerable(camerable: &impl Camerable) -> Self {
+        Self {
+            view: camerable.get_view_matrix(),
+            position: camerable.get_position(),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub use crate::graphics::window::CursorMode;
+use crate::prelude::*;
+
+///
+/// Cursor requested by the systems, applied to the window at the end of the frame
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub mode: CursorMode,
+}
+impl Resource for Cursor {}
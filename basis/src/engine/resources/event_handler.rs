@@ -1,142 +1,429 @@
-use std::collections::HashMap;
+use std::{collections::HashSet, hash::Hash};
 
-use crate::{graphics::window::KeyEvent, prelude::*};
+use glfw::{Action, Key, Modifiers, MouseButton, WindowEvent};
 
-#[derive(Debug)]
+use crate::prelude::*;
+
+/// Lock keys toggle a state rather than being held, they never prevent a binding from matching
+const LOCK_MODIFIERS: Modifiers = Modifiers::CapsLock.union(Modifiers::NumLock);
+
+/// The left and right keys of each modifier
+const MODIFIER_KEYS: [(Key, Key, Modifiers); 4] = [
+    (Key::LeftShift, Key::RightShift, Modifiers::Shift),
+    (Key::LeftControl, Key::RightControl, Modifiers::Control),
+    (Key::LeftAlt, Key::RightAlt, Modifiers::Alt),
+    (Key::LeftSuper, Key::RightSuper, Modifiers::Super),
+];
+
+///
+/// The modifier `key` sets while held, empty for the other keys
+///
+fn modifier_of(key: Key) -> Modifiers {
+    MODIFIER_KEYS
+        .iter()
+        .find(|(left, right, _)| key == *left || key == *right)
+        .map_or(Modifiers::empty(), |(_, _, modifier)| *modifier)
+}
+
+///
+/// Held state of a set of buttons, keys or mouse buttons, and the transitions of the last frame
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonStates<T: Hash + Eq> {
+    held: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T: Hash + Eq> Default for ButtonStates<T> {
+    fn default() -> Self {
+        Self {
+            held: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Copy> ButtonStates<T> {
+    ///
+    /// Forgets the transitions of the previous frame, the held buttons stay held
+    ///
+    pub fn begin_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    ///
+    /// A press of a held button is ignored, so key repeats are not pressed again
+    ///
+    pub fn press(&mut self, button: T) {
+        if self.held.insert(button) {
+            self.just_pressed.insert(button);
+        }
+    }
+
+    pub fn release(&mut self, button: T) {
+        if self.held.remove(&button) {
+            self.just_released.insert(button);
+        }
+    }
+
+    ///
+    /// Releases every held button, e.g. when the window loses the focus and will not receive
+    /// their release
+    ///
+    pub fn release_all(&mut self) {
+        for button in self.held.drain() {
+            self.just_released.insert(button);
+        }
+    }
+
+    pub fn held(&self, button: T) -> bool {
+        self.held.contains(&button)
+    }
+
+    ///
+    /// Whether the button went down during the last frame, it may already be released
+    ///
+    pub fn just_pressed(&self, button: T) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    ///
+    /// Whether the button went up during the last frame
+    ///
+    pub fn just_released(&self, button: T) -> bool {
+        self.just_released.contains(&button)
+    }
+}
+
+///
+/// Keyboard and mouse state built from the window events of each frame. Keys are tracked per
+/// physical key, the modifiers are derived from the modifier keys held, so pressing Ctrl while
+/// W is held does not change the state of W.
+///
+#[derive(Debug, Default)]
 pub struct EventHandler {
-    pub hold_keys: HashMap<KeyEvent, bool>,
-    pub events: Vec<glfw::WindowEvent>,
+    keys: ButtonStates<Key>,
+    mouse_buttons: ButtonStates<MouseButton>,
+    /// Last position of the cursor in screen coordinates, from the top left of the window
+    cursor_position: Option<(f64, f64)>,
+    /// Distance the cursor moved during the last frame
+    mouse_delta: (f64, f64),
+    /// Scroll offset of the last frame, `y` for a regular mouse wheel
+    scroll: (f64, f64),
+    pub events: Vec<WindowEvent>,
 }
 impl Resource for EventHandler {}
 impl EventHandler {
     pub fn new() -> Self {
-        Self {
-            hold_keys: HashMap::new(),
-            events: Vec::new(),
-        }
+        Self::default()
     }
 
-    pub fn update(&mut self, events: Vec<glfw::WindowEvent>) {
+    pub fn update(&mut self, events: Vec<WindowEvent>) {
         self.events.clear();
         self.events = events;
     }
 
+    ///
+    /// Applies the events of the frame given to `update`
+    ///
     pub fn process(&mut self) {
+        self.keys.begin_frame();
+        self.mouse_buttons.begin_frame();
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll = (0.0, 0.0);
+
         for event in self.events.iter() {
-            if let glfw::WindowEvent::Key(k, _, action, m) = *event {
-                let key = KeyEvent {
-                    key: k,
-                    modifiers: m,
-                };
-
-                if action == glfw::Action::Press {
-                    self.hold_keys.insert(key, true);
-                } else if action == glfw::Action::Release {
-                    // make sure we release all of them, even if the key was pressed by using a
-                    // modifier
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::empty(),
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::Alt,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::Shift,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::Control,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::Super,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::NumLock,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(
-                        KeyEvent {
-                            key: k,
-                            modifiers: glfw::Modifiers::CapsLock,
-                        },
-                        false,
-                    );
-                    self.hold_keys.insert(key, false);
+            match *event {
+                WindowEvent::Key(key, _, Action::Press, _) => self.keys.press(key),
+                WindowEvent::Key(key, _, Action::Release, _) => self.keys.release(key),
+                WindowEvent::MouseButton(button, Action::Press, _) => {
+                    self.mouse_buttons.press(button)
                 }
+                WindowEvent::MouseButton(button, Action::Release, _) => {
+                    self.mouse_buttons.release(button)
+                }
+                WindowEvent::CursorPos(x, y) => {
+                    if let Some((previous_x, previous_y)) = self.cursor_position {
+                        self.mouse_delta.0 += x - previous_x;
+                        self.mouse_delta.1 += y - previous_y;
+                    }
+                    self.cursor_position = Some((x, y));
+                }
+                WindowEvent::Scroll(x, y) => {
+                    self.scroll.0 += x;
+                    self.scroll.1 += y;
+                }
+                WindowEvent::Focus(false) => {
+                    self.keys.release_all();
+                    self.mouse_buttons.release_all();
+                }
+                _ => {}
             }
-            // match event {
-            //     glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
-            //         gl::Viewport(0, 0, width, height)
-            //     },
-            //     glfw::WindowEvent::Key(Key::Escape, _, glfw::Action::Press, _) => {
-            //         self.window_handle.set_should_close(true)
-            //     }
-            //     _ => {}
-            // }
         }
     }
 
     pub fn mouse_move(&self) -> Option<(f64, f64)> {
         for event in self.events.iter() {
-            if let glfw::WindowEvent::CursorPos(x, y) = event {
+            if let WindowEvent::CursorPos(x, y) = event {
                 return Some((*x, *y));
             }
         }
         None
     }
 
-    pub fn hold(&self, key: glfw::Key, modifiers: glfw::Modifiers) -> bool {
-        let is_pressing = self.hold_keys.get(&KeyEvent { key, modifiers });
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor_position
+    }
 
-        match is_pressing {
-            Some(value) => *value,
-            None => false,
-        }
+    ///
+    /// Distance the cursor moved during the last frame, `y` grows downwards
+    ///
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
     }
 
-    pub fn released(&self, key: glfw::Key, modifiers: glfw::Modifiers) -> bool {
-        let found = self.events.iter().find(|event| {
-            if let glfw::WindowEvent::Key(k, _, action, m) = event {
-                if k == &key && m == &modifiers && action == &glfw::Action::Release {
-                    return true;
-                }
-            }
-            false
-        });
-        found.is_some()
+    pub fn scroll(&self) -> (f64, f64) {
+        self.scroll
     }
 
-    pub fn pressed(&self, key: glfw::Key, modifiers: glfw::Modifiers) -> bool {
-        let found = self.events.iter().find(|event| {
-            if let glfw::WindowEvent::Key(k, _, action, m) = event {
-                if k == &key && m == &modifiers && action == &glfw::Action::Press {
-                    return true;
-                }
+    ///
+    /// The next cursor position is not compared to the last one, e.g. after the cursor got
+    /// locked and jumped to the center of the window
+    ///
+    pub fn forget_cursor_position(&mut self) {
+        self.cursor_position = None;
+    }
+
+    ///
+    /// The modifiers whose keys are held, lock modifiers are not included
+    ///
+    pub fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        for (left, right, modifier) in MODIFIER_KEYS {
+            if self.keys.held(left) || self.keys.held(right) {
+                modifiers |= modifier;
             }
-            false
-        });
-        found.is_some()
+        }
+        modifiers
+    }
+
+    ///
+    /// A modifier key bound alone, e.g. Shift to run, sets its own modifier while held, which
+    /// is left out of the comparison
+    ///
+    fn modifiers_match(&self, key: Key, modifiers: Modifiers) -> bool {
+        self.modifiers().difference(modifier_of(key)) == modifiers.difference(LOCK_MODIFIERS)
+    }
+
+    pub fn key_held(&self, key: Key) -> bool {
+        self.keys.held(key)
+    }
+
+    pub fn just_pressed(&self, key: Key) -> bool {
+        self.keys.just_pressed(key)
+    }
+
+    pub fn just_released(&self, key: Key) -> bool {
+        self.keys.just_released(key)
+    }
+
+    ///
+    /// Whether `key` is held while exactly `modifiers` are held, use `key_held` to ignore them
+    ///
+    pub fn hold(&self, key: Key, modifiers: Modifiers) -> bool {
+        self.key_held(key) && self.modifiers_match(key, modifiers)
+    }
+
+    pub fn released(&self, key: Key, modifiers: Modifiers) -> bool {
+        self.just_released(key) && self.modifiers_match(key, modifiers)
+    }
+
+    pub fn pressed(&self, key: Key, modifiers: Modifiers) -> bool {
+        self.just_pressed(key) && self.modifiers_match(key, modifiers)
+    }
+
+    pub fn mouse_button_hold(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.held(button)
+    }
+
+    pub fn mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.just_pressed(button)
+    }
+
+    pub fn mouse_button_released(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.just_released(button)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: Key, action: Action) -> WindowEvent {
+        WindowEvent::Key(key, 0, action, Modifiers::empty())
+    }
+
+    fn frame(event_handler: &mut EventHandler, events: Vec<WindowEvent>) {
+        event_handler.update(events);
+        event_handler.process();
+    }
+
+    #[test]
+    fn it_should_keep_a_key_held_across_modifier_changes() {
+        let mut event_handler = EventHandler::new();
+
+        frame(&mut event_handler, vec![key(Key::W, Action::Press)]);
+        assert!(event_handler.hold(Key::W, Modifiers::empty()));
+        assert!(event_handler.pressed(Key::W, Modifiers::empty()));
+
+        frame(
+            &mut event_handler,
+            vec![key(Key::LeftControl, Action::Press)],
+        );
+        assert!(event_handler.key_held(Key::W));
+        assert!(!event_handler.just_pressed(Key::W));
+        assert!(event_handler.hold(Key::W, Modifiers::Control));
+        assert!(!event_handler.hold(Key::W, Modifiers::empty()));
+
+        // GLFW reports the modifiers of the release, they do not matter
+        frame(
+            &mut event_handler,
+            vec![WindowEvent::Key(
+                Key::W,
+                0,
+                Action::Release,
+                Modifiers::Control | Modifiers::Shift,
+            )],
+        );
+        assert!(!event_handler.key_held(Key::W));
+        assert!(event_handler.just_released(Key::W));
+        assert!(event_handler.released(Key::W, Modifiers::Control));
+
+        frame(
+            &mut event_handler,
+            vec![key(Key::LeftControl, Action::Release)],
+        );
+        assert!(!event_handler.just_released(Key::W));
+        assert_eq!(event_handler.modifiers(), Modifiers::empty());
+    }
+
+    #[test]
+    fn it_should_compute_transitions_per_frame() {
+        let mut event_handler = EventHandler::new();
+
+        // pressed and released within the same frame
+        frame(
+            &mut event_handler,
+            vec![
+                key(Key::Space, Action::Press),
+                key(Key::Space, Action::Release),
+            ],
+        );
+        assert!(event_handler.just_pressed(Key::Space));
+        assert!(event_handler.just_released(Key::Space));
+        assert!(!event_handler.key_held(Key::Space));
+
+        frame(&mut event_handler, vec![key(Key::A, Action::Press)]);
+        assert!(!event_handler.just_pressed(Key::Space));
+        assert!(!event_handler.just_released(Key::Space));
+
+        // repeats and empty frames keep the key held without pressing it again
+        frame(&mut event_handler, vec![key(Key::A, Action::Repeat)]);
+        frame(&mut event_handler, vec![key(Key::A, Action::Press)]);
+        frame(&mut event_handler, vec![]);
+        assert!(event_handler.key_held(Key::A));
+        assert!(!event_handler.just_pressed(Key::A));
+
+        // a release without press, e.g. the key was held before the window got the focus
+        frame(&mut event_handler, vec![key(Key::B, Action::Release)]);
+        assert!(!event_handler.just_released(Key::B));
+    }
+
+    #[test]
+    fn it_should_ignore_lock_modifiers_and_match_both_sides() {
+        let mut event_handler = EventHandler::new();
+
+        frame(
+            &mut event_handler,
+            vec![
+                key(Key::RightShift, Action::Press),
+                key(Key::Up, Action::Press),
+            ],
+        );
+
+        assert!(event_handler.hold(Key::Up, Modifiers::Shift));
+        assert!(event_handler.hold(Key::Up, Modifiers::Shift | Modifiers::CapsLock));
+        assert!(!event_handler.hold(Key::Up, Modifiers::Shift | Modifiers::Alt));
+    }
+
+    #[test]
+    fn it_should_match_modifier_keys_bound_alone() {
+        let mut event_handler = EventHandler::new();
+
+        frame(&mut event_handler, vec![key(Key::LeftShift, Action::Press)]);
+        assert!(event_handler.hold(Key::LeftShift, Modifiers::empty()));
+        assert!(event_handler.pressed(Key::LeftShift, Modifiers::empty()));
+        assert!(!event_handler.hold(Key::LeftShift, Modifiers::Control));
+
+        frame(
+            &mut event_handler,
+            vec![key(Key::LeftShift, Action::Release)],
+        );
+        assert!(event_handler.released(Key::LeftShift, Modifiers::empty()));
+    }
+
+    #[test]
+    fn it_should_release_everything_when_the_focus_is_lost() {
+        let mut event_handler = EventHandler::new();
+        frame(
+            &mut event_handler,
+            vec![
+                key(Key::D, Action::Press),
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, Modifiers::empty()),
+            ],
+        );
+
+        frame(&mut event_handler, vec![WindowEvent::Focus(false)]);
+
+        assert!(!event_handler.key_held(Key::D));
+        assert!(event_handler.just_released(Key::D));
+        assert!(!event_handler.mouse_button_hold(MouseButton::Button1));
+        assert!(event_handler.mouse_button_released(MouseButton::Button1));
+    }
+
+    #[test]
+    fn it_should_accumulate_mouse_deltas_and_scroll_per_frame() {
+        let mut event_handler = EventHandler::new();
+
+        // the first position has nothing to be compared with
+        frame(&mut event_handler, vec![WindowEvent::CursorPos(10.0, 10.0)]);
+        assert_eq!(event_handler.mouse_delta(), (0.0, 0.0));
+
+        frame(
+            &mut event_handler,
+            vec![
+                WindowEvent::CursorPos(12.0, 9.0),
+                WindowEvent::Scroll(0.0, 1.0),
+                WindowEvent::CursorPos(15.0, 5.0),
+                WindowEvent::Scroll(0.0, 2.0),
+            ],
+        );
+        assert_eq!(event_handler.mouse_delta(), (5.0, -5.0));
+        assert_eq!(event_handler.scroll(), (0.0, 3.0));
+        assert_eq!(event_handler.cursor_position(), Some((15.0, 5.0)));
+
+        frame(&mut event_handler, vec![]);
+        assert_eq!(event_handler.mouse_delta(), (0.0, 0.0));
+        assert_eq!(event_handler.scroll(), (0.0, 0.0));
+
+        event_handler.forget_cursor_position();
+        frame(
+            &mut event_handler,
+            vec![WindowEvent::CursorPos(400.0, 300.0)],
+        );
+        assert_eq!(event_handler.mouse_delta(), (0.0, 0.0));
     }
 }
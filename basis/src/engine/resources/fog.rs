@@ -0,0 +1,213 @@
+use crate::graphics::glw;
+use crate::prelude::*;
+
+///
+/// Visibility under which a fragment is drawn with the fog color only, one step of an 8 bit
+/// color channel
+///
+const OPAQUE_VISIBILITY: f32 = 1.0 / 255.0;
+
+///
+/// How the visibility of a fragment decreases with its distance to the camera
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogMode {
+    /// Fully visible before `start`, fully hidden after `end`. When `end` is not after `start`
+    /// the fog goes from clear to opaque at `start`
+    Linear { start: f32, end: f32 },
+    /// `e^-(density * distance)`
+    Exponential,
+    /// `e^-(density * distance)^2`, stays clear longer then thickens quickly
+    ExponentialSquared,
+}
+
+impl FogMode {
+    /// Value of the `fog_mode` uniform, 0 disables the fog
+    fn shader_id(&self) -> i32 {
+        match self {
+            FogMode::Linear { .. } => 1,
+            FogMode::Exponential => 2,
+            FogMode::ExponentialSquared => 3,
+        }
+    }
+}
+
+///
+/// Distance fog applied by the lit shader, far geometry fades into `color` instead of popping
+/// in at the far plane. The clear color is replaced by the fog color while the fog is enabled
+/// and the skybox fades into it toward the horizon.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    pub enabled: bool,
+    pub mode: FogMode,
+    pub color: Vec3,
+    /// Used by the exponential modes, the higher the thicker
+    pub density: f32,
+}
+impl Resource for Fog {}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: FogMode::ExponentialSquared,
+            color: Vec3::new(0.2, 0.3, 0.3),
+            density: 0.02,
+        }
+    }
+}
+
+impl Fog {
+    pub fn linear(color: Vec3, start: f32, end: f32) -> Fog {
+        Fog {
+            enabled: true,
+            mode: FogMode::Linear { start, end },
+            color,
+            ..Default::default()
+        }
+    }
+
+    pub fn exponential(color: Vec3, density: f32) -> Fog {
+        Fog {
+            enabled: true,
+            mode: FogMode::Exponential,
+            color,
+            density,
+        }
+    }
+
+    pub fn exponential_squared(color: Vec3, density: f32) -> Fog {
+        Fog {
+            enabled: true,
+            mode: FogMode::ExponentialSquared,
+            color,
+            density,
+        }
+    }
+
+    ///
+    /// Part of the fragment color kept at `distance` from the camera, from 1.0 (no fog) to 0.0.
+    /// Must match `compute_fog_visibility` of the lit shader
+    ///
+    pub fn visibility(&self, distance: f32) -> f32 {
+        if !self.enabled {
+            return 1.0;
+        }
+
+        let visibility = match self.mode {
+            FogMode::Linear { start, end } if end <= start => {
+                if distance < start {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            FogMode::Linear { start, end } => (end - distance) / (end - start),
+            FogMode::Exponential => (-self.density * distance).exp(),
+            FogMode::ExponentialSquared => (-(self.density * distance).powi(2)).exp(),
+        };
+        visibility.clamp(0.0, 1.0)
+    }
+
+    ///
+    /// Distance from which the geometry is completely hidden by the fog, `None` without fog.
+    /// Segments spawned further than this can't be seen popping in.
+    ///
+    pub fn opaque_distance(&self) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+
+        let distance = match self.mode {
+            FogMode::Linear { start, end } => end.max(start),
+            FogMode::Exponential => -OPAQUE_VISIBILITY.ln() / self.density,
+            FogMode::ExponentialSquared => (-OPAQUE_VISIBILITY.ln()).sqrt() / self.density,
+        };
+        Some(distance)
+    }
+
+    ///
+    /// Far plane of the projection, nothing past the opaque distance needs to be drawn
+    ///
+    pub fn far_plane(&self, default_far: f32) -> f32 {
+        self.opaque_distance()
+            .map_or(default_far, |distance| distance.min(default_far))
+    }
+
+    pub fn set_uniforms(&self, shader: &glw::Shader) {
+        let (start, end) = match self.mode {
+            FogMode::Linear { start, end } => (start, end),
+            _ => (0.0, 0.0),
+        };
+
+        shader
+            .get_uniform_location("fog_mode")
+            .uniform1i(if self.enabled {
+                self.mode.shader_id()
+            } else {
+                0
+            });
+        shader.get_uniform_location("fog_color").uniform3f(
+            self.color.x,
+            self.color.y,
+            self.color.z,
+        );
+        shader
+            .get_uniform_location("fog_density")
+            .uniform1f(self.density);
+        shader.get_uniform_location("fog_start").uniform1f(start);
+        shader.get_uniform_location("fog_end").uniform1f(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_not_hide_anything_when_disabled() {
+        let fog = Fog::default();
+
+        assert_eq!(fog.visibility(10000.0), 1.0);
+        assert_eq!(fog.opaque_distance(), None);
+        assert_eq!(fog.far_plane(1000.0), 1000.0);
+    }
+
+    #[test]
+    fn it_should_fade_linearly_between_start_and_end() {
+        let fog = Fog::linear(Vec3::splat(0.5), 10.0, 30.0);
+
+        assert_eq!(fog.visibility(5.0), 1.0);
+        assert_eq!(fog.visibility(20.0), 0.5);
+        assert_eq!(fog.visibility(40.0), 0.0);
+        assert_eq!(fog.opaque_distance(), Some(30.0));
+        assert_eq!(fog.far_plane(1000.0), 30.0);
+    }
+
+    #[test]
+    fn it_should_switch_at_start_when_the_linear_range_is_empty() {
+        for end in [10.0, 5.0] {
+            let fog = Fog::linear(Vec3::splat(0.5), 10.0, end);
+
+            assert_eq!(fog.visibility(9.0), 1.0);
+            assert_eq!(fog.visibility(10.0), 0.0);
+            assert_eq!(fog.visibility(20.0), 0.0);
+            assert_eq!(fog.opaque_distance(), Some(10.0));
+        }
+    }
+
+    #[test]
+    fn it_should_hide_geometry_past_the_opaque_distance() {
+        for fog in [
+            Fog::exponential(Vec3::splat(0.5), 0.05),
+            Fog::exponential_squared(Vec3::splat(0.5), 0.05),
+        ] {
+            let distance = fog.opaque_distance().expect("Fog is enabled");
+
+            assert!(fog.visibility(distance * 0.9) > OPAQUE_VISIBILITY);
+            assert!((fog.visibility(distance) - OPAQUE_VISIBILITY).abs() < 1e-5);
+            assert!(fog.far_plane(1000.0) < 1000.0);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use crate::prelude::*;
+
+#[derive(Debug)]
+struct Recording {
+    file_path: String,
+    next_frame: usize,
+    frames: usize,
+}
+
+///
+/// Requests to save what the window shows into image files, the format is picked from the
+/// file extension (".ppm" or ".png"). Frames are saved once rendered, before being displayed.
+///
+#[derive(Debug, Default)]
+pub struct FrameCapture {
+    screenshots: Vec<String>,
+    recording: Option<Recording>,
+}
+impl Resource for FrameCapture {}
+
+impl FrameCapture {
+    ///
+    /// Saves the next frame to `file_path`
+    ///
+    pub fn screenshot(&mut self, file_path: &str) {
+        self.screenshots.push(file_path.to_string());
+    }
+
+    ///
+    /// Saves the next `frames` frames as a numbered image sequence, "frames/run.png" gives
+    /// "frames/run_0000.png", "frames/run_0001.png", etc. Replaces the current recording.
+    ///
+    pub fn record(&mut self, file_path: &str, frames: usize) {
+        self.recording = Some(Recording {
+            file_path: file_path.to_string(),
+            next_frame: 0,
+            frames,
+        });
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn has_pending_captures(&self) -> bool {
+        !self.screenshots.is_empty() || self.is_recording()
+    }
+
+    ///
+    /// Paths the current frame has to be saved to, the recording moves on to the next frame
+    ///
+    pub fn next_frame_paths(&mut self) -> Vec<String> {
+        let mut paths = std::mem::take(&mut self.screenshots);
+
+        if let Some(recording) = self.recording.as_mut() {
+            paths.push(numbered_path(&recording.file_path, recording.next_frame));
+            recording.next_frame += 1;
+            if recording.next_frame >= recording.frames {
+                self.recording = None;
+            }
+        }
+
+        paths
+    }
+}
+
+///
+/// Inserts the frame number before the extension of `file_path`
+///
+pub fn numbered_path(file_path: &str, frame: usize) -> String {
+    let path = Path::new(file_path);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let file_name = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}_{:04}.{}", stem, frame, extension),
+        None => format!("{}_{:04}", stem, frame),
+    };
+
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_number_paths() {
+        assert_eq!(numbered_path("frames/run.png", 0), "frames/run_0000.png");
+        assert_eq!(numbered_path("run.ppm", 42), "run_0042.ppm");
+        assert_eq!(numbered_path("a/b/run", 12345), "a/b/run_12345");
+    }
+
+    #[test]
+    fn it_should_capture_screenshot_once() {
+        let mut capture = FrameCapture::default();
+
+        capture.screenshot("a.png");
+        capture.screenshot("b.ppm");
+
+        assert!(capture.has_pending_captures());
+        assert_eq!(capture.next_frame_paths(), vec!["a.png", "b.ppm"]);
+        assert!(!capture.has_pending_captures());
+        assert_eq!(capture.next_frame_paths(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_should_record_n_frames() {
+        let mut capture = FrameCapture::default();
+
+        capture.record("run.png", 2);
+        capture.screenshot("shot.png");
+
+        assert_eq!(capture.next_frame_paths(), vec!["shot.png", "run_0000.png"]);
+        assert!(capture.is_recording());
+        assert_eq!(capture.next_frame_paths(), vec!["run_0001.png"]);
+        assert!(!capture.is_recording());
+        assert_eq!(capture.next_frame_paths(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/// Number of frames the statistics are computed over by default, a few seconds of play
+pub const FRAME_STATS_WINDOW: usize = 300;
+
+///
+/// Durations of the last frames, measured on the clock of the window so they include the waits
+/// for the vsync and the frame limiter. Times are in seconds.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStats {
+    frame_times: VecDeque<f32>,
+    window: usize,
+}
+impl Resource for FrameStats {}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        FrameStats::new(FRAME_STATS_WINDOW)
+    }
+}
+
+impl FrameStats {
+    pub fn new(window: usize) -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(window),
+            window: window.max(1),
+        }
+    }
+
+    ///
+    /// Adds the duration of a frame, the oldest one is forgotten once the window is full
+    ///
+    pub fn record(&mut self, frame_time: f32) {
+        if self.frame_times.len() == self.window {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_times.len()
+    }
+
+    ///
+    /// Frames per second over the window, 0 before the first frame
+    ///
+    pub fn fps(&self) -> f32 {
+        fps(self.frame_times.iter().copied())
+    }
+
+    pub fn frame_time_min(&self) -> f32 {
+        self.frame_times
+            .iter()
+            .copied()
+            .reduce(f32::min)
+            .unwrap_or(0.0)
+    }
+
+    pub fn frame_time_avg(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32
+    }
+
+    pub fn frame_time_max(&self) -> f32 {
+        self.frame_times
+            .iter()
+            .copied()
+            .reduce(f32::max)
+            .unwrap_or(0.0)
+    }
+
+    ///
+    /// Frames per second over the slowest 1% of the frames, at least the slowest one. Shows
+    /// the stutters an average hides.
+    ///
+    pub fn one_percent_low(&self) -> f32 {
+        let mut frame_times = self.frame_times.iter().copied().collect::<Vec<f32>>();
+        frame_times.sort_by(|a, b| b.total_cmp(a));
+        let slowest = frame_times.len().div_ceil(100);
+        fps(frame_times.into_iter().take(slowest))
+    }
+}
+
+fn fps(frame_times: impl Iterator<Item = f32>) -> f32 {
+    let (count, total) = frame_times.fold((0, 0.0), |(count, total), frame_time| {
+        (count + 1, total + frame_time)
+    });
+    if total <= 0.0 {
+        return 0.0;
+    }
+    count as f32 / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_compute_the_frame_times() {
+        let mut frame_stats = FrameStats::default();
+        assert_eq!(frame_stats.fps(), 0.0);
+        assert_eq!(frame_stats.one_percent_low(), 0.0);
+
+        for _ in 0..198 {
+            frame_stats.record(0.01);
+        }
+        frame_stats.record(0.05);
+        frame_stats.record(0.1);
+
+        assert_eq!(frame_stats.frame_count(), 200);
+        assert_eq!(frame_stats.frame_time_min(), 0.01);
+        assert_eq!(frame_stats.frame_time_max(), 0.1);
+        assert!((frame_stats.frame_time_avg() - 0.01065).abs() < 1e-6);
+        assert!((frame_stats.fps() - 1.0 / 0.01065).abs() < 0.01);
+        // the 2 slowest frames of 200
+        assert!((frame_stats.one_percent_low() - 1.0 / 0.075).abs() < 0.01);
+    }
+
+    #[test]
+    fn it_should_forget_the_frames_out_of_the_window() {
+        let mut frame_stats = FrameStats::new(3);
+
+        for frame_time in [0.5, 0.02, 0.02, 0.02] {
+            frame_stats.record(frame_time);
+        }
+
+        assert_eq!(frame_stats.frame_count(), 3);
+        assert_eq!(frame_stats.frame_time_max(), 0.02);
+        assert!((frame_stats.fps() - 50.0).abs() < 0.01);
+    }
+}
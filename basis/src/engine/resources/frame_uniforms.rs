@@ -0,0 +1,47 @@
+use crate::graphics::{
+    glw,
+    std140::{Std140Writer, UniformBlock},
+};
+use crate::prelude::*;
+
+/// Name of the uniform block declared in `include/frame.glsl`
+pub const FRAME_BLOCK: &str = "Frame";
+/// Binding point of the `Frame` uniform block
+pub const FRAME_BLOCK_BINDING: u32 = 0;
+
+///
+/// Camera and light data shared by every program, must match the `Frame` block of
+/// `include/frame.glsl`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameData {
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub light_space: Mat4,
+    pub camera_position: Vec3,
+    pub shadows_enabled: bool,
+}
+
+impl UniformBlock for FrameData {
+    fn write_std140(&self, writer: &mut Std140Writer) {
+        writer
+            .mat4(&self.view)
+            .mat4(&self.projection)
+            .mat4(&self.light_space)
+            .vec3(&self.camera_position)
+            .bool(self.shadows_enabled);
+    }
+}
+
+///
+/// Uniform buffer uploaded once per frame by the renderer, read by every program bound to the
+/// `Frame` block
+///
+pub struct FrameUniforms(pub glw::UniformBuffer<FrameData>);
+impl Resource for FrameUniforms {}
+
+impl Default for FrameUniforms {
+    fn default() -> Self {
+        Self(glw::UniformBuffer::new(FRAME_BLOCK_BINDING))
+    }
+}
@@ -0,0 +1,343 @@
+use glfw::{GamepadAxis, GamepadButton};
+
+pub use crate::graphics::window::GamepadInput;
+use crate::{
+    graphics::window::{GAMEPAD_AXES, GAMEPAD_BUTTONS},
+    prelude::*,
+};
+
+/// How far an axis must be pushed to act like a held button
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadEvent {
+    Connected { id: usize, name: String },
+    Disconnected { id: usize },
+}
+
+///
+/// A connected gamepad, with the standard layout of GLFW: an Xbox controller
+///
+#[derive(Debug, Clone)]
+pub struct Gamepad {
+    id: usize,
+    name: String,
+    buttons: ButtonStates<GamepadButton>,
+    /// In the order of `GAMEPAD_AXES`, the triggers are mapped from 0 to 1
+    axes: [f32; 6],
+    previous_axes: [f32; 6],
+    deadzone: f32,
+}
+
+impl Gamepad {
+    fn new(id: usize, name: String) -> Gamepad {
+        Gamepad {
+            id,
+            name,
+            buttons: ButtonStates::default(),
+            axes: [0.0; 6],
+            previous_axes: [0.0; 6],
+            deadzone: 0.0,
+        }
+    }
+
+    fn update(&mut self, input: &GamepadInput, deadzone: f32) {
+        self.buttons.begin_frame();
+        for button in GAMEPAD_BUTTONS {
+            if input.buttons.contains(&button) {
+                self.buttons.press(button);
+            } else {
+                self.buttons.release(button);
+            }
+        }
+
+        self.previous_axes = self.axes;
+        for (index, axis) in GAMEPAD_AXES.iter().enumerate() {
+            self.axes[index] = match axis {
+                GamepadAxis::AxisLeftTrigger | GamepadAxis::AxisRightTrigger => {
+                    (input.axes[index] + 1.0) / 2.0
+                }
+                _ => input.axes[index],
+            };
+        }
+        self.deadzone = deadzone;
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn held(&self, button: GamepadButton) -> bool {
+        self.buttons.held(button)
+    }
+
+    pub fn just_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons.just_pressed(button)
+    }
+
+    pub fn just_released(&self, button: GamepadButton) -> bool {
+        self.buttons.just_released(button)
+    }
+
+    ///
+    /// Value of the axis with the deadzone removed, the remaining range is stretched back to
+    /// reach 1. Sticks go from -1 to 1, `y` grows downwards, triggers go from 0 to 1.
+    ///
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        apply_deadzone(self.axes[axis as usize], self.deadzone)
+    }
+
+    ///
+    /// Both axes of the left stick, the deadzone is applied to their length so diagonals are
+    /// not snapped to the axes
+    ///
+    pub fn left_stick(&self) -> (f32, f32) {
+        self.stick(GamepadAxis::AxisLeftX, GamepadAxis::AxisLeftY)
+    }
+
+    pub fn right_stick(&self) -> (f32, f32) {
+        self.stick(GamepadAxis::AxisRightX, GamepadAxis::AxisRightY)
+    }
+
+    fn stick(&self, x: GamepadAxis, y: GamepadAxis) -> (f32, f32) {
+        let (x, y) = (self.axes[x as usize], self.axes[y as usize]);
+        let length = (x * x + y * y).sqrt();
+        if length <= self.deadzone {
+            return (0.0, 0.0);
+        }
+        let scale = apply_deadzone(length.min(1.0), self.deadzone) / length;
+        (x * scale, y * scale)
+    }
+
+    ///
+    /// Whether the axis is pushed past `AXIS_PRESS_THRESHOLD` in the direction of `positive`
+    ///
+    pub fn axis_held(&self, axis: GamepadAxis, positive: bool) -> bool {
+        past_threshold(self.axes[axis as usize], positive)
+    }
+
+    pub fn axis_just_pressed(&self, axis: GamepadAxis, positive: bool) -> bool {
+        self.axis_held(axis, positive)
+            && !past_threshold(self.previous_axes[axis as usize], positive)
+    }
+
+    pub fn axis_just_released(&self, axis: GamepadAxis, positive: bool) -> bool {
+        !self.axis_held(axis, positive)
+            && past_threshold(self.previous_axes[axis as usize], positive)
+    }
+}
+
+fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() <= deadzone {
+        return 0.0;
+    }
+    value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)
+}
+
+fn past_threshold(value: f32, positive: bool) -> bool {
+    if positive {
+        value > AXIS_PRESS_THRESHOLD
+    } else {
+        value < -AXIS_PRESS_THRESHOLD
+    }
+}
+
+///
+/// The gamepads connected, polled once per frame. The queries not taking a gamepad look at
+/// all of them, enough for a single player game.
+///
+#[derive(Debug)]
+pub struct Gamepads {
+    pads: Vec<Gamepad>,
+    events: Vec<GamepadEvent>,
+    /// Fraction of the axes ignored around their rest position, worn sticks never go back to 0
+    pub deadzone: f32,
+}
+impl Resource for Gamepads {}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self {
+            pads: Vec::new(),
+            events: Vec::new(),
+            deadzone: 0.15,
+        }
+    }
+}
+
+impl Gamepads {
+    ///
+    /// Replaces the state with the gamepads polled this frame, the gamepads missing from
+    /// `inputs` are disconnected
+    ///
+    pub fn update(&mut self, inputs: Vec<GamepadInput>) {
+        self.events.clear();
+
+        let events = &mut self.events;
+        self.pads.retain(|pad| {
+            let connected = inputs.iter().any(|input| input.id == pad.id);
+            if !connected {
+                events.push(GamepadEvent::Disconnected { id: pad.id });
+            }
+            connected
+        });
+
+        for input in &inputs {
+            let index = match self.pads.iter().position(|pad| pad.id == input.id) {
+                Some(index) => index,
+                None => {
+                    self.events.push(GamepadEvent::Connected {
+                        id: input.id,
+                        name: input.name.clone(),
+                    });
+                    self.pads.push(Gamepad::new(input.id, input.name.clone()));
+                    self.pads.len() - 1
+                }
+            };
+            self.pads[index].update(input, self.deadzone);
+        }
+    }
+
+    pub fn connected(&self) -> &[Gamepad] {
+        &self.pads
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Gamepad> {
+        self.pads.iter().find(|pad| pad.id == id)
+    }
+
+    ///
+    /// Connections and disconnections of the last frame
+    ///
+    pub fn events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    pub fn held(&self, button: GamepadButton) -> bool {
+        self.pads.iter().any(|pad| pad.held(button))
+    }
+
+    pub fn just_pressed(&self, button: GamepadButton) -> bool {
+        self.pads.iter().any(|pad| pad.just_pressed(button))
+    }
+
+    pub fn just_released(&self, button: GamepadButton) -> bool {
+        self.pads.iter().any(|pad| pad.just_released(button))
+    }
+
+    pub fn axis_held(&self, axis: GamepadAxis, positive: bool) -> bool {
+        self.pads.iter().any(|pad| pad.axis_held(axis, positive))
+    }
+
+    pub fn axis_just_pressed(&self, axis: GamepadAxis, positive: bool) -> bool {
+        self.pads
+            .iter()
+            .any(|pad| pad.axis_just_pressed(axis, positive))
+    }
+
+    pub fn axis_just_released(&self, axis: GamepadAxis, positive: bool) -> bool {
+        self.pads
+            .iter()
+            .any(|pad| pad.axis_just_released(axis, positive))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(id: usize, buttons: Vec<GamepadButton>, axes: [f32; 6]) -> GamepadInput {
+        GamepadInput {
+            id,
+            name: format!("Pad {}", id),
+            buttons,
+            axes,
+        }
+    }
+
+    /// Axes at rest, the triggers rest at -1
+    const REST: [f32; 6] = [0.0, 0.0, 0.0, 0.0, -1.0, -1.0];
+
+    #[test]
+    fn it_should_report_connections_and_disconnections() {
+        let mut gamepads = Gamepads::default();
+
+        gamepads.update(vec![input(0, vec![], REST), input(3, vec![], REST)]);
+        assert_eq!(
+            gamepads.events(),
+            &[
+                GamepadEvent::Connected {
+                    id: 0,
+                    name: "Pad 0".to_string()
+                },
+                GamepadEvent::Connected {
+                    id: 3,
+                    name: "Pad 3".to_string()
+                },
+            ]
+        );
+
+        gamepads.update(vec![input(3, vec![], REST)]);
+        assert_eq!(gamepads.events(), &[GamepadEvent::Disconnected { id: 0 }]);
+        assert_eq!(gamepads.connected().len(), 1);
+        assert_eq!(gamepads.get(3).map(Gamepad::name), Some("Pad 3"));
+
+        gamepads.update(vec![input(3, vec![], REST)]);
+        assert!(gamepads.events().is_empty());
+    }
+
+    #[test]
+    fn it_should_track_button_transitions() {
+        let mut gamepads = Gamepads::default();
+
+        gamepads.update(vec![input(0, vec![GamepadButton::ButtonA], REST)]);
+        assert!(gamepads.held(GamepadButton::ButtonA));
+        assert!(gamepads.just_pressed(GamepadButton::ButtonA));
+
+        gamepads.update(vec![input(0, vec![GamepadButton::ButtonA], REST)]);
+        assert!(gamepads.held(GamepadButton::ButtonA));
+        assert!(!gamepads.just_pressed(GamepadButton::ButtonA));
+
+        gamepads.update(vec![input(0, vec![], REST)]);
+        assert!(!gamepads.held(GamepadButton::ButtonA));
+        assert!(gamepads.just_released(GamepadButton::ButtonA));
+    }
+
+    #[test]
+    fn it_should_apply_the_deadzone_to_the_axes() {
+        let mut gamepads = Gamepads {
+            deadzone: 0.2,
+            ..Gamepads::default()
+        };
+
+        gamepads.update(vec![input(0, vec![], [0.1, -0.6, 0.1, 0.1, 0.0, -1.0])]);
+        let pad = gamepads.get(0).unwrap();
+
+        assert_eq!(pad.axis(GamepadAxis::AxisLeftX), 0.0);
+        assert!((pad.axis(GamepadAxis::AxisLeftY) + 0.5).abs() < 1e-6);
+        assert!((pad.axis(GamepadAxis::AxisLeftTrigger) - 0.375).abs() < 1e-6);
+        assert_eq!(pad.axis(GamepadAxis::AxisRightTrigger), 0.0);
+        // the right stick is below the deadzone once both axes are combined
+        assert_eq!(pad.right_stick(), (0.0, 0.0));
+        let (x, y) = pad.left_stick();
+        assert!(x > 0.0 && y < -0.5);
+    }
+
+    #[test]
+    fn it_should_press_axes_past_the_threshold() {
+        let mut gamepads = Gamepads::default();
+
+        gamepads.update(vec![input(0, vec![], [-0.9, 0.0, 0.0, 0.0, -1.0, -1.0])]);
+        assert!(gamepads.axis_held(GamepadAxis::AxisLeftX, false));
+        assert!(gamepads.axis_just_pressed(GamepadAxis::AxisLeftX, false));
+        assert!(!gamepads.axis_held(GamepadAxis::AxisLeftX, true));
+
+        gamepads.update(vec![input(0, vec![], [-0.2, 0.0, 0.0, 0.0, 1.0, -1.0])]);
+        assert!(gamepads.axis_just_released(GamepadAxis::AxisLeftX, false));
+        assert!(gamepads.axis_just_pressed(GamepadAxis::AxisLeftTrigger, true));
+    }
+}
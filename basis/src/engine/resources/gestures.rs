@@ -0,0 +1,347 @@
+use glfw::{Action, MouseButton, WindowEvent};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// `velocity` in pixels per second, averaged since the pointer went down
+    Swipe {
+        direction: SwipeDirection,
+        velocity: f32,
+    },
+    Tap {
+        position: (f64, f64),
+    },
+}
+
+///
+/// Pointer input the gestures are recognized from, a mouse dragged with its left button or a
+/// finger on a touch screen. Positions are in screen coordinates, `y` grows downwards, and
+/// times in seconds.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEvent {
+    Down { position: (f64, f64), time: f64 },
+    Move { position: (f64, f64), time: f64 },
+    Up { position: (f64, f64), time: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureThresholds {
+    /// Distance in pixels the pointer must travel to swipe
+    pub swipe_distance: f64,
+    /// Slower drags are not swipes, in pixels per second
+    pub swipe_velocity: f64,
+    /// A tap moves less than this distance in pixels
+    pub tap_distance: f64,
+    /// A tap is released within this duration in seconds
+    pub tap_duration: f64,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        Self {
+            swipe_distance: 50.0,
+            swipe_velocity: 300.0,
+            tap_distance: 10.0,
+            tap_duration: 0.25,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stroke {
+    start: (f64, f64),
+    start_time: f64,
+    /// A stroke swipes once, the rest of it is ignored until the pointer goes up
+    swiped: bool,
+}
+
+///
+/// Turns a sequence of `PointerEvent` into gestures. A swipe is recognized as soon as the
+/// pointer traveled far and fast enough, without waiting for it to go up, which keeps the
+/// controls responsive.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GestureRecognizer {
+    pub thresholds: GestureThresholds,
+    stroke: Option<Stroke>,
+}
+
+impl GestureRecognizer {
+    pub fn new(thresholds: GestureThresholds) -> Self {
+        Self {
+            thresholds,
+            stroke: None,
+        }
+    }
+
+    pub fn feed(&mut self, event: PointerEvent) -> Option<Gesture> {
+        match event {
+            PointerEvent::Down { position, time } => {
+                self.stroke = Some(Stroke {
+                    start: position,
+                    start_time: time,
+                    swiped: false,
+                });
+                None
+            }
+            PointerEvent::Move { position, time } => {
+                let stroke = self.stroke.as_mut()?;
+                let swipe = swipe(stroke, position, time, &self.thresholds);
+                stroke.swiped |= swipe.is_some();
+                swipe
+            }
+            PointerEvent::Up { position, time } => {
+                let stroke = self.stroke.take()?;
+                if let Some(swipe) = swipe(&stroke, position, time, &self.thresholds) {
+                    return Some(swipe);
+                }
+
+                let is_tap = !stroke.swiped
+                    && distance(stroke.start, position) <= self.thresholds.tap_distance
+                    && time - stroke.start_time <= self.thresholds.tap_duration;
+                is_tap.then_some(Gesture::Tap { position })
+            }
+        }
+    }
+}
+
+fn distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt()
+}
+
+fn swipe(
+    stroke: &Stroke,
+    position: (f64, f64),
+    time: f64,
+    thresholds: &GestureThresholds,
+) -> Option<Gesture> {
+    let travelled = distance(stroke.start, position);
+    if stroke.swiped || travelled < thresholds.swipe_distance {
+        return None;
+    }
+    // events of the same frame share their time, they are as fast as it gets
+    let velocity = travelled / (time - stroke.start_time).max(f64::EPSILON);
+    if velocity < thresholds.swipe_velocity {
+        return None;
+    }
+
+    let (dx, dy) = (position.0 - stroke.start.0, position.1 - stroke.start.1);
+    let direction = if dx.abs() >= dy.abs() {
+        if dx < 0.0 {
+            SwipeDirection::Left
+        } else {
+            SwipeDirection::Right
+        }
+    } else if dy < 0.0 {
+        SwipeDirection::Up
+    } else {
+        SwipeDirection::Down
+    };
+
+    Some(Gesture::Swipe {
+        direction,
+        velocity: velocity as f32,
+    })
+}
+
+///
+/// Gestures made with the left mouse button during the last frame
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gestures {
+    pub recognizer: GestureRecognizer,
+    gestures: Vec<Gesture>,
+    cursor_position: (f64, f64),
+}
+impl Resource for Gestures {}
+
+impl Gestures {
+    ///
+    /// Feeds the window events of a frame to the recognizer, they all happened at `time`
+    ///
+    pub fn update(&mut self, events: &[WindowEvent], time: f64) {
+        self.gestures.clear();
+
+        for event in events {
+            let pointer_event = match *event {
+                WindowEvent::CursorPos(x, y) => {
+                    self.cursor_position = (x, y);
+                    PointerEvent::Move {
+                        position: (x, y),
+                        time,
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                    PointerEvent::Down {
+                        position: self.cursor_position,
+                        time,
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                    PointerEvent::Up {
+                        position: self.cursor_position,
+                        time,
+                    }
+                }
+                _ => continue,
+            };
+            self.gestures.extend(self.recognizer.feed(pointer_event));
+        }
+    }
+
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    pub fn swiped(&self, direction: SwipeDirection) -> bool {
+        self.gestures.iter().any(|gesture| {
+            matches!(gesture, Gesture::Swipe { direction: swiped, .. } if *swiped == direction)
+        })
+    }
+
+    pub fn tapped(&self) -> Option<(f64, f64)> {
+        self.gestures.iter().find_map(|gesture| match gesture {
+            Gesture::Tap { position } => Some(*position),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn down(x: f64, y: f64, time: f64) -> PointerEvent {
+        PointerEvent::Down {
+            position: (x, y),
+            time,
+        }
+    }
+
+    fn drag(x: f64, y: f64, time: f64) -> PointerEvent {
+        PointerEvent::Move {
+            position: (x, y),
+            time,
+        }
+    }
+
+    fn up(x: f64, y: f64, time: f64) -> PointerEvent {
+        PointerEvent::Up {
+            position: (x, y),
+            time,
+        }
+    }
+
+    fn recognize(events: Vec<PointerEvent>) -> Vec<Gesture> {
+        let mut recognizer = GestureRecognizer::default();
+        events
+            .into_iter()
+            .filter_map(|event| recognizer.feed(event))
+            .collect()
+    }
+
+    #[test]
+    fn it_should_swipe_in_the_dominant_direction() {
+        #[rustfmt::skip]
+        let cases = [
+            ((-80.0, 10.0), SwipeDirection::Left),
+            ((80.0, -30.0), SwipeDirection::Right),
+            ((20.0, -60.0), SwipeDirection::Up),
+            ((-10.0, 90.0), SwipeDirection::Down),
+        ];
+
+        for ((dx, dy), expected) in cases {
+            let gestures = recognize(vec![
+                down(100.0, 100.0, 1.0),
+                drag(100.0 + dx, 100.0 + dy, 1.1),
+                up(100.0 + dx, 100.0 + dy, 1.2),
+            ]);
+
+            assert!(
+                matches!(gestures.as_slice(), [Gesture::Swipe { direction, .. }] if *direction == expected),
+                "{:?}",
+                gestures
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_swipe_once_per_stroke_before_the_release() {
+        let mut recognizer = GestureRecognizer::default();
+
+        assert_eq!(recognizer.feed(down(0.0, 0.0, 0.0)), None);
+        assert_eq!(recognizer.feed(drag(20.0, 0.0, 0.05)), None);
+        assert_eq!(
+            recognizer.feed(drag(60.0, 0.0, 0.1)),
+            Some(Gesture::Swipe {
+                direction: SwipeDirection::Right,
+                velocity: 600.0
+            })
+        );
+        assert_eq!(recognizer.feed(drag(200.0, 0.0, 0.15)), None);
+        assert_eq!(recognizer.feed(up(200.0, 0.0, 0.2)), None);
+    }
+
+    #[test]
+    fn it_should_ignore_slow_drags_and_moves_without_press() {
+        let gestures = recognize(vec![
+            drag(0.0, 0.0, 0.0),
+            drag(300.0, 0.0, 0.1),
+            down(300.0, 0.0, 1.0),
+            drag(380.0, 0.0, 2.0),
+            up(380.0, 0.0, 3.0),
+        ]);
+
+        assert!(gestures.is_empty());
+    }
+
+    #[test]
+    fn it_should_tap_on_short_presses() {
+        let gestures = recognize(vec![
+            down(10.0, 10.0, 0.0),
+            drag(12.0, 11.0, 0.05),
+            up(12.0, 11.0, 0.1),
+            // held too long
+            down(10.0, 10.0, 1.0),
+            up(10.0, 10.0, 2.0),
+        ]);
+
+        assert_eq!(
+            gestures,
+            vec![Gesture::Tap {
+                position: (12.0, 11.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_recognize_gestures_from_window_events() {
+        let mut gestures = Gestures::default();
+        let press = |action| {
+            WindowEvent::MouseButton(MouseButton::Button1, action, glfw::Modifiers::empty())
+        };
+
+        gestures.update(
+            &[WindowEvent::CursorPos(200.0, 300.0), press(Action::Press)],
+            0.0,
+        );
+        assert!(gestures.gestures().is_empty());
+
+        gestures.update(&[WindowEvent::CursorPos(200.0, 200.0)], 0.1);
+        assert!(gestures.swiped(SwipeDirection::Up));
+        assert!(!gestures.swiped(SwipeDirection::Down));
+
+        gestures.update(&[press(Action::Release)], 0.2);
+        assert!(gestures.gestures().is_empty());
+    }
+}
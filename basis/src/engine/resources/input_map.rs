@@ -0,0 +1,595 @@
+use std::{fmt, fs, str::FromStr};
+
+use glfw::{GamepadAxis, GamepadButton, Key, Modifiers, MouseButton};
+
+use crate::{
+    graphics::window::{GAMEPAD_AXIS_NAMES, GAMEPAD_BUTTON_NAMES},
+    prelude::*,
+};
+
+///
+/// Keys accepted in a bindings file, named after their `glfw::Key` variant
+///
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        pub(crate) const KEYS: &[(Key, &str)] = &[$((Key::$key, stringify!($key))),*];
+    };
+}
+
+#[rustfmt::skip]
+key_names![
+    Space, Apostrophe, Comma, Minus, Period, Slash,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    Semicolon, Equal,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+    Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up,
+    PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13,
+    F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+    KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+    LeftShift, LeftControl, LeftAlt, LeftSuper,
+    RightShift, RightControl, RightAlt, RightSuper, Menu,
+];
+
+const MODIFIERS: &[(Modifiers, &str)] = &[
+    (Modifiers::Control, "Ctrl"),
+    (Modifiers::Shift, "Shift"),
+    (Modifiers::Alt, "Alt"),
+    (Modifiers::Super, "Super"),
+];
+
+const MOUSE_BUTTONS: &[(MouseButton, &str)] = &[
+    (MouseButton::Button1, "Left"),
+    (MouseButton::Button2, "Right"),
+    (MouseButton::Button3, "Middle"),
+    (MouseButton::Button4, "4"),
+    (MouseButton::Button5, "5"),
+    (MouseButton::Button6, "6"),
+    (MouseButton::Button7, "7"),
+    (MouseButton::Button8, "8"),
+];
+
+const SWIPES: &[(SwipeDirection, &str)] = &[
+    (SwipeDirection::Left, "Left"),
+    (SwipeDirection::Right, "Right"),
+    (SwipeDirection::Up, "Up"),
+    (SwipeDirection::Down, "Down"),
+];
+
+///
+/// Physical input an action can be bound to
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// The key pressed with exactly these modifiers
+    Key {
+        key: Key,
+        modifiers: Modifiers,
+    },
+    MouseButton(MouseButton),
+    /// The button on any connected gamepad
+    GamepadButton(GamepadButton),
+    /// A stick or trigger of any connected gamepad pushed past `AXIS_PRESS_THRESHOLD`, towards
+    /// the positive or negative values
+    GamepadAxis {
+        axis: GamepadAxis,
+        positive: bool,
+    },
+    /// A swipe of the `Gestures`, held and pressed during the frame it is recognized
+    Swipe(SwipeDirection),
+}
+
+impl Binding {
+    pub fn key(key: Key) -> Binding {
+        Binding::Key {
+            key,
+            modifiers: Modifiers::empty(),
+        }
+    }
+
+    ///
+    /// Reads the `EventHandler`, the `Gamepads` and the `Gestures`
+    ///
+    pub fn held(&self, resources: &ResourcesManager) -> bool {
+        match *self {
+            Binding::Key { key, modifiers } => resources.get::<EventHandler>().hold(key, modifiers),
+            Binding::MouseButton(button) => {
+                resources.get::<EventHandler>().mouse_button_hold(button)
+            }
+            Binding::GamepadButton(button) => resources.get::<Gamepads>().held(button),
+            Binding::GamepadAxis { axis, positive } => {
+                resources.get::<Gamepads>().axis_held(axis, positive)
+            }
+            Binding::Swipe(direction) => resources.get::<Gestures>().swiped(direction),
+        }
+    }
+
+    pub fn pressed(&self, resources: &ResourcesManager) -> bool {
+        match *self {
+            Binding::Key { key, modifiers } => {
+                resources.get::<EventHandler>().pressed(key, modifiers)
+            }
+            Binding::MouseButton(button) => {
+                resources.get::<EventHandler>().mouse_button_pressed(button)
+            }
+            Binding::GamepadButton(button) => resources.get::<Gamepads>().just_pressed(button),
+            Binding::GamepadAxis { axis, positive } => resources
+                .get::<Gamepads>()
+                .axis_just_pressed(axis, positive),
+            Binding::Swipe(direction) => resources.get::<Gestures>().swiped(direction),
+        }
+    }
+
+    pub fn released(&self, resources: &ResourcesManager) -> bool {
+        match *self {
+            Binding::Key { key, modifiers } => {
+                resources.get::<EventHandler>().released(key, modifiers)
+            }
+            Binding::MouseButton(button) => resources
+                .get::<EventHandler>()
+                .mouse_button_released(button),
+            Binding::GamepadButton(button) => resources.get::<Gamepads>().just_released(button),
+            Binding::GamepadAxis { axis, positive } => resources
+                .get::<Gamepads>()
+                .axis_just_released(axis, positive),
+            Binding::Swipe(_) => false,
+        }
+    }
+}
+
+///
+/// Parses the names written by `Display`: `W`, `Ctrl+W`, `Ctrl+Shift+Up`, `Mouse:Left`,
+/// `Mouse:4`, `Gamepad:A`, `Gamepad:DpadUp`, `Gamepad:LeftX-`, `Gamepad:RightTrigger+`,
+/// `Swipe:Left`. The case is ignored.
+///
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((device, button)) = s.split_once(':') {
+            let device = device.trim();
+            if device.eq_ignore_ascii_case("Mouse") {
+                return find_by_name(MOUSE_BUTTONS, button)
+                    .map(Binding::MouseButton)
+                    .ok_or_else(|| format!("Unknown mouse button '{}'", button.trim()));
+            }
+            if device.eq_ignore_ascii_case("Swipe") {
+                return find_by_name(SWIPES, button)
+                    .map(Binding::Swipe)
+                    .ok_or_else(|| format!("Unknown swipe direction '{}'", button.trim()));
+            }
+            if !device.eq_ignore_ascii_case("Gamepad") {
+                return Err(format!("Unknown device '{}'", device));
+            }
+
+            let button = button.trim();
+            let direction = [("+", true), ("-", false)]
+                .into_iter()
+                .find_map(|(sign, positive)| Some((button.strip_suffix(sign)?, positive)));
+            return match direction {
+                Some((axis, positive)) => find_by_name(&GAMEPAD_AXIS_NAMES, axis)
+                    .map(|axis| Binding::GamepadAxis { axis, positive })
+                    .ok_or_else(|| format!("Unknown gamepad axis '{}'", axis.trim())),
+                None => find_by_name(&GAMEPAD_BUTTON_NAMES, button)
+                    .map(Binding::GamepadButton)
+                    .ok_or_else(|| format!("Unknown gamepad button '{}'", button)),
+            };
+        }
+
+        let mut parts = s.split('+').collect::<Vec<&str>>();
+        let key = parts.pop().unwrap_or_default();
+        let mut modifiers = Modifiers::empty();
+        for modifier in parts {
+            let alias = modifier.trim().eq_ignore_ascii_case("Control");
+            modifiers |= find_by_name(MODIFIERS, if alias { "Ctrl" } else { modifier })
+                .ok_or_else(|| format!("Unknown modifier '{}'", modifier.trim()))?;
+        }
+        let key = find_by_name(KEYS, key).ok_or_else(|| format!("Unknown key '{}'", key.trim()))?;
+
+        Ok(Binding::Key { key, modifiers })
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Binding::Key { key, modifiers } => {
+                for (modifier, modifier_name) in MODIFIERS {
+                    if modifiers.contains(*modifier) {
+                        write!(f, "{}+", modifier_name)?;
+                    }
+                }
+                write!(f, "{}", name_of(KEYS, key))
+            }
+            Binding::MouseButton(button) => write!(f, "Mouse:{}", name_of(MOUSE_BUTTONS, button)),
+            Binding::GamepadButton(button) => {
+                write!(f, "Gamepad:{}", name_of(&GAMEPAD_BUTTON_NAMES, button))
+            }
+            Binding::GamepadAxis { axis, positive } => write!(
+                f,
+                "Gamepad:{}{}",
+                name_of(&GAMEPAD_AXIS_NAMES, axis),
+                if positive { "+" } else { "-" }
+            ),
+            Binding::Swipe(direction) => write!(f, "Swipe:{}", name_of(SWIPES, direction)),
+        }
+    }
+}
+
+fn find_by_name<T: Copy>(table: &[(T, &str)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(_, candidate)| candidate.eq_ignore_ascii_case(name.trim()))
+        .map(|(value, _)| *value)
+}
+
+fn name_of<T: PartialEq>(table: &[(T, &'static str)], value: T) -> &'static str {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == value)
+        .map_or("Unknown", |(_, name)| *name)
+}
+
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+impl std::error::Error for InputMapError {}
+
+impl From<std::io::Error> for InputMapError {
+    fn from(err: std::io::Error) -> Self {
+        InputMapError::Io(err)
+    }
+}
+
+impl fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputMapError::Io(err) => write!(f, "Failed to read the bindings: {}", err),
+            InputMapError::Parse { line, message } => write!(f, "{}: {}", line, message),
+        }
+    }
+}
+
+///
+/// Binds the actions of a game, an enum like `Jump` or `MoveLeft`, to keys, mouse buttons and
+/// gamepads.
+/// Systems query the actions instead of the keys, so a mapping shared by several systems is
+/// written once and players can rebind it.
+///
+/// Bindings files list the bindings of one action per line, `#` starts a comment:
+///
+/// ```text
+/// MoveForward = W, Up, Gamepad:LeftY-
+/// Jump = Space, Mouse:Left, Gamepad:A
+/// Pause = Escape
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputMap<A> {
+    /// In the order of the bindings file, kept when saving it back
+    bindings: Vec<(A, Vec<Binding>)>,
+}
+impl<A: 'static> Resource for InputMap<A> {}
+
+impl<A> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<A: Copy + PartialEq> InputMap<A> {
+    pub fn bind(&mut self, action: A, binding: Binding) -> &mut Self {
+        let bindings = self.entry(action);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    pub fn unbind(&mut self, action: A, binding: Binding) -> &mut Self {
+        if let Some((_, bindings)) = self.bindings.iter_mut().find(|(other, _)| *other == action) {
+            bindings.retain(|other| *other != binding);
+        }
+        self
+    }
+
+    ///
+    /// Replaces every binding of `action`
+    ///
+    pub fn rebind(&mut self, action: A, bindings: Vec<Binding>) -> &mut Self {
+        *self.entry(action) = bindings;
+        self
+    }
+
+    pub fn bindings(&self, action: A) -> &[Binding] {
+        self.bindings
+            .iter()
+            .find(|(other, _)| *other == action)
+            .map_or(&[], |(_, bindings)| bindings.as_slice())
+    }
+
+    fn entry(&mut self, action: A) -> &mut Vec<Binding> {
+        let index = match self.bindings.iter().position(|(other, _)| *other == action) {
+            Some(index) => index,
+            None => {
+                self.bindings.push((action, Vec::new()));
+                self.bindings.len() - 1
+            }
+        };
+        &mut self.bindings[index].1
+    }
+
+    ///
+    /// Whether one of the bindings of `action` is held
+    ///
+    pub fn action_held(&self, action: A, resources: &ResourcesManager) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.held(resources))
+    }
+
+    ///
+    /// Whether one of the bindings of `action` was pressed this frame
+    ///
+    pub fn action_pressed(&self, action: A, resources: &ResourcesManager) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.pressed(resources))
+    }
+
+    ///
+    /// Whether one of the bindings of `action` was released this frame
+    ///
+    pub fn action_released(&self, action: A, resources: &ResourcesManager) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.released(resources))
+    }
+}
+
+impl<A: Copy + PartialEq + FromStr> InputMap<A> {
+    ///
+    /// Reads the bindings from a bindings file, the actions are parsed with `A::from_str`
+    ///
+    pub fn parse(config: &str) -> Result<InputMap<A>, InputMapError> {
+        let mut input_map = InputMap::default();
+
+        for (index, line) in config.lines().enumerate() {
+            let error = |message: String| InputMapError::Parse {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (action, bindings) = line
+                .split_once('=')
+                .ok_or_else(|| error("Expected 'Action = Binding, ...'".to_string()))?;
+            let action = action
+                .trim()
+                .parse::<A>()
+                .map_err(|_| error(format!("Unknown action '{}'", action.trim())))?;
+            // an action without binding is kept, so it is still listed when saved
+            input_map.entry(action);
+            for binding in bindings
+                .split(',')
+                .filter(|binding| !binding.trim().is_empty())
+            {
+                input_map.bind(action, binding.parse().map_err(error)?);
+            }
+        }
+
+        Ok(input_map)
+    }
+
+    pub fn load(path: &str) -> Result<InputMap<A>, InputMapError> {
+        InputMap::parse(&fs::read_to_string(path)?)
+    }
+}
+
+impl<A: Copy + PartialEq + fmt::Display> InputMap<A> {
+    ///
+    /// Writes the bindings in the format read by `parse`
+    ///
+    pub fn to_config(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|(action, bindings)| {
+                let bindings = bindings
+                    .iter()
+                    .map(Binding::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("{} = {}\n", action, bindings)
+            })
+            .collect()
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), InputMapError> {
+        Ok(fs::write(path, self.to_config())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glfw::{Action, WindowEvent};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum TestAction {
+        Jump,
+        MoveForward,
+        Pause,
+    }
+
+    impl FromStr for TestAction {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "Jump" => Ok(TestAction::Jump),
+                "MoveForward" => Ok(TestAction::MoveForward),
+                "Pause" => Ok(TestAction::Pause),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl fmt::Display for TestAction {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    fn resources_with(events: Vec<WindowEvent>) -> ResourcesManager {
+        let mut event_handler = EventHandler::new();
+        event_handler.update(events);
+        event_handler.process();
+
+        let mut resources = ResourcesManager::default();
+        resources.add(event_handler);
+        resources.add(Gamepads::default());
+        resources.add(Gestures::default());
+        resources
+    }
+
+    #[test]
+    fn it_should_parse_bindings() {
+        let config = "# movement\nMoveForward = W, ctrl+shift+up\n\nJump = Space, Mouse:Left # both\nPause =\n";
+
+        let input_map = InputMap::<TestAction>::parse(config).expect("This should work");
+
+        assert_eq!(
+            input_map.bindings(TestAction::MoveForward),
+            &[
+                Binding::key(Key::W),
+                Binding::Key {
+                    key: Key::Up,
+                    modifiers: Modifiers::Control | Modifiers::Shift,
+                },
+            ]
+        );
+        assert_eq!(
+            input_map.bindings(TestAction::Jump),
+            &[
+                Binding::key(Key::Space),
+                Binding::MouseButton(MouseButton::Button1)
+            ]
+        );
+        assert!(input_map.bindings(TestAction::Pause).is_empty());
+        assert_eq!(
+            input_map.to_config(),
+            "MoveForward = W, Ctrl+Shift+Up\nJump = Space, Mouse:Left\nPause = \n"
+        );
+        assert_eq!(
+            InputMap::parse(&input_map.to_config()).ok(),
+            Some(input_map)
+        );
+    }
+
+    #[test]
+    fn it_should_report_the_line_of_invalid_bindings() {
+        let error = |config: &str| {
+            InputMap::<TestAction>::parse(config)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(error("Jump = Space\nFly = F"), "2: Unknown action 'Fly'");
+        assert_eq!(error("Jump = Spacebar"), "1: Unknown key 'Spacebar'");
+        assert_eq!(error("Jump = Hyper+J"), "1: Unknown modifier 'Hyper'");
+        assert_eq!(error("Jump = Mouse:9"), "1: Unknown mouse button '9'");
+        assert_eq!(error("Jump = Gamepad:Z"), "1: Unknown gamepad button 'Z'");
+        assert_eq!(
+            error("Jump = Gamepad:Wheel+"),
+            "1: Unknown gamepad axis 'Wheel'"
+        );
+        assert_eq!(error("Jump = Pedal:1"), "1: Unknown device 'Pedal'");
+        assert_eq!(
+            error("Jump = Swipe:Back"),
+            "1: Unknown swipe direction 'Back'"
+        );
+        assert_eq!(error("\nJump"), "2: Expected 'Action = Binding, ...'");
+    }
+
+    #[test]
+    fn it_should_query_actions_through_their_bindings() {
+        let mut input_map = InputMap::default();
+        input_map
+            .bind(TestAction::Jump, Binding::key(Key::Space))
+            .bind(TestAction::Jump, Binding::MouseButton(MouseButton::Button1))
+            .bind(TestAction::Pause, Binding::key(Key::Escape));
+
+        let resources = resources_with(vec![
+            WindowEvent::MouseButton(MouseButton::Button1, Action::Press, Modifiers::empty()),
+            WindowEvent::Key(Key::Escape, 0, Action::Press, Modifiers::empty()),
+            WindowEvent::Key(Key::Escape, 0, Action::Release, Modifiers::empty()),
+        ]);
+
+        assert!(input_map.action_held(TestAction::Jump, &resources));
+        assert!(input_map.action_pressed(TestAction::Jump, &resources));
+        assert!(!input_map.action_released(TestAction::Jump, &resources));
+        assert!(!input_map.action_held(TestAction::Pause, &resources));
+        assert!(input_map.action_released(TestAction::Pause, &resources));
+        assert!(!input_map.action_held(TestAction::MoveForward, &resources));
+
+        input_map.rebind(TestAction::Jump, vec![Binding::key(Key::Space)]);
+        assert!(!input_map.action_held(TestAction::Jump, &resources));
+    }
+
+    #[test]
+    fn it_should_bind_gamepad_buttons_and_axes() {
+        let input_map = InputMap::<TestAction>::parse(
+            "Jump = Gamepad:a, Gamepad:DpadUp\nMoveForward = gamepad:LeftY-\n",
+        )
+        .expect("This should work");
+        assert_eq!(
+            input_map.to_config(),
+            "Jump = Gamepad:A, Gamepad:DpadUp\nMoveForward = Gamepad:LeftY-\n"
+        );
+
+        let mut resources = resources_with(vec![]);
+        resources.get_mut::<Gamepads>().update(vec![GamepadInput {
+            id: 0,
+            name: "Pad".to_string(),
+            buttons: vec![GamepadButton::ButtonDpadUp],
+            axes: [0.0, -0.8, 0.0, 0.0, -1.0, -1.0],
+        }]);
+
+        assert!(input_map.action_pressed(TestAction::Jump, &resources));
+        assert!(input_map.action_held(TestAction::MoveForward, &resources));
+        assert!(!input_map.action_held(TestAction::Pause, &resources));
+    }
+
+    #[test]
+    fn it_should_bind_swipes() {
+        let input_map =
+            InputMap::<TestAction>::parse("Jump = Space, swipe:up\n").expect("This should work");
+        assert_eq!(input_map.to_config(), "Jump = Space, Swipe:Up\n");
+
+        let mut resources = resources_with(vec![]);
+        let button =
+            |action| WindowEvent::MouseButton(MouseButton::Button1, action, Modifiers::empty());
+        resources.get_mut::<Gestures>().update(
+            &[
+                WindowEvent::CursorPos(100.0, 300.0),
+                button(Action::Press),
+                WindowEvent::CursorPos(100.0, 100.0),
+            ],
+            1.0,
+        );
+
+        assert!(input_map.action_pressed(TestAction::Jump, &resources));
+        assert!(!input_map.action_released(TestAction::Jump, &resources));
+    }
+}
@@ -1,7 +1,41 @@
+mod background;
+mod camera;
+mod cursor;
 mod deltatime;
 mod event_handler;
+mod fog;
+mod frame_capture;
+mod frame_stats;
+mod frame_uniforms;
+mod gamepads;
+mod gestures;
+mod input_map;
+mod post_process;
+mod post_process_config;
+mod random;
+mod screen;
 mod shader;
+mod shadow_map;
+mod text_input;
+mod window_settings;
 
+pub use background::*;
+pub use camera::*;
+pub use cursor::*;
 pub use deltatime::*;
 pub use event_handler::*;
+pub use fog::*;
+pub use frame_capture::*;
+pub use frame_stats::*;
+pub use frame_uniforms::*;
+pub use gamepads::*;
+pub use gestures::*;
+pub use input_map::*;
+pub use post_process::*;
+pub use post_process_config::*;
+pub use random::*;
+pub use screen::*;
 pub use shader::*;
+pub use shadow_map::*;
+pub use text_input::*;
+pub use window_settings::*;
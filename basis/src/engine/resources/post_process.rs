@@ -0,0 +1,134 @@
+use std::{ffi::c_void, mem, ptr};
+
+use crate::graphics::{glw, shader_program::ShaderProgram};
+use crate::prelude::*;
+
+const FULLSCREEN_VERTEX_SHADER: &str = "basis/src/assets/shaders/vertex_fullscreen_shader.glsl";
+
+///
+/// Two triangles covering the whole screen, each vertex is a position followed by its texture
+/// coordinates
+///
+#[rustfmt::skip]
+const FULLSCREEN_QUAD: [f32; 24] = [
+    -1.0,  1.0, 0.0, 1.0,
+    -1.0, -1.0, 0.0, 0.0,
+     1.0, -1.0, 1.0, 0.0,
+
+    -1.0,  1.0, 0.0, 1.0,
+     1.0, -1.0, 1.0, 0.0,
+     1.0,  1.0, 1.0, 1.0,
+];
+
+///
+/// GPU resources used to render the scene offscreen and post-process it. The scene is rendered
+/// into `scene`, then each effect reads the previous result and writes into one of the two
+/// `ping_pong` targets, the last one writes into the window.
+///
+pub struct PostProcessStack {
+    pub scene: glw::RenderTarget,
+    pub ping_pong: [glw::RenderTarget; 2],
+    pub quad: glw::Vao,
+    /// Vertices read through `quad`
+    pub quad_vertices: glw::BufferObject,
+
+    pub copy_shader: ShaderProgram,
+    pub tonemapping_shader: ShaderProgram,
+    pub speed_lines_shader: ShaderProgram,
+    pub vignette_shader: ShaderProgram,
+    pub fxaa_shader: ShaderProgram,
+}
+impl Resource for PostProcessStack {}
+
+impl PostProcessStack {
+    pub fn new(width: i32, height: i32) -> Self {
+        let (quad, quad_vertices) = fullscreen_quad();
+        let render_target = |internal_format| {
+            glw::RenderTarget::new(width, height, internal_format)
+                .expect("Post-processing render target to be complete")
+        };
+
+        Self {
+            // floating point so tonemapping has the colors brighter than 1.0
+            scene: render_target(gl::RGBA16F),
+            ping_pong: [render_target(gl::RGBA8), render_target(gl::RGBA8)],
+            quad,
+            quad_vertices,
+
+            copy_shader: fullscreen_shader("basis/src/assets/shaders/fragment_copy_shader.glsl"),
+            tonemapping_shader: fullscreen_shader(
+                "basis/src/assets/shaders/fragment_tonemapping_shader.glsl",
+            ),
+            speed_lines_shader: fullscreen_shader(
+                "basis/src/assets/shaders/fragment_speed_lines_shader.glsl",
+            ),
+            vignette_shader: fullscreen_shader(
+                "basis/src/assets/shaders/fragment_vignette_shader.glsl",
+            ),
+            fxaa_shader: fullscreen_shader("basis/src/assets/shaders/fragment_fxaa_shader.glsl"),
+        }
+    }
+
+    ///
+    /// Keeps the render targets the same size as the window framebuffer
+    ///
+    pub fn resize(&mut self, width: i32, height: i32) {
+        self.scene.resize(width, height);
+        for target in self.ping_pong.iter_mut() {
+            target.resize(width, height);
+        }
+    }
+
+    pub fn shader(&self, effect: PostEffect) -> &glw::Shader {
+        match effect {
+            PostEffect::Tonemapping => &self.tonemapping_shader,
+            PostEffect::SpeedLines => &self.speed_lines_shader,
+            PostEffect::Vignette => &self.vignette_shader,
+            PostEffect::Fxaa => &self.fxaa_shader,
+        }
+    }
+
+    pub fn shaders_mut(&mut self) -> [&mut ShaderProgram; 5] {
+        [
+            &mut self.copy_shader,
+            &mut self.tonemapping_shader,
+            &mut self.speed_lines_shader,
+            &mut self.vignette_shader,
+            &mut self.fxaa_shader,
+        ]
+    }
+}
+
+fn fullscreen_shader(fragment_shader: &str) -> ShaderProgram {
+    ShaderProgram::new(FULLSCREEN_VERTEX_SHADER, fragment_shader)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+fn fullscreen_quad() -> (glw::Vao, glw::BufferObject) {
+    let vao = glw::Vao::default();
+    vao.bind();
+    let vbo = glw::BufferObject::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
+    vbo.bind();
+    vbo.store_f32(&FULLSCREEN_QUAD);
+
+    let stride_length = (4 * mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizei;
+    let start_pointer = ptr::null::<gl::types::GLfloat>();
+    let position_attribute = glw::VertexAttribute::new(
+        0,
+        2,
+        gl::FLOAT,
+        gl::FALSE,
+        stride_length,
+        start_pointer as *const c_void,
+    );
+    position_attribute.enable();
+
+    let texture_coordinate_attribute =
+        glw::VertexAttribute::new(1, 2, gl::FLOAT, gl::FALSE, stride_length, unsafe {
+            start_pointer.add(2) as *const c_void
+        });
+    texture_coordinate_attribute.enable();
+
+    vao.unbind();
+    (vao, vbo)
+}
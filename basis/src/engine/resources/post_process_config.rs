@@ -0,0 +1,118 @@
+use crate::prelude::*;
+
+///
+/// Post-processing effects applied to the rendered scene, in the order they are applied
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostEffect {
+    Tonemapping,
+    SpeedLines,
+    Vignette,
+    Fxaa,
+}
+
+///
+/// Which post-processing effects are enabled, it can be changed at any time by the systems
+///
+#[derive(Debug, Clone, Copy)]
+pub struct PostProcessConfig {
+    pub tonemapping: bool,
+    /// Scales the scene colors before they are mapped to the displayable range
+    pub exposure: f32,
+
+    pub vignette: bool,
+    /// How dark the corners of the screen get, from 0.0 to 1.0
+    pub vignette_strength: f32,
+
+    pub speed_lines: bool,
+    /// From 0.0 to 1.0, how close the runner is to its top speed. Nothing is drawn at 0.0
+    pub speed: f32,
+
+    pub fxaa: bool,
+}
+impl Resource for PostProcessConfig {}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            tonemapping: true,
+            exposure: 1.0,
+            vignette: true,
+            vignette_strength: 0.3,
+            speed_lines: true,
+            speed: 0.0,
+            fxaa: true,
+        }
+    }
+}
+
+impl PostProcessConfig {
+    ///
+    /// The effects to apply this frame. Anti-aliasing is done last, once the colors are final
+    ///
+    pub fn enabled_effects(&self) -> Vec<PostEffect> {
+        let mut effects = Vec::new();
+
+        if self.tonemapping {
+            effects.push(PostEffect::Tonemapping);
+        }
+        if self.speed_lines && self.speed > 0.0 {
+            effects.push(PostEffect::SpeedLines);
+        }
+        if self.vignette && self.vignette_strength > 0.0 {
+            effects.push(PostEffect::Vignette);
+        }
+        if self.fxaa {
+            effects.push(PostEffect::Fxaa);
+        }
+        effects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_order_enabled_effects() {
+        let config = PostProcessConfig {
+            speed: 0.5,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.enabled_effects(),
+            vec![
+                PostEffect::Tonemapping,
+                PostEffect::SpeedLines,
+                PostEffect::Vignette,
+                PostEffect::Fxaa
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_skip_disabled_effects() {
+        let config = PostProcessConfig {
+            tonemapping: false,
+            vignette_strength: 0.0,
+            fxaa: false,
+            ..Default::default()
+        };
+
+        assert_eq!(config.enabled_effects(), vec![]);
+    }
+
+    #[test]
+    fn it_should_only_draw_speed_lines_when_moving() {
+        let mut config = PostProcessConfig::default();
+
+        assert!(!config.enabled_effects().contains(&PostEffect::SpeedLines));
+
+        config.speed = 1.0;
+        assert!(config.enabled_effects().contains(&PostEffect::SpeedLines));
+
+        config.speed_lines = false;
+        assert!(!config.enabled_effects().contains(&PostEffect::SpeedLines));
+    }
+}
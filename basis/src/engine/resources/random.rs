@@ -0,0 +1,51 @@
+use std::ops::{Deref, DerefMut};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::prelude::*;
+
+///
+/// Random number generator of the game, the systems draw from it instead of `rand::rng()` so a
+/// run is reproduced exactly from its seed. Use it through the `rand::Rng` trait.
+///
+#[derive(Debug, Clone)]
+pub struct Random {
+    seed: u64,
+    rng: StdRng,
+}
+impl Resource for Random {}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    ///
+    /// The seed the generator started from, the values already drawn are not taken into account
+    ///
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Random::new(rand::random())
+    }
+}
+
+impl Deref for Random {
+    type Target = StdRng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+impl DerefMut for Random {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
@@ -0,0 +1,136 @@
+use crate::prelude::*;
+
+///
+/// New size of the window, in screen coordinates and in pixels
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowResized {
+    pub width: i32,
+    pub height: i32,
+    pub framebuffer_width: i32,
+    pub framebuffer_height: i32,
+}
+
+///
+/// Size of the window as of the last frame. The framebuffer size is in pixels, it is larger
+/// than the window size on HiDPI screens and is the one to draw with.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    size: (i32, i32),
+    framebuffer_size: (i32, i32),
+    content_scale: (f32, f32),
+    monitors: Vec<MonitorInfo>,
+    resized: Option<WindowResized>,
+}
+impl Resource for Screen {}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self {
+            size: (0, 0),
+            framebuffer_size: (0, 0),
+            content_scale: (1.0, 1.0),
+            monitors: Vec::new(),
+            resized: None,
+        }
+    }
+}
+
+impl Screen {
+    ///
+    /// Records the sizes read from the window this frame, `resized` reports whether they changed
+    ///
+    pub fn update(
+        &mut self,
+        size: (i32, i32),
+        framebuffer_size: (i32, i32),
+        content_scale: (f32, f32),
+    ) {
+        self.resized = (size != self.size || framebuffer_size != self.framebuffer_size).then_some(
+            WindowResized {
+                width: size.0,
+                height: size.1,
+                framebuffer_width: framebuffer_size.0,
+                framebuffer_height: framebuffer_size.1,
+            },
+        );
+        self.size = size;
+        self.framebuffer_size = framebuffer_size;
+        self.content_scale = content_scale;
+    }
+
+    pub fn set_monitors(&mut self, monitors: Vec<MonitorInfo>) {
+        self.monitors = monitors;
+    }
+
+    ///
+    /// The resize of the last frame, also sent on the first frame
+    ///
+    pub fn resized(&self) -> Option<WindowResized> {
+        self.resized
+    }
+
+    pub fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    pub fn framebuffer_size(&self) -> (i32, i32) {
+        self.framebuffer_size
+    }
+
+    pub fn content_scale(&self) -> (f32, f32) {
+        self.content_scale
+    }
+
+    ///
+    /// Width over height of the framebuffer, 1 while the window is minimized
+    ///
+    pub fn aspect_ratio(&self) -> f32 {
+        if self.is_minimized() {
+            return 1.0;
+        }
+        self.framebuffer_size.0 as f32 / self.framebuffer_size.1 as f32
+    }
+
+    ///
+    /// A minimized window has an empty framebuffer, there is nothing to draw
+    ///
+    pub fn is_minimized(&self) -> bool {
+        self.framebuffer_size.0 <= 0 || self.framebuffer_size.1 <= 0
+    }
+
+    pub fn monitors(&self) -> &[MonitorInfo] {
+        &self.monitors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_report_resizes_once() {
+        let mut screen = Screen::default();
+
+        screen.update((800, 600), (1600, 1200), (2.0, 2.0));
+        assert_eq!(
+            screen.resized(),
+            Some(WindowResized {
+                width: 800,
+                height: 600,
+                framebuffer_width: 1600,
+                framebuffer_height: 1200
+            })
+        );
+        assert_eq!(screen.aspect_ratio(), 1600.0 / 1200.0);
+
+        screen.update((800, 600), (1600, 1200), (2.0, 2.0));
+        assert_eq!(screen.resized(), None);
+
+        screen.update((800, 600), (0, 0), (2.0, 2.0));
+        assert!(screen.resized().is_some());
+        assert!(screen.is_minimized());
+        assert_eq!(screen.aspect_ratio(), 1.0);
+    }
+}
@@ -1,27 +1,25 @@
 use std::ops::{Deref, DerefMut};
 
 pub use crate::engine::prelude::*;
+use crate::graphics::shader_program::ShaderProgram;
 
-pub struct RenderShader(graphics::glw::Shader);
+pub struct RenderShader(ShaderProgram);
 impl Resource for RenderShader {}
 
 impl Default for RenderShader {
     fn default() -> Self {
-        let shader = glw::Shader::default();
-        shader
-            .link_multiple(vec![
-                glw::ShaderType::Vertex("basis/src/assets/shaders/vertex_perspective_shader.glsl"),
-                glw::ShaderType::Fragment(
-                    "basis/src/assets/shaders/fragment_perspective_shader.glsl",
-                ),
-            ])
-            .expect("Shader to be found, compiled and linked");
+        let shader = ShaderProgram::new(
+            "basis/src/assets/shaders/vertex_perspective_shader.glsl",
+            "basis/src/assets/shaders/fragment_perspective_shader.glsl",
+        )
+        .unwrap_or_else(|err| panic!("{}", err))
+        .with_uniform_block(FRAME_BLOCK, FRAME_BLOCK_BINDING);
         Self(shader)
     }
 }
 
 impl Deref for RenderShader {
-    type Target = graphics::glw::Shader;
+    type Target = ShaderProgram;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -32,3 +30,74 @@ impl DerefMut for RenderShader {
         &mut self.0
     }
 }
+
+///
+/// Polls the shader sources and rebuilds the programs whose sources changed, enabled in debug
+/// builds. A program that fails to build keeps drawing with its previous version.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderWatcher {
+    pub enabled: bool,
+    /// Seconds between two polls of the source files
+    pub interval: f32,
+    last_poll: Option<f32>,
+}
+impl Resource for ShaderWatcher {}
+
+impl Default for ShaderWatcher {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            interval: 0.5,
+            last_poll: None,
+        }
+    }
+}
+
+impl ShaderWatcher {
+    ///
+    /// Whether the sources should be polled at `time`, in seconds since the start
+    ///
+    pub fn should_poll(&mut self, time: f32) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match self.last_poll {
+            Some(last_poll) if time - last_poll < self.interval => false,
+            _ => {
+                self.last_poll = Some(time);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_poll_once_per_interval() {
+        let mut watcher = ShaderWatcher {
+            enabled: true,
+            ..Default::default()
+        };
+
+        assert!(watcher.should_poll(0.0));
+        assert!(!watcher.should_poll(0.2));
+        assert!(watcher.should_poll(0.5));
+        assert!(!watcher.should_poll(0.9));
+        assert!(watcher.should_poll(1.1));
+    }
+
+    #[test]
+    fn it_should_not_poll_when_disabled() {
+        let mut watcher = ShaderWatcher {
+            enabled: false,
+            ..Default::default()
+        };
+
+        assert!(!watcher.should_poll(0.0));
+        assert!(!watcher.should_poll(10.0));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::graphics::{glw, shader_program::ShaderProgram};
+use crate::prelude::*;
+
+/// Width and height of the shadow map in texels
+pub const SHADOW_MAP_SIZE: i32 = 2048;
+
+///
+/// Depth of the scene as seen from the light, rendered before the scene itself
+///
+pub struct ShadowMap {
+    pub framebuffer: glw::Framebuffer,
+    pub depth_texture: glw::Texture,
+    pub shader: ShaderProgram,
+    pub size: i32,
+}
+impl Resource for ShadowMap {}
+
+impl Default for ShadowMap {
+    fn default() -> Self {
+        let shader = ShaderProgram::new(
+            "basis/src/assets/shaders/vertex_shadow_shader.glsl",
+            "basis/src/assets/shaders/fragment_shadow_shader.glsl",
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+
+        let depth_texture = glw::Texture::new(gl::TEXTURE_2D);
+        depth_texture.bind();
+        depth_texture.tex_image2d(
+            0,
+            gl::DEPTH_COMPONENT as i32,
+            SHADOW_MAP_SIZE,
+            SHADOW_MAP_SIZE,
+            0,
+            gl::DEPTH_COMPONENT,
+            gl::FLOAT,
+            std::ptr::null(),
+        );
+        depth_texture.tex_parameteri(gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+        depth_texture.tex_parameteri(gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+        // everything outside of the shadow map is lit
+        depth_texture.tex_parameteri(gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32);
+        depth_texture.tex_parameteri(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32);
+        depth_texture.tex_parameterfv(gl::TEXTURE_BORDER_COLOR, vec![1.0, 1.0, 1.0, 1.0]);
+        depth_texture.unbind();
+
+        let framebuffer = glw::Framebuffer::default();
+        framebuffer.bind();
+        framebuffer.attach_texture2d(gl::DEPTH_ATTACHMENT, &depth_texture);
+        glw::draw_buffer(gl::NONE);
+        glw::read_buffer(gl::NONE);
+        framebuffer
+            .check_status()
+            .expect("Shadow map framebuffer to be complete");
+        framebuffer.unbind();
+
+        Self {
+            framebuffer,
+            depth_texture,
+            shader,
+            size: SHADOW_MAP_SIZE,
+        }
+    }
+}
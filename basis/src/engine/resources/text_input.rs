@@ -0,0 +1,277 @@
+use glfw::{Action, Key, Modifiers, WindowEvent};
+
+use crate::{graphics::window_backend::WindowBackend, prelude::*};
+
+///
+/// Where the text is copied to and pasted from, the system clipboard of the window
+///
+pub trait Clipboard {
+    fn get(&mut self) -> Option<String>;
+    fn set(&mut self, text: &str);
+}
+
+impl<W: WindowBackend> Clipboard for W {
+    fn get(&mut self) -> Option<String> {
+        self.get_clipboard_string()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.set_clipboard_string(text);
+    }
+}
+
+///
+/// A line of text typed by the player, e.g. a name for the high scores. It only reads the
+/// keyboard while active, the systems reading the keys for something else should check
+/// `is_active`.
+///
+/// Backspace, Delete, Left, Right, Home and End edit the text at the cursor, Enter submits it,
+/// Ctrl+C and Ctrl+X copy and cut the whole text, Ctrl+V pastes.
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// Position of the cursor in chars, from 0 before the first one to the number of chars
+    cursor: usize,
+    active: bool,
+    submitted: bool,
+    /// Chars typed past this limit are dropped
+    pub max_chars: Option<usize>,
+}
+impl Resource for TextInput {}
+
+impl TextInput {
+    ///
+    /// Starts reading the keyboard, the text is kept
+    ///
+    pub fn start(&mut self) {
+        self.active = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// Replaces the text, the cursor goes to its end
+    ///
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.cursor = 0;
+        self.insert(text);
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    ///
+    /// Whether Enter was pressed during the last frame
+    ///
+    pub fn submitted(&self) -> bool {
+        self.submitted
+    }
+
+    ///
+    /// Inserts at the cursor and moves the cursor after the text, the control chars are ignored
+    ///
+    pub fn insert(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if self
+                .max_chars
+                .is_some_and(|max_chars| self.text.chars().count() >= max_chars)
+            {
+                break;
+            }
+            let index = self.byte_index(self.cursor);
+            self.text.insert(index, c);
+            self.cursor += 1;
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.delete();
+        }
+    }
+
+    ///
+    /// Removes the char after the cursor
+    ///
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.chars().count() {
+            let index = self.byte_index(self.cursor);
+            self.text.remove(index);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.text.chars().count();
+    }
+
+    ///
+    /// Applies the typed chars and editing keys of the frame, when active
+    ///
+    pub fn update(&mut self, events: &[WindowEvent], clipboard: &mut impl Clipboard) {
+        self.submitted = false;
+        if !self.active {
+            return;
+        }
+
+        for event in events {
+            match *event {
+                WindowEvent::Char(c) => self.insert(c.encode_utf8(&mut [0; 4])),
+                WindowEvent::Key(key, _, Action::Press | Action::Repeat, modifiers) => {
+                    self.edit(key, modifiers, clipboard)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn edit(&mut self, key: Key, modifiers: Modifiers, clipboard: &mut impl Clipboard) {
+        let ctrl = modifiers.contains(Modifiers::Control);
+        match key {
+            Key::Backspace => self.backspace(),
+            Key::Delete => self.delete(),
+            Key::Left => self.move_left(),
+            Key::Right => self.move_right(),
+            Key::Home => self.move_home(),
+            Key::End => self.move_end(),
+            Key::Enter | Key::KpEnter => self.submitted = true,
+            Key::C if ctrl => clipboard.set(&self.text),
+            Key::X if ctrl => {
+                clipboard.set(&self.text);
+                self.set_text("");
+            }
+            Key::V if ctrl => {
+                if let Some(text) = clipboard.get() {
+                    self.insert(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestClipboard(Option<String>);
+
+    impl Clipboard for TestClipboard {
+        fn get(&mut self) -> Option<String> {
+            self.0.clone()
+        }
+
+        fn set(&mut self, text: &str) {
+            self.0 = Some(text.to_string());
+        }
+    }
+
+    fn press(key: Key, modifiers: Modifiers) -> WindowEvent {
+        WindowEvent::Key(key, 0, Action::Press, modifiers)
+    }
+
+    fn typed(text: &str) -> Vec<WindowEvent> {
+        text.chars().map(WindowEvent::Char).collect()
+    }
+
+    #[test]
+    fn it_should_type_and_edit_utf8_text() {
+        let mut text_input = TextInput::default();
+        let mut clipboard = TestClipboard::default();
+        text_input.start();
+
+        text_input.update(&typed("Zoé"), &mut clipboard);
+        assert_eq!(text_input.text(), "Zoé");
+        assert_eq!(text_input.cursor(), 3);
+
+        text_input.update(
+            &[
+                press(Key::Left, Modifiers::empty()),
+                press(Key::Backspace, Modifiers::empty()),
+                WindowEvent::Char('ü'),
+                press(Key::End, Modifiers::empty()),
+                press(Key::Backspace, Modifiers::empty()),
+                press(Key::Home, Modifiers::empty()),
+                press(Key::Delete, Modifiers::empty()),
+                WindowEvent::Char('\u{8}'),
+            ],
+            &mut clipboard,
+        );
+        assert_eq!(text_input.text(), "ü");
+        assert_eq!(text_input.cursor(), 0);
+        assert!(!text_input.submitted());
+
+        text_input.update(&[press(Key::Enter, Modifiers::empty())], &mut clipboard);
+        assert!(text_input.submitted());
+        text_input.update(&[], &mut clipboard);
+        assert!(!text_input.submitted());
+    }
+
+    #[test]
+    fn it_should_ignore_the_keyboard_when_inactive() {
+        let mut text_input = TextInput::default();
+
+        text_input.update(&typed("abc"), &mut TestClipboard::default());
+
+        assert_eq!(text_input.text(), "");
+    }
+
+    #[test]
+    fn it_should_limit_the_length() {
+        let mut text_input = TextInput {
+            max_chars: Some(3),
+            ..TextInput::default()
+        };
+
+        text_input.set_text("abcdef");
+
+        assert_eq!(text_input.text(), "abc");
+    }
+
+    #[test]
+    fn it_should_copy_and_paste_through_the_clipboard() {
+        let mut text_input = TextInput::default();
+        let mut clipboard = TestClipboard(Some("line\nbreak".to_string()));
+        text_input.start();
+
+        text_input.update(&[press(Key::V, Modifiers::Control)], &mut clipboard);
+        assert_eq!(text_input.text(), "linebreak");
+
+        text_input.update(&[press(Key::X, Modifiers::Control)], &mut clipboard);
+        assert_eq!(text_input.text(), "");
+        assert_eq!(clipboard.0.as_deref(), Some("linebreak"));
+    }
+}
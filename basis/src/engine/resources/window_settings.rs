@@ -0,0 +1,43 @@
+pub use crate::graphics::window::{DisplayMode, MonitorInfo, Vsync};
+use crate::prelude::*;
+
+///
+/// Window requested by the systems, applied to the window at the end of the frame
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSettings {
+    pub mode: DisplayMode,
+    /// Index in `Screen::monitors` of the monitor used when not windowed
+    pub monitor: usize,
+    pub vsync: Vsync,
+    /// Frames per second the engine waits for, on top of the vsync. Unlimited when `None`.
+    pub target_fps: Option<u32>,
+}
+impl Resource for WindowSettings {}
+
+impl WindowSettings {
+    ///
+    /// Switches between windowed and borderless, the usual Alt+Enter
+    ///
+    pub fn toggle_fullscreen(&mut self) {
+        self.mode = match self.mode {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Fullscreen | DisplayMode::Borderless => DisplayMode::Windowed,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_toggle_fullscreen() {
+        let mut settings = WindowSettings::default();
+
+        settings.toggle_fullscreen();
+        assert_eq!(settings.mode, DisplayMode::Borderless);
+        settings.toggle_fullscreen();
+        assert_eq!(settings.mode, DisplayMode::Windowed);
+    }
+}
@@ -1,15 +1,208 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
-use std::ffi::CString;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
 use std::fs;
-use std::io::Error;
+use std::marker::PhantomData;
 use std::os::raw::*;
+use std::panic::Location;
 use std::ptr;
 
 use gl::types;
 use gl::types::*;
 
+use crate::graphics::std140::{self, UniformBlock};
 use crate::math::prelude::*;
 
+///
+/// Error of a glw call
+///
+#[derive(Debug)]
+pub enum Error {
+    /// A shader file could not be read
+    Io { path: String, err: std::io::Error },
+    Compile {
+        /// File the shader was read from, `None` when compiled from a string
+        path: Option<String>,
+        /// Info log of the compiler
+        log: String,
+        /// The lines of the log pointing into the source
+        messages: Vec<ShaderMessage>,
+    },
+    Link {
+        /// Info log of the linker
+        log: String,
+    },
+    /// Status returned by `gl::CheckFramebufferStatus`
+    IncompleteFramebuffer(GLenum),
+    /// Code returned by `gl::GetError` after a glw call
+    Gl {
+        code: GLenum,
+        call: &'static str,
+        location: &'static Location<'static>,
+    },
+}
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io { path, err } => write!(f, "Failed to read '{}': {}", path, err),
+            Error::Compile { path, log, .. } => match path {
+                Some(path) => write!(f, "Failed to compile '{}':\n{}", path, log.trim_end()),
+                None => write!(f, "Failed to compile shader:\n{}", log.trim_end()),
+            },
+            Error::Link { log } => write!(f, "Failed to link program:\n{}", log.trim_end()),
+            Error::IncompleteFramebuffer(status) => {
+                write!(f, "Framebuffer is not complete: 0x{:x}", status)
+            }
+            Error::Gl {
+                code,
+                call,
+                location,
+            } => write!(
+                f,
+                "{} in {} called at {}",
+                error_name(*code),
+                call,
+                location
+            ),
+        }
+    }
+}
+
+///
+/// Line of a compiler info log pointing into the source
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderMessage {
+    /// Source string number, set by the `#line` directives
+    pub source: u32,
+    pub line: u32,
+    pub text: String,
+}
+
+///
+/// Finds the messages pointing into the source in a compiler info log, the drivers format
+/// them differently:
+///
+/// * Mesa: `0:12(3): error: ...`
+/// * NVIDIA: `0(12) : error C0000: ...`
+/// * AMD and Intel: `ERROR: 0:12: ...`
+///
+pub fn parse_info_log(log: &str) -> Vec<ShaderMessage> {
+    log.lines().filter_map(parse_info_log_line).collect()
+}
+
+fn parse_info_log_line(line: &str) -> Option<ShaderMessage> {
+    let number = |s: &str| s.trim().parse::<u32>().ok();
+
+    // AMD and Intel put the severity first
+    for severity in ["ERROR:", "WARNING:"] {
+        if let Some(rest) = line.strip_prefix(severity) {
+            let (source, rest) = rest.split_once(':')?;
+            let (line, text) = rest.split_once(':')?;
+            return Some(ShaderMessage {
+                source: number(source)?,
+                line: number(line)?,
+                text: format!("{} {}", severity, text.trim()),
+            });
+        }
+    }
+
+    let position_end = line.find(':')?;
+    let (position, text) = (&line[..position_end], line[position_end + 1..].trim());
+    let (source, line) = match position.split_once('(') {
+        // NVIDIA: `0(12) `
+        Some((source, line)) if !source.contains(':') => (source, line.trim().strip_suffix(')')?),
+        _ => {
+            // Mesa: `0:12(3)`, the column is in parentheses after the line
+            let (source, rest) = line.split_once(':')?;
+            let (line, column) = rest.split_once('(')?;
+            let (_, text) = column.split_once(':')?;
+            return Some(ShaderMessage {
+                source: number(source)?,
+                line: number(line)?,
+                text: text.trim().to_string(),
+            });
+        }
+    };
+
+    Some(ShaderMessage {
+        source: number(source)?,
+        line: number(line)?,
+        text: text.to_string(),
+    })
+}
+
+fn error_name(code: GLenum) -> String {
+    match code {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM".to_string(),
+        gl::INVALID_VALUE => "GL_INVALID_VALUE".to_string(),
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION".to_string(),
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION".to_string(),
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY".to_string(),
+        code => format!("GL error 0x{:x}", code),
+    }
+}
+
+///
+/// Number of GL objects alive on the current thread, per kind. Every wrapper owning a GL name
+/// counts itself when created and deletes the name when dropped, a test can compare two
+/// snapshots to catch leaks. Only counted in debug builds.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LiveObjects {
+    pub buffers: usize,
+    pub vertex_arrays: usize,
+    pub textures: usize,
+    pub programs: usize,
+    pub shaders: usize,
+    pub framebuffers: usize,
+    pub renderbuffers: usize,
+}
+
+impl LiveObjects {
+    pub fn total(&self) -> usize {
+        self.buffers
+            + self.vertex_arrays
+            + self.textures
+            + self.programs
+            + self.shaders
+            + self.framebuffers
+            + self.renderbuffers
+    }
+}
+
+pub fn live_objects() -> LiveObjects {
+    LIVE_OBJECTS.with(|live| live.get())
+}
+
+fn track(update: impl FnOnce(&mut LiveObjects)) {
+    if cfg!(debug_assertions) {
+        LIVE_OBJECTS.with(|live| {
+            let mut objects = live.get();
+            update(&mut objects);
+            live.set(objects);
+        });
+    }
+}
+
+thread_local! {
+    static ERROR_CHECKS: Cell<bool> = const { Cell::new(cfg!(debug_assertions)) };
+
+    static LIVE_OBJECTS: Cell<LiveObjects> = Cell::new(LiveObjects::default());
+
+    ///
+    /// Locations returned by `gl::GetUniformLocation`, per program and name. GL objects belong
+    /// to the thread of their context, so does the cache.
+    ///
+    static UNIFORM_LOCATIONS: RefCell<HashMap<(GLuint, String), GLint>> =
+        RefCell::new(HashMap::new());
+
+    static DEBUG_HANDLER: RefCell<Option<DebugHandler>> = const { RefCell::new(None) };
+}
+
 ///
 /// The BufferObject is how we can load stuff into the graphics card memory. The BufferObject can
 /// have many types. One example of BufferObject is the Vertex Buffer Object (VBO) which is
@@ -32,7 +225,7 @@ use crate::math::prelude::*;
 /// vbo.store_f32(&vertices);
 /// vbo.unbind(); // this should be called after finishing all actions
 /// ```
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct BufferObject {
     id: gl::types::GLuint,
     r#type: gl::types::GLenum,
@@ -52,11 +245,14 @@ impl BufferObject {
     ///     - `gl::STREAM_DRAW`: the data is set only once and used by the GPU at most a few times.
     ///     - `gl::STATIC_DRAW`: the data is set only once and used many times.
     ///     - `gl::DYNAMIC_DRAW`: the data is changed a lot and used many times.
+    #[track_caller]
     pub fn new(r#type: gl::types::GLenum, usage: gl::types::GLenum) -> Self {
+        let _check = ErrorCheck::new("BufferObject::new");
         let mut id = 0;
         unsafe {
             gl::GenBuffers(1, &mut id);
         }
+        track(|live| live.buffers += 1);
         Self { id, r#type, usage }
     }
 
@@ -66,7 +262,9 @@ impl BufferObject {
     ///
     /// This function can be bind multiple buffers at the same time as long as they have different types.
     ///
+    #[track_caller]
     pub fn bind(&self) {
+        let _check = ErrorCheck::new("BufferObject::bind");
         unsafe {
             gl::BindBuffer(self.r#type, self.id);
         }
@@ -75,7 +273,9 @@ impl BufferObject {
     ///
     /// Tells OpenGL that there is no context to operate on.
     ///
+    #[track_caller]
     pub fn unbind(&self) {
+        let _check = ErrorCheck::new("BufferObject::unbind");
         unsafe {
             gl::BindBuffer(self.r#type, 0);
         }
@@ -84,7 +284,9 @@ impl BufferObject {
     ///
     /// Copies the passed data into the buffer's memory.
     ///
+    #[track_caller]
     pub fn store_f32(&self, data: &[f32]) {
+        let _check = ErrorCheck::new("BufferObject::store_f32");
         unsafe {
             gl::BufferData(
                 self.r#type,
@@ -98,7 +300,9 @@ impl BufferObject {
     ///
     /// Store data to the GPU
     ///
+    #[track_caller]
     pub fn store_i32(&self, data: &[i32]) {
+        let _check = ErrorCheck::new("BufferObject::store_i32");
         unsafe {
             gl::BufferData(
                 self.r#type,
@@ -112,7 +316,9 @@ impl BufferObject {
     ///
     /// Store data to the GPU
     ///
+    #[track_caller]
     pub fn store_u32(&self, data: &[u32]) {
+        let _check = ErrorCheck::new("BufferObject::store_u32");
         unsafe {
             gl::BufferData(
                 self.r#type,
@@ -122,9 +328,99 @@ impl BufferObject {
             )
         }
     }
+
+    ///
+    /// Store matrices to the GPU, each one laid out as 16 floats in column-major order.
+    ///
+    #[track_caller]
+    pub fn store_mat4(&self, data: &[Mat4]) {
+        let _check = ErrorCheck::new("BufferObject::store_mat4");
+        unsafe {
+            gl::BufferData(
+                self.r#type,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
+                data.as_ptr() as *const c_void,
+                self.usage,
+            )
+        }
+    }
+
+    ///
+    /// Store raw bytes to the GPU, used for data laid out by hand like uniform blocks
+    ///
+    #[track_caller]
+    pub fn store_u8(&self, data: &[u8]) {
+        let _check = ErrorCheck::new("BufferObject::store_u8");
+        unsafe {
+            gl::BufferData(
+                self.r#type,
+                data.len() as gl::types::GLsizeiptr,
+                data.as_ptr() as *const c_void,
+                self.usage,
+            )
+        }
+    }
+
+    ///
+    /// Binds the buffer to the indexed binding point `index` of its type, used by
+    /// `gl::UNIFORM_BUFFER` to feed the uniform blocks bound to the same index
+    ///
+    #[track_caller]
+    pub fn bind_base(&self, index: GLuint) {
+        let _check = ErrorCheck::new("BufferObject::bind_base");
+        unsafe {
+            gl::BindBufferBase(self.r#type, index, self.id);
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Drop for BufferObject {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+        track(|live| live.buffers -= 1);
+    }
+}
+
+///
+/// Uniform Buffer Object holding a `T`, the programs read it through the uniform block bound to
+/// the same binding point with `Shader::uniform_block_binding`. Uploading it once is enough for
+/// every program using the block.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct UniformBuffer<T: UniformBlock> {
+    buffer: BufferObject,
+    binding: GLuint,
+    block: PhantomData<T>,
+}
+
+impl<T: UniformBlock> UniformBuffer<T> {
+    pub fn new(binding: GLuint) -> Self {
+        let buffer = BufferObject::new(gl::UNIFORM_BUFFER, gl::DYNAMIC_DRAW);
+        Self {
+            buffer,
+            binding,
+            block: PhantomData,
+        }
+    }
+
+    pub fn binding(&self) -> GLuint {
+        self.binding
+    }
+
+    ///
+    /// Replaces the content of the buffer with `block` laid out with the std140 rules
+    ///
+    pub fn upload(&self, block: &T) {
+        self.buffer.bind();
+        self.buffer.store_u8(&std140::to_bytes(block));
+        self.buffer.unbind();
+        self.buffer.bind_base(self.binding);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Vao {
     id: gl::types::GLuint,
 }
@@ -135,19 +431,33 @@ impl Default for Vao {
         unsafe {
             gl::GenVertexArrays(1, &mut id);
         }
+        track(|live| live.vertex_arrays += 1);
 
         Self { id }
     }
 }
 
+impl Drop for Vao {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.id);
+        }
+        track(|live| live.vertex_arrays -= 1);
+    }
+}
+
 impl Vao {
+    #[track_caller]
     pub fn bind(&self) {
+        let _check = ErrorCheck::new("Vao::bind");
         unsafe {
             gl::BindVertexArray(self.id);
         }
     }
 
+    #[track_caller]
     pub fn unbind(&self) {
+        let _check = ErrorCheck::new("Vao::unbind");
         unsafe {
             gl::BindVertexArray(0);
         }
@@ -194,6 +504,7 @@ pub struct VertexAttribute {
 }
 
 impl VertexAttribute {
+    #[track_caller]
     pub fn new(
         index: u32,
         size: i32,
@@ -202,6 +513,7 @@ impl VertexAttribute {
         stride: GLsizei,
         pointer: *const c_void,
     ) -> Self {
+        let _check = ErrorCheck::new("VertexAttribute::new");
         unsafe {
             gl::VertexAttribPointer(index, size, r#type, normalized, stride, pointer);
         }
@@ -209,24 +521,58 @@ impl VertexAttribute {
         VertexAttribute { index }
     }
 
+    #[track_caller]
     pub fn enable(&self) {
+        let _check = ErrorCheck::new("VertexAttribute::enable");
         unsafe {
             gl::EnableVertexAttribArray(self.index);
         }
     }
 
+    #[track_caller]
     pub fn disable(&self) {
+        let _check = ErrorCheck::new("VertexAttribute::disable");
         unsafe {
             gl::DisableVertexAttribArray(self.index);
         }
     }
+
+    ///
+    /// Sets how often the attribute advances during an instanced draw call.
+    ///
+    /// * `divisor`
+    ///     - `0`: advances once per vertex (default)
+    ///     - `1`: advances once per instance
+    ///     - `n`: advances once every `n` instances
+    ///
+    #[track_caller]
+    pub fn divisor(&self, divisor: GLuint) {
+        let _check = ErrorCheck::new("VertexAttribute::divisor");
+        unsafe {
+            gl::VertexAttribDivisor(self.index, divisor);
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct Shader {
     id: gl::types::GLuint,
 }
 
+///
+/// Uniform or vertex attribute used by a linked program, see `Shader::active_uniforms`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveVariable {
+    pub name: String,
+    /// `gl::FLOAT_VEC3`, `gl::FLOAT_MAT4`, `gl::SAMPLER_2D`, ...
+    pub r#type: GLenum,
+    /// Length of the array, 1 for anything else
+    pub size: GLint,
+    /// -1 for the uniforms stored in a uniform block
+    pub location: GLint,
+}
+
 pub enum ShaderType {
     Vertex(&'static str),
     Fragment(&'static str),
@@ -235,53 +581,71 @@ pub enum ShaderType {
 impl Default for Shader {
     fn default() -> Self {
         let program_id = unsafe { gl::CreateProgram() };
+        track(|live| live.programs += 1);
 
         Self { id: program_id }
     }
 }
 
+impl Drop for Shader {
+    fn drop(&mut self) {
+        forget_uniform_locations(self.id);
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+        track(|live| live.programs -= 1);
+    }
+}
+
 impl Shader {
+    #[track_caller]
     pub fn bind(&self) -> &Self {
+        let _check = ErrorCheck::new("Shader::bind");
         unsafe {
             gl::UseProgram(self.id);
         }
         self
     }
 
+    #[track_caller]
     pub fn unbind(&self) -> &Self {
+        let _check = ErrorCheck::new("Shader::unbind");
         unsafe {
             gl::UseProgram(0);
         }
         self
     }
 
+    #[track_caller]
     pub fn attach_shader(&self, shader: &ShaderFile) -> &Self {
+        let _check = ErrorCheck::new("Shader::attach_shader");
         unsafe {
             gl::AttachShader(self.id, shader.id);
         }
         self
     }
 
+    #[track_caller]
     pub fn link(&self) -> Result<(), Error> {
+        let _check = ErrorCheck::new("Shader::link");
         unsafe {
             let mut success: gl::types::GLint = 0;
 
+            forget_uniform_locations(self.id);
             gl::LinkProgram(self.id);
             gl::GetProgramiv(self.id, gl::LINK_STATUS, &mut success);
             if success <= 0 {
-                let mut info_log = CString::default();
-                let ptr = info_log.into_raw();
-
-                gl::GetProgramInfoLog(self.id, 512, ptr::null_mut(), ptr);
-                info_log = CString::from_raw(ptr);
-
-                return Err(Error::other(info_log.to_str().unwrap()));
+                return Err(Error::Link {
+                    log: program_info_log(self.id),
+                });
             }
         }
         Ok(())
     }
 
+    #[track_caller]
     pub fn link_multiple(&self, shaderpaths: Vec<ShaderType>) -> Result<&Self, Error> {
+        let _check = ErrorCheck::new("Shader::link_multiple");
         let shaders = shaderpaths
             .iter()
             .map(|_type| {
@@ -300,59 +664,201 @@ impl Shader {
         unsafe {
             let mut success: gl::types::GLint = 0;
 
+            forget_uniform_locations(self.id);
             gl::LinkProgram(self.id);
             gl::GetProgramiv(self.id, gl::LINK_STATUS, &mut success);
             if success <= 0 {
-                let mut info_log = CString::default();
-                let ptr = info_log.into_raw();
-
-                gl::GetProgramInfoLog(self.id, 512, ptr::null_mut(), ptr);
-                info_log = CString::from_raw(ptr);
-
-                shaders.iter().for_each(|shaderfile| shaderfile.delete());
-                return Err(Error::other(info_log.to_str().unwrap()));
+                return Err(Error::Link {
+                    log: program_info_log(self.id),
+                });
             }
         }
-        shaders.iter().for_each(|shaderfile| shaderfile.delete());
+        // the shaders are only flagged for deletion while attached, they go with the program
+        drop(shaders);
         Ok(self)
     }
 
-    pub fn get_uniform_location(&self, name: &'static str) -> UniformLocation {
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    ///
+    /// Location of the uniform `name`, only the first lookup of a name queries OpenGL
+    ///
+    #[track_caller]
+    pub fn get_uniform_location(&self, name: &str) -> UniformLocation {
+        let _check = ErrorCheck::new("Shader::get_uniform_location");
+        let key = (self.id, name.to_string());
+        let cached = UNIFORM_LOCATIONS.with(|locations| locations.borrow().get(&key).copied());
+        let id = cached.unwrap_or_else(|| {
+            let s = CString::new(name).expect("CString::new");
+            let id = unsafe { gl::GetUniformLocation(self.id, s.as_ptr()) };
+            UNIFORM_LOCATIONS.with(|locations| locations.borrow_mut().insert(key, id));
+            id
+        });
+
+        UniformLocation { id }
+    }
+
+    ///
+    /// Binds the uniform block `name` to the binding point of a `UniformBuffer`. Returns false
+    /// when the program has no such block, the compiler removes the unused ones.
+    ///
+    #[track_caller]
+    pub fn uniform_block_binding(&self, name: &str, binding: GLuint) -> bool {
+        let _check = ErrorCheck::new("Shader::uniform_block_binding");
         let s = CString::new(name).expect("CString::new");
-        let id = unsafe { gl::GetUniformLocation(self.id, s.as_ptr()) };
+        let index = unsafe { gl::GetUniformBlockIndex(self.id, s.as_ptr()) };
+        if index == gl::INVALID_INDEX {
+            return false;
+        }
+        unsafe { gl::UniformBlockBinding(self.id, index, binding) };
+        true
+    }
+
+    ///
+    /// Uniforms used by the linked program, sorted by name
+    ///
+    pub fn active_uniforms(&self) -> Vec<ActiveVariable> {
+        self.active_variables(false)
+    }
 
-        UniformLocation { id, name }
+    ///
+    /// Vertex attributes used by the linked program, sorted by name
+    ///
+    pub fn active_attributes(&self) -> Vec<ActiveVariable> {
+        self.active_variables(true)
+    }
+
+    fn active_variables(&self, attributes: bool) -> Vec<ActiveVariable> {
+        let (count, max_length) = if attributes {
+            (gl::ACTIVE_ATTRIBUTES, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH)
+        } else {
+            (gl::ACTIVE_UNIFORMS, gl::ACTIVE_UNIFORM_MAX_LENGTH)
+        };
+        let mut variable_count: GLint = 0;
+        let mut name_length: GLint = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, count, &mut variable_count);
+            gl::GetProgramiv(self.id, max_length, &mut name_length);
+        }
+
+        let mut variables = (0..variable_count.max(0) as GLuint)
+            .map(|index| {
+                let mut buffer = vec![0u8; name_length.max(1) as usize];
+                let mut length: GLsizei = 0;
+                let mut size: GLint = 0;
+                let mut r#type: GLenum = 0;
+                let name_ptr = buffer.as_mut_ptr() as *mut GLchar;
+                let buffer_size = buffer.len() as GLsizei;
+                unsafe {
+                    if attributes {
+                        gl::GetActiveAttrib(
+                            self.id,
+                            index,
+                            buffer_size,
+                            &mut length,
+                            &mut size,
+                            &mut r#type,
+                            name_ptr,
+                        );
+                    } else {
+                        gl::GetActiveUniform(
+                            self.id,
+                            index,
+                            buffer_size,
+                            &mut length,
+                            &mut size,
+                            &mut r#type,
+                            name_ptr,
+                        );
+                    }
+                }
+                buffer.truncate(length.max(0) as usize);
+                let name = String::from_utf8_lossy(&buffer).into_owned();
+
+                let c_name = CString::new(name.as_str()).expect("CString::new");
+                let location = unsafe {
+                    if attributes {
+                        gl::GetAttribLocation(self.id, c_name.as_ptr())
+                    } else {
+                        gl::GetUniformLocation(self.id, c_name.as_ptr())
+                    }
+                };
+
+                ActiveVariable {
+                    name,
+                    r#type,
+                    size,
+                    location,
+                }
+            })
+            .collect::<Vec<ActiveVariable>>();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+        variables
     }
 }
 
+///
+/// Locations change when a program is linked again, and ids are reused once deleted
+///
+fn forget_uniform_locations(program: GLuint) {
+    UNIFORM_LOCATIONS.with(|locations| {
+        locations
+            .borrow_mut()
+            .retain(|(cached_program, _), _| *cached_program != program)
+    });
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct UniformLocation {
     id: gl::types::GLint,
-    #[allow(dead_code)]
-    name: &'static str,
 }
 
 impl UniformLocation {
+    ///
+    /// -1 when the program does not use the uniform, setting it is then ignored
+    ///
+    pub fn id(&self) -> GLint {
+        self.id
+    }
+
+    #[track_caller]
     pub fn uniform1b(&self, v0: bool) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform1b");
         unsafe { gl::Uniform1i(self.id, v0 as types::GLint) }
         self
     }
 
+    #[track_caller]
     pub fn uniform1i(&self, v0: types::GLint) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform1i");
         unsafe { gl::Uniform1i(self.id, v0) }
         self
     }
 
+    #[track_caller]
     pub fn uniform1f(&self, v0: types::GLfloat) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform1f");
         unsafe { gl::Uniform1f(self.id, v0) }
         self
     }
 
+    #[track_caller]
+    pub fn uniform2f(&self, v0: types::GLfloat, v1: types::GLfloat) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform2f");
+        unsafe { gl::Uniform2f(self.id, v0, v1) };
+        self
+    }
+
+    #[track_caller]
     pub fn uniform3f(&self, v0: types::GLfloat, v1: types::GLfloat, v2: types::GLfloat) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform3f");
         unsafe { gl::Uniform3f(self.id, v0, v1, v2) }
         self
     }
 
+    #[track_caller]
     pub fn uniform4f(
         &self,
         v0: types::GLfloat,
@@ -360,19 +866,99 @@ impl UniformLocation {
         v2: types::GLfloat,
         v3: types::GLfloat,
     ) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform4f");
         unsafe { gl::Uniform4f(self.id, v0, v1, v2, v3) }
         self
     }
 
+    pub fn uniform_vec3(&self, v: &Vec3) -> &Self {
+        self.uniform3f(v.x, v.y, v.z)
+    }
+
+    pub fn uniform_vec4(&self, v: &Vec4) -> &Self {
+        self.uniform4f(v.x, v.y, v.z, v.w)
+    }
+
+    #[track_caller]
     pub fn uniform_matrix4fv(&self, mat: &Mat4) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform_matrix4fv");
         unsafe {
             gl::UniformMatrix4fv(self.id, 1, gl::FALSE, mat.as_f32_ptr());
         }
         self
     }
+
+    ///
+    /// * `columns` - The 9 values of the matrix in column-major order
+    ///
+    #[track_caller]
+    pub fn uniform_matrix3fv(&self, columns: &[f32; 9]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform_matrix3fv");
+        unsafe {
+            gl::UniformMatrix3fv(self.id, 1, gl::FALSE, columns.as_ptr());
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn uniform1iv(&self, values: &[types::GLint]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform1iv");
+        unsafe { gl::Uniform1iv(self.id, values.len() as GLsizei, values.as_ptr()) }
+        self
+    }
+
+    #[track_caller]
+    pub fn uniform1fv(&self, values: &[types::GLfloat]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform1fv");
+        unsafe { gl::Uniform1fv(self.id, values.len() as GLsizei, values.as_ptr()) }
+        self
+    }
+
+    ///
+    /// Sets a `vec3` array, `Vec3` is laid out as 3 consecutive floats
+    ///
+    #[track_caller]
+    pub fn uniform3fv(&self, values: &[Vec3]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform3fv");
+        unsafe {
+            gl::Uniform3fv(
+                self.id,
+                values.len() as GLsizei,
+                values.as_ptr() as *const GLfloat,
+            )
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn uniform4fv(&self, values: &[Vec4]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform4fv");
+        unsafe {
+            gl::Uniform4fv(
+                self.id,
+                values.len() as GLsizei,
+                values.as_ptr() as *const GLfloat,
+            )
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn uniform_matrix4fv_array(&self, mats: &[Mat4]) -> &Self {
+        let _check = ErrorCheck::new("UniformLocation::uniform_matrix4fv_array");
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.id,
+                mats.len() as GLsizei,
+                gl::FALSE,
+                mats.as_ptr() as *const GLfloat,
+            );
+        }
+        self
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 pub struct ShaderFile {
     id: gl::types::GLuint,
     #[allow(dead_code)]
@@ -381,11 +967,23 @@ pub struct ShaderFile {
 
 impl ShaderFile {
     pub fn new(shaderfilepath: &str, r#type: gl::types::GLenum) -> Result<Self, Error> {
-        let contents = fs::read_to_string(shaderfilepath)?;
-        ShaderFile::new_from_source(contents.as_str(), r#type)
+        let contents = fs::read_to_string(shaderfilepath).map_err(|err| Error::Io {
+            path: shaderfilepath.to_string(),
+            err,
+        })?;
+        ShaderFile::new_from_source(contents.as_str(), r#type).map_err(|err| match err {
+            Error::Compile { log, messages, .. } => Error::Compile {
+                path: Some(shaderfilepath.to_string()),
+                log,
+                messages,
+            },
+            err => err,
+        })
     }
 
+    #[track_caller]
     pub fn new_from_source(code: &str, r#type: gl::types::GLenum) -> Result<Self, Error> {
+        let _check = ErrorCheck::new("ShaderFile::new_from_source");
         #[allow(unused_assignments)]
         let mut shader_id: u32 = 0;
         unsafe {
@@ -397,29 +995,76 @@ impl ShaderFile {
             gl::CompileShader(shader_id);
             gl::GetShaderiv(shader_id, gl::COMPILE_STATUS, &mut success);
             if success <= 0 {
-                let mut info_log = CString::default();
-                let ptr = info_log.into_raw();
-
-                gl::GetShaderInfoLog(shader_id, 512, ptr::null_mut(), ptr);
-                info_log = CString::from_raw(ptr);
-
-                return Err(Error::other(info_log.to_str().unwrap()));
+                let info_log = shader_info_log(shader_id);
+                gl::DeleteShader(shader_id);
+
+                return Err(Error::Compile {
+                    path: None,
+                    messages: parse_info_log(&info_log),
+                    log: info_log,
+                });
             }
         }
+        track(|live| live.shaders += 1);
         Ok(Self {
             id: shader_id,
             r#type,
         })
     }
+}
 
-    pub fn delete(&self) {
+impl Drop for ShaderFile {
+    fn drop(&mut self) {
         unsafe {
             gl::DeleteShader(self.id);
         }
+        track(|live| live.shaders -= 1);
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+///
+/// Reads the info log of a shader, the buffer is sized from `gl::INFO_LOG_LENGTH`
+///
+fn shader_info_log(id: gl::types::GLuint) -> String {
+    let mut length: gl::types::GLint = 0;
+    unsafe { gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut length) };
+
+    let mut buffer = vec![0u8; length.max(1) as usize];
+    let mut written: gl::types::GLsizei = 0;
+    unsafe {
+        gl::GetShaderInfoLog(
+            id,
+            buffer.len() as gl::types::GLsizei,
+            &mut written,
+            buffer.as_mut_ptr() as *mut gl::types::GLchar,
+        )
+    };
+    buffer.truncate(written.max(0) as usize);
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+///
+/// Same as `shader_info_log` for a program
+///
+fn program_info_log(id: gl::types::GLuint) -> String {
+    let mut length: gl::types::GLint = 0;
+    unsafe { gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut length) };
+
+    let mut buffer = vec![0u8; length.max(1) as usize];
+    let mut written: gl::types::GLsizei = 0;
+    unsafe {
+        gl::GetProgramInfoLog(
+            id,
+            buffer.len() as gl::types::GLsizei,
+            &mut written,
+            buffer.as_mut_ptr() as *mut gl::types::GLchar,
+        )
+    };
+    buffer.truncate(written.max(0) as usize);
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Texture {
     id: gl::types::GLuint,
     r#type: gl::types::GLenum,
@@ -429,39 +1074,51 @@ impl Texture {
     ///
     /// `r#type`: The type of the texture. It can be: `gl::TEXTURE_2D`, `gl::TEXTURE_3D`, etc.
     ///
+    #[track_caller]
     pub fn new(r#type: gl::types::GLenum) -> Self {
+        let _check = ErrorCheck::new("Texture::new");
         let mut id = 0;
         unsafe {
             gl::GenTextures(1, &mut id);
         }
+        track(|live| live.textures += 1);
         Self { id, r#type }
     }
 
+    #[track_caller]
     pub fn active(&self, index: gl::types::GLenum) {
+        let _check = ErrorCheck::new("Texture::active");
         unsafe {
             gl::ActiveTexture(index);
         }
     }
 
+    #[track_caller]
     pub fn bind(&self) {
+        let _check = ErrorCheck::new("Texture::bind");
         unsafe {
             gl::BindTexture(self.r#type, self.id);
         }
     }
 
+    #[track_caller]
     pub fn tex_parameteri(&self, pname: types::GLenum, param: types::GLint) {
+        let _check = ErrorCheck::new("Texture::tex_parameteri");
         unsafe {
             gl::TexParameteri(self.r#type, pname, param);
         }
     }
 
+    #[track_caller]
     pub fn tex_parameterfv(&self, pname: types::GLenum, param: Vec<f32>) {
+        let _check = ErrorCheck::new("Texture::tex_parameterfv");
         unsafe {
             gl::TexParameterfv(self.r#type, pname, param.as_ptr());
         }
     }
 
     #[allow(clippy::too_many_arguments)]
+    #[track_caller]
     pub fn tex_image2d(
         &self,
         level: types::GLint,
@@ -473,6 +1130,7 @@ impl Texture {
         type_: types::GLenum,
         pixels: *const c_void,
     ) {
+        let _check = ErrorCheck::new("Texture::tex_image2d");
         unsafe {
             gl::TexImage2D(
                 self.r#type,
@@ -488,7 +1146,43 @@ impl Texture {
         }
     }
 
+    ///
+    /// Same as `tex_image2d` for one of the six faces of a `gl::TEXTURE_CUBE_MAP`
+    ///
+    /// * `face` - From 0 to 5, in the order +X, -X, +Y, -Y, +Z, -Z
+    ///
+    #[allow(clippy::too_many_arguments)]
+    #[track_caller]
+    pub fn tex_image_cube_face(
+        &self,
+        face: types::GLenum,
+        level: types::GLint,
+        internal_format: types::GLint,
+        width: types::GLsizei,
+        height: types::GLsizei,
+        format: types::GLenum,
+        type_: types::GLenum,
+        pixels: *const c_void,
+    ) {
+        let _check = ErrorCheck::new("Texture::tex_image_cube_face");
+        debug_assert!(face < 6);
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                level,
+                internal_format,
+                width,
+                height,
+                0,
+                format,
+                type_,
+                pixels,
+            )
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
+    #[track_caller]
     pub fn tex_image3d(
         &self,
         level: types::GLint,
@@ -501,6 +1195,7 @@ impl Texture {
         type_: types::GLenum,
         pixels: *const c_void,
     ) {
+        let _check = ErrorCheck::new("Texture::tex_image3d");
         unsafe {
             gl::TexImage3D(
                 self.r#type,
@@ -517,54 +1212,397 @@ impl Texture {
         }
     }
 
+    #[track_caller]
     pub fn generate_mipmap(&self) {
+        let _check = ErrorCheck::new("Texture::generate_mipmap");
         unsafe {
             gl::GenerateMipmap(self.r#type);
         }
     }
 
+    #[track_caller]
     pub fn unbind(&self) {
+        let _check = ErrorCheck::new("Texture::unbind");
         unsafe {
             gl::BindTexture(self.r#type, 0);
         }
     }
 }
 
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+        track(|live| live.textures -= 1);
+    }
+}
+
+///
+/// Destination of the draw calls other than the default framebuffer (the window). The images
+/// are attached to it, textures when they have to be sampled afterwards, renderbuffers
+/// otherwise.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct Framebuffer {
+    id: gl::types::GLuint,
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut id);
+        }
+        track(|live| live.framebuffers += 1);
+
+        Self { id }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+        track(|live| live.framebuffers -= 1);
+    }
+}
+
+impl Framebuffer {
+    #[track_caller]
+    pub fn bind(&self) {
+        let _check = ErrorCheck::new("Framebuffer::bind");
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    ///
+    /// Binds the default framebuffer back
+    ///
+    #[track_caller]
+    pub fn unbind(&self) {
+        let _check = ErrorCheck::new("Framebuffer::unbind");
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    ///
+    /// * `attachment` - `gl::COLOR_ATTACHMENT0`, `gl::DEPTH_ATTACHMENT`, etc.
+    ///
+    #[track_caller]
+    pub fn attach_texture2d(&self, attachment: types::GLenum, texture: &Texture) {
+        let _check = ErrorCheck::new("Framebuffer::attach_texture2d");
+        unsafe {
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, texture.r#type, texture.id, 0);
+        }
+    }
+
+    #[track_caller]
+    pub fn attach_renderbuffer(&self, attachment: types::GLenum, renderbuffer: &Renderbuffer) {
+        let _check = ErrorCheck::new("Framebuffer::attach_renderbuffer");
+        unsafe {
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                attachment,
+                gl::RENDERBUFFER,
+                renderbuffer.id,
+            );
+        }
+    }
+
+    ///
+    /// Must be called while bound, once every image is attached
+    ///
+    #[track_caller]
+    pub fn check_status(&self) -> Result<(), Error> {
+        let _check = ErrorCheck::new("Framebuffer::check_status");
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(Error::IncompleteFramebuffer(status));
+        }
+        Ok(())
+    }
+}
+
+///
+/// Image that can only be attached to a `Framebuffer`, it can not be sampled by a shader
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct Renderbuffer {
+    id: gl::types::GLuint,
+}
+
+impl Default for Renderbuffer {
+    fn default() -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+        }
+        track(|live| live.renderbuffers += 1);
+
+        Self { id }
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.id);
+        }
+        track(|live| live.renderbuffers -= 1);
+    }
+}
+
+impl Renderbuffer {
+    #[track_caller]
+    pub fn bind(&self) {
+        let _check = ErrorCheck::new("Renderbuffer::bind");
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.id);
+        }
+    }
+
+    #[track_caller]
+    pub fn unbind(&self) {
+        let _check = ErrorCheck::new("Renderbuffer::unbind");
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+    }
+
+    ///
+    /// * `internalformat` - `gl::DEPTH24_STENCIL8`, `gl::RGBA8`, etc.
+    ///
+    #[track_caller]
+    pub fn storage(
+        &self,
+        internalformat: types::GLenum,
+        width: types::GLsizei,
+        height: types::GLsizei,
+    ) {
+        let _check = ErrorCheck::new("Renderbuffer::storage");
+        unsafe {
+            gl::RenderbufferStorage(gl::RENDERBUFFER, internalformat, width, height);
+        }
+    }
+}
+
+///
+/// Framebuffer with a color texture, that can be sampled once rendered, and a depth
+/// renderbuffer. Used to render the scene offscreen before post-processing it.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderTarget {
+    pub framebuffer: Framebuffer,
+    pub color: Texture,
+    pub depth: Renderbuffer,
+    pub width: types::GLsizei,
+    pub height: types::GLsizei,
+    internal_format: types::GLint,
+}
+
+impl RenderTarget {
+    ///
+    /// * `internal_format` - Format of the color texture, `gl::RGBA8` or `gl::RGBA16F` to keep
+    ///   colors brighter than 1.0
+    ///
+    pub fn new(
+        width: types::GLsizei,
+        height: types::GLsizei,
+        internal_format: types::GLenum,
+    ) -> Result<Self, Error> {
+        let render_target = Self {
+            framebuffer: Framebuffer::default(),
+            color: Texture::new(gl::TEXTURE_2D),
+            depth: Renderbuffer::default(),
+            width,
+            height,
+            internal_format: internal_format as types::GLint,
+        };
+
+        render_target.allocate();
+        render_target.color.bind();
+        render_target
+            .color
+            .tex_parameteri(gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+        render_target
+            .color
+            .tex_parameteri(gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        render_target
+            .color
+            .tex_parameteri(gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        render_target
+            .color
+            .tex_parameteri(gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        render_target.color.unbind();
+
+        render_target.framebuffer.bind();
+        render_target
+            .framebuffer
+            .attach_texture2d(gl::COLOR_ATTACHMENT0, &render_target.color);
+        render_target
+            .framebuffer
+            .attach_renderbuffer(gl::DEPTH_STENCIL_ATTACHMENT, &render_target.depth);
+        let status = render_target.framebuffer.check_status();
+        render_target.framebuffer.unbind();
+
+        status.map(|_| render_target)
+    }
+
+    ///
+    /// Reallocates the attachments, their content is lost
+    ///
+    pub fn resize(&mut self, width: types::GLsizei, height: types::GLsizei) {
+        if self.width == width && self.height == height {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.allocate();
+    }
+
+    ///
+    /// Binds the framebuffer and makes the viewport cover it
+    ///
+    pub fn bind(&self) {
+        self.framebuffer.bind();
+        viewport(0, 0, self.width, self.height);
+    }
+
+    pub fn unbind(&self) {
+        self.framebuffer.unbind();
+    }
+
+    fn allocate(&self) {
+        self.color.bind();
+        self.color.tex_image2d(
+            0,
+            self.internal_format,
+            self.width,
+            self.height,
+            0,
+            gl::RGBA,
+            gl::FLOAT,
+            ptr::null(),
+        );
+        self.color.unbind();
+
+        self.depth.bind();
+        self.depth
+            .storage(gl::DEPTH24_STENCIL8, self.width, self.height);
+        self.depth.unbind();
+    }
+}
+
+#[track_caller]
+pub fn viewport(x: types::GLint, y: types::GLint, width: types::GLsizei, height: types::GLsizei) {
+    let _check = ErrorCheck::new("viewport");
+    unsafe {
+        gl::Viewport(x, y, width, height);
+    }
+}
+
+///
+/// Which color buffers the bound framebuffer draws into, `gl::NONE` for a depth only framebuffer
+///
+#[track_caller]
+pub fn draw_buffer(buf: types::GLenum) {
+    let _check = ErrorCheck::new("draw_buffer");
+    unsafe {
+        gl::DrawBuffer(buf);
+    }
+}
+
+#[track_caller]
+pub fn read_buffer(src: types::GLenum) {
+    let _check = ErrorCheck::new("read_buffer");
+    unsafe {
+        gl::ReadBuffer(src);
+    }
+}
+
+#[track_caller]
 pub fn clear_color(
     red: types::GLfloat,
     green: types::GLfloat,
     blue: types::GLfloat,
     alpha: types::GLfloat,
 ) {
+    let _check = ErrorCheck::new("clear_color");
     unsafe {
         gl::ClearColor(red, green, blue, alpha);
     }
 }
 
+#[track_caller]
 pub fn clear(mask: types::GLbitfield) {
+    let _check = ErrorCheck::new("clear");
     unsafe {
         gl::Clear(mask);
     }
 }
 
+#[track_caller]
 pub fn draw_arrays(mode: types::GLenum, first: types::GLint, count: types::GLsizei) {
+    let _check = ErrorCheck::new("draw_arrays");
     unsafe {
         gl::DrawArrays(mode, first, count);
     }
 }
 
+#[track_caller]
 pub fn draw_elements(
     mode: types::GLenum,
     count: types::GLsizei,
     type_: types::GLenum,
     indices: *const c_void,
 ) {
+    let _check = ErrorCheck::new("draw_elements");
     unsafe {
         gl::DrawElements(mode, count, type_, indices);
     }
 }
 
+///
+/// Same as `draw_arrays` but draws the range `instancecount` times, exposing `gl_InstanceID` to
+/// the shader and advancing the attributes that have a divisor set.
+///
+#[track_caller]
+pub fn draw_arrays_instanced(
+    mode: types::GLenum,
+    first: types::GLint,
+    count: types::GLsizei,
+    instancecount: types::GLsizei,
+) {
+    let _check = ErrorCheck::new("draw_arrays_instanced");
+    unsafe {
+        gl::DrawArraysInstanced(mode, first, count, instancecount);
+    }
+}
+
+///
+/// Same as `draw_elements` but draws the elements `instancecount` times.
+///
+#[track_caller]
+pub fn draw_elements_instanced(
+    mode: types::GLenum,
+    count: types::GLsizei,
+    type_: types::GLenum,
+    indices: *const c_void,
+    instancecount: types::GLsizei,
+) {
+    let _check = ErrorCheck::new("draw_elements_instanced");
+    unsafe {
+        gl::DrawElementsInstanced(mode, count, type_, indices, instancecount);
+    }
+}
+
+#[track_caller]
 pub fn polygon_mode(face: types::GLenum, mode: types::GLenum) {
+    let _check = ErrorCheck::new("polygon_mode");
     unsafe {
         gl::PolygonMode(face, mode);
     }
@@ -574,23 +1612,362 @@ pub fn polygon_mode(face: types::GLenum, mode: types::GLenum) {
 ///     - `gl::MAX_VERTEX_ATTRIBS`: The amount of input variables (Vertex Attributes) we can sent to a shader
 ///     - ...
 /// * `data` Where the result will be stored
+#[track_caller]
 pub fn get_integerv(pname: types::GLenum, data: *mut types::GLint) {
+    let _check = ErrorCheck::new("get_integerv");
     unsafe {
         gl::GetIntegerv(pname, data);
     }
 }
 
+#[track_caller]
 pub fn enable(cap: types::GLenum) {
+    let _check = ErrorCheck::new("enable");
     unsafe {
         gl::Enable(cap);
     }
 }
 
-pub fn check_error() {
+#[track_caller]
+pub fn disable(cap: types::GLenum) {
+    let _check = ErrorCheck::new("disable");
+    unsafe {
+        gl::Disable(cap);
+    }
+}
+
+///
+/// How the fragment color (`sfactor`) is combined with the color already in the framebuffer
+/// (`dfactor`) when `gl::BLEND` is enabled.
+///
+#[track_caller]
+pub fn blend_func(sfactor: types::GLenum, dfactor: types::GLenum) {
+    let _check = ErrorCheck::new("blend_func");
+    unsafe {
+        gl::BlendFunc(sfactor, dfactor);
+    }
+}
+
+///
+/// Comparison used by the depth test, `gl::LESS` by default
+///
+#[track_caller]
+pub fn depth_func(func: types::GLenum) {
+    let _check = ErrorCheck::new("depth_func");
+    unsafe {
+        gl::DepthFunc(func);
+    }
+}
+
+///
+/// Enables or disables writing into the depth buffer, the depth test itself still happens.
+///
+#[track_caller]
+pub fn depth_mask(flag: bool) {
+    let _check = ErrorCheck::new("depth_mask");
+    unsafe {
+        gl::DepthMask(if flag { gl::TRUE } else { gl::FALSE });
+    }
+}
+
+///
+/// Reads a block of pixels from the bound framebuffer into `data`, starting from the bottom left
+///
+#[track_caller]
+pub fn read_pixels(
+    x: types::GLint,
+    y: types::GLint,
+    width: types::GLsizei,
+    height: types::GLsizei,
+    format: types::GLenum,
+    type_: types::GLenum,
+    data: *mut c_void,
+) {
+    let _check = ErrorCheck::new("read_pixels");
+    unsafe {
+        gl::ReadPixels(x, y, width, height, format, type_, data);
+    }
+}
+
+///
+/// * `pname` - `gl::PACK_ALIGNMENT` for `read_pixels`, `gl::UNPACK_ALIGNMENT` for the uploads
+///
+#[track_caller]
+pub fn pixel_storei(pname: types::GLenum, param: types::GLint) {
+    let _check = ErrorCheck::new("pixel_storei");
+    unsafe {
+        gl::PixelStorei(pname, param);
+    }
+}
+
+///
+/// Message of the `KHR_debug` output, core since OpenGL 4.3
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMessage {
+    /// `gl::DEBUG_SOURCE_API`, `gl::DEBUG_SOURCE_SHADER_COMPILER`, ...
+    pub source: GLenum,
+    /// `gl::DEBUG_TYPE_ERROR`, `gl::DEBUG_TYPE_PERFORMANCE`, ...
+    pub r#type: GLenum,
+    pub id: GLuint,
+    /// `gl::DEBUG_SEVERITY_HIGH` to `gl::DEBUG_SEVERITY_NOTIFICATION`
+    pub severity: GLenum,
+    pub message: String,
+}
+
+impl DebugMessage {
+    pub fn is_notification(&self) -> bool {
+        self.severity == gl::DEBUG_SEVERITY_NOTIFICATION
+    }
+}
+
+impl std::fmt::Display for DebugMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let severity = match self.severity {
+            gl::DEBUG_SEVERITY_HIGH => "high",
+            gl::DEBUG_SEVERITY_MEDIUM => "medium",
+            gl::DEBUG_SEVERITY_LOW => "low",
+            _ => "notification",
+        };
+        let r#type = match self.r#type {
+            gl::DEBUG_TYPE_ERROR => "error",
+            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+            gl::DEBUG_TYPE_PORTABILITY => "portability",
+            gl::DEBUG_TYPE_PERFORMANCE => "performance",
+            _ => "other",
+        };
+        write!(
+            f,
+            "[GL {} {} {}] {}",
+            severity,
+            r#type,
+            self.id,
+            self.message.trim_end()
+        )
+    }
+}
+
+///
+/// Routes the `KHR_debug` messages of the current context to `handler`. The output is made
+/// synchronous so the messages arrive during the faulty call. Returns false when the driver
+/// does not support the extension, the context should also be created with the debug flag for
+/// most drivers to report anything.
+///
+#[track_caller]
+pub fn debug_message_callback(handler: impl Fn(&DebugMessage) + 'static) -> bool {
+    if !gl::DebugMessageCallback::is_loaded() {
+        return false;
+    }
+    let _check = ErrorCheck::new("debug_message_callback");
+    DEBUG_HANDLER.with(|debug_handler| *debug_handler.borrow_mut() = Some(Box::new(handler)));
     unsafe {
-        let err = gl::GetError();
-        if err != gl::NO_ERROR {
-            panic!("Error in OpenGL call: {}", err);
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(debug_message_trampoline), ptr::null());
+    }
+    true
+}
+
+type DebugHandler = Box<dyn Fn(&DebugMessage)>;
+
+extern "system" fn debug_message_trampoline(
+    source: GLenum,
+    r#type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    _user_param: *mut c_void,
+) {
+    let message = if length < 0 {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .to_string()
+    } else {
+        let bytes = unsafe { std::slice::from_raw_parts(message as *const u8, length as usize) };
+        String::from_utf8_lossy(bytes).to_string()
+    };
+    let message = DebugMessage {
+        source,
+        r#type,
+        id,
+        severity,
+        message,
+    };
+    // called synchronously on the thread of the context, a message sent while the handler runs
+    // is dropped instead of borrowing it twice
+    DEBUG_HANDLER.with(|handler| {
+        if let Ok(handler) = handler.try_borrow() {
+            if let Some(handler) = handler.as_ref() {
+                handler(&message);
+            }
+        }
+    });
+}
+
+///
+/// Whether every glw call of this thread is followed by a `gl::GetError` check, enabled by
+/// default in debug builds. A failed check panics with the call and where it was made from.
+///
+pub fn set_error_checks(enabled: bool) {
+    ERROR_CHECKS.with(|checks| checks.set(enabled));
+}
+
+///
+/// Reports the oldest pending GL error. The errors are only queued by OpenGL, it may come from
+/// any call since the last check.
+///
+#[track_caller]
+pub fn check_error(call: &'static str) -> Result<(), Error> {
+    match pending_error() {
+        Some(code) => Err(Error::Gl {
+            code,
+            call,
+            location: Location::caller(),
+        }),
+        None => Ok(()),
+    }
+}
+
+///
+/// The oldest error queued by OpenGL, the queue is drained so the next check only reports new
+/// errors
+///
+fn pending_error() -> Option<GLenum> {
+    let code = unsafe { gl::GetError() };
+    if code == gl::NO_ERROR {
+        return None;
+    }
+    while unsafe { gl::GetError() } != gl::NO_ERROR {}
+    Some(code)
+}
+
+///
+/// Checks the GL errors once the glw call it was created in returns, see `set_error_checks`
+///
+struct ErrorCheck {
+    call: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl ErrorCheck {
+    #[track_caller]
+    fn new(call: &'static str) -> Option<ErrorCheck> {
+        ERROR_CHECKS
+            .with(|checks| checks.get())
+            .then(|| ErrorCheck {
+                call,
+                location: Location::caller(),
+            })
+    }
+}
+
+impl Drop for ErrorCheck {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(code) = pending_error() {
+            panic!(
+                "{}",
+                Error::Gl {
+                    code,
+                    call: self.call,
+                    location: self.location,
+                }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::graphics::headless::HeadlessContext;
+
+    #[test]
+    fn it_should_parse_the_info_logs_of_the_drivers() {
+        let message = |source, line, text: &str| ShaderMessage {
+            source,
+            line,
+            text: text.to_string(),
+        };
+
+        #[rustfmt::skip]
+        let cases = [
+            (
+                "0:12(3): error: syntax error, unexpected ';'\n",
+                message(0, 12, "error: syntax error, unexpected ';'"),
+            ),
+            (
+                "2(7) : error C1008: undefined variable \"foo\"\n",
+                message(2, 7, "error C1008: undefined variable \"foo\""),
+            ),
+            (
+                "ERROR: 1:3: 'foo' : undeclared identifier\n",
+                message(1, 3, "ERROR: 'foo' : undeclared identifier"),
+            ),
+        ];
+
+        for (log, expected) in cases {
+            assert_eq!(parse_info_log(log), vec![expected]);
         }
     }
+
+    #[test]
+    fn it_should_skip_the_lines_without_position() {
+        let log = "Fragment shader failed to compile with the following errors:\nERROR: error(#273) 1 compilation errors.  No code generated\n0:4(1): warning: unused\n";
+
+        assert_eq!(
+            parse_info_log(log),
+            vec![ShaderMessage {
+                source: 0,
+                line: 4,
+                text: "warning: unused".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_report_gl_errors() {
+        let Some(_context) = HeadlessContext::for_test(1, 1) else {
+            return;
+        };
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let received = messages.clone();
+        let debug_output = debug_message_callback(move |message| {
+            received.borrow_mut().push(message.clone());
+        });
+
+        set_error_checks(false);
+        enable(0xFFFF);
+        let err = check_error("enable").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Gl {
+                code: gl::INVALID_ENUM,
+                call: "enable",
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("GL_INVALID_ENUM in enable called at "));
+        assert!(check_error("enable").is_ok());
+        if debug_output {
+            assert!(messages
+                .borrow()
+                .iter()
+                .any(|message| message.r#type == gl::DEBUG_TYPE_ERROR));
+        }
+
+        set_error_checks(true);
+        let panic = std::panic::catch_unwind(|| disable(0xFFFF)).unwrap_err();
+        let panic = panic.downcast_ref::<String>().unwrap();
+        assert!(panic.starts_with("GL_INVALID_ENUM in disable called at "));
+        assert!(panic.contains(file!()), "{}", panic);
+    }
 }
@@ -0,0 +1,119 @@
+use khronos_egl as egl;
+
+/// `EGL_PLATFORM_SURFACELESS_MESA`, a display that needs neither a window system nor a GPU
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+/// Environment variable skipping the tests that need OpenGL, on machines without EGL
+const SKIP_GL_TESTS: &str = "SKIP_GL_TESTS";
+
+///
+/// OpenGL 3.3 core context drawing into an offscreen pbuffer, the pbuffer is the default
+/// framebuffer. Used by the tests to render without a window, with Mesa llvmpipe it does not
+/// need a GPU either.
+///
+pub struct HeadlessContext {
+    egl: egl::DynamicInstance<egl::EGL1_5>,
+    display: egl::Display,
+    surface: egl::Surface,
+    context: egl::Context,
+}
+
+impl HeadlessContext {
+    ///
+    /// Creates the context and makes it current on the calling thread. Fails when libEGL or a
+    /// driver supporting surfaceless displays is not installed.
+    ///
+    pub fn new(width: i32, height: i32) -> Result<HeadlessContext, String> {
+        let egl = unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required() }
+            .map_err(|err| format!("Failed to load libEGL: {}", err))?;
+
+        let display = unsafe {
+            egl.get_platform_display(
+                PLATFORM_SURFACELESS_MESA,
+                egl::DEFAULT_DISPLAY,
+                &[egl::ATTRIB_NONE],
+            )
+        }
+        .map_err(|err| format!("Failed to get a surfaceless display: {}", err))?;
+        egl.initialize(display)
+            .map_err(|err| format!("Failed to initialize EGL: {}", err))?;
+        egl.bind_api(egl::OPENGL_API)
+            .map_err(|err| format!("Failed to bind OpenGL: {}", err))?;
+
+        #[rustfmt::skip]
+        let config_attributes = [
+            egl::SURFACE_TYPE, egl::PBUFFER_BIT,
+            egl::RENDERABLE_TYPE, egl::OPENGL_BIT,
+            egl::RED_SIZE, 8,
+            egl::GREEN_SIZE, 8,
+            egl::BLUE_SIZE, 8,
+            egl::ALPHA_SIZE, 8,
+            egl::DEPTH_SIZE, 24,
+            egl::NONE,
+        ];
+        let config = egl
+            .choose_first_config(display, &config_attributes)
+            .map_err(|err| format!("Failed to choose a config: {}", err))?
+            .ok_or("No config supports OpenGL pbuffers")?;
+
+        let surface = egl
+            .create_pbuffer_surface(
+                display,
+                config,
+                &[egl::WIDTH, width, egl::HEIGHT, height, egl::NONE],
+            )
+            .map_err(|err| format!("Failed to create the pbuffer: {}", err))?;
+
+        #[rustfmt::skip]
+        let context_attributes = [
+            egl::CONTEXT_MAJOR_VERSION, 3,
+            egl::CONTEXT_MINOR_VERSION, 3,
+            egl::CONTEXT_OPENGL_PROFILE_MASK, egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+            egl::CONTEXT_OPENGL_DEBUG, cfg!(debug_assertions) as egl::Int,
+            egl::NONE,
+        ];
+        let context = egl
+            .create_context(display, config, None, &context_attributes)
+            .map_err(|err| format!("Failed to create the context: {}", err))?;
+
+        egl.make_current(display, Some(surface), Some(surface), Some(context))
+            .map_err(|err| format!("Failed to make the context current: {}", err))?;
+        gl::load_with(|name| {
+            egl.get_proc_address(name)
+                .map_or(std::ptr::null(), |address| address as *const _)
+        });
+
+        Ok(HeadlessContext {
+            egl,
+            display,
+            surface,
+            context,
+        })
+    }
+}
+
+impl HeadlessContext {
+    ///
+    /// Context of a test that needs OpenGL, `None` when `SKIP_GL_TESTS` is set. Panics when the
+    /// context can't be created, so a machine without EGL does not report the test as passed.
+    ///
+    pub fn for_test(width: i32, height: i32) -> Option<HeadlessContext> {
+        if std::env::var_os(SKIP_GL_TESTS).is_some() {
+            eprintln!("Skipping the OpenGL test, {} is set", SKIP_GL_TESTS);
+            return None;
+        }
+
+        let context = HeadlessContext::new(width, height).unwrap_or_else(|err| {
+            panic!("{}, set {}=1 to skip the OpenGL tests", err, SKIP_GL_TESTS)
+        });
+        Some(context)
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        let _ = self.egl.make_current(self.display, None, None, None);
+        let _ = self.egl.destroy_context(self.display, self.context);
+        let _ = self.egl.destroy_surface(self.display, self.surface);
+    }
+}
@@ -0,0 +1,283 @@
+use super::{DecodeError, Image};
+
+const FILE_HEADER_LENGTH: usize = 14;
+const CORE_HEADER_LENGTH: usize = 12;
+const INFO_HEADER_LENGTH: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, DecodeError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(DecodeError::Truncated)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, DecodeError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(DecodeError::Truncated)
+}
+
+///
+/// Extracts a channel described by a bit mask and scales it to 8 bits
+///
+#[derive(Debug, Clone, Copy)]
+struct Mask {
+    mask: u32,
+    shift: u32,
+    max: u32,
+}
+
+impl Mask {
+    fn new(mask: u32) -> Mask {
+        if mask == 0 {
+            return Mask {
+                mask,
+                shift: 0,
+                max: 0,
+            };
+        }
+        let shift = mask.trailing_zeros();
+        Mask {
+            mask,
+            shift,
+            max: mask >> shift,
+        }
+    }
+
+    fn extract(&self, value: u32, default: u8) -> u8 {
+        if self.mask == 0 {
+            return default;
+        }
+        (((value & self.mask) >> self.shift) as u64 * 255 / self.max as u64) as u8
+    }
+}
+
+///
+/// Decodes uncompressed BMP files with 1, 4, 8, 16, 24 or 32 bits per pixel, including the ones
+/// using bit field masks.
+///
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    if data.len() < FILE_HEADER_LENGTH + CORE_HEADER_LENGTH || &data[0..2] != b"BM" {
+        return Err(DecodeError::InvalidHeader(
+            "Invalid BMP signature".to_string(),
+        ));
+    }
+
+    let pixels_offset = read_u32(data, 10)? as usize;
+    let dib = FILE_HEADER_LENGTH;
+    let dib_length = read_u32(data, dib)? as usize;
+
+    let (width, height, bits_per_pixel, compression, colors_used, palette_entry_length) =
+        if dib_length == CORE_HEADER_LENGTH {
+            (
+                read_u16(data, dib + 4)? as i32,
+                read_u16(data, dib + 6)? as i16 as i32,
+                read_u16(data, dib + 10)?,
+                BI_RGB,
+                0,
+                3,
+            )
+        } else if dib_length >= INFO_HEADER_LENGTH {
+            (
+                read_u32(data, dib + 4)? as i32,
+                read_u32(data, dib + 8)? as i32,
+                read_u16(data, dib + 14)?,
+                read_u32(data, dib + 16)?,
+                read_u32(data, dib + 32)? as usize,
+                4,
+            )
+        } else {
+            return Err(DecodeError::InvalidHeader(format!(
+                "Unknown DIB header length {}",
+                dib_length
+            )));
+        };
+
+    if width <= 0 || height == 0 {
+        return Err(DecodeError::InvalidHeader(
+            "Image has no pixels".to_string(),
+        ));
+    }
+
+    // a negative height means the rows are stored top to bottom
+    let is_top_to_bottom = height < 0;
+    let width = width as u32;
+    let height = height.unsigned_abs();
+
+    let masks = match compression {
+        BI_RGB => match bits_per_pixel {
+            16 => [0x7c00, 0x03e0, 0x001f, 0],
+            _ => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+        },
+        BI_BITFIELDS | BI_ALPHABITFIELDS => {
+            // masks are part of the header from V3 onwards, otherwise they follow it
+            let masks_offset = dib + INFO_HEADER_LENGTH;
+            let alpha = if compression == BI_ALPHABITFIELDS || dib_length >= 56 {
+                read_u32(data, masks_offset + 12)?
+            } else {
+                0
+            };
+            [
+                read_u32(data, masks_offset)?,
+                read_u32(data, masks_offset + 4)?,
+                read_u32(data, masks_offset + 8)?,
+                alpha,
+            ]
+        }
+        other => {
+            return Err(DecodeError::Unsupported(format!(
+                "BMP compression method {}",
+                other
+            )))
+        }
+    };
+    let [red, green, blue, alpha] = masks.map(Mask::new);
+
+    let palette = if bits_per_pixel <= 8 {
+        let mut palette_offset = dib + dib_length;
+        if dib_length == INFO_HEADER_LENGTH && compression == BI_BITFIELDS {
+            palette_offset += 12;
+        }
+        let count = if colors_used == 0 {
+            1 << bits_per_pixel
+        } else {
+            colors_used
+        };
+        let entries = data
+            .get(palette_offset..palette_offset + count * palette_entry_length)
+            .ok_or(DecodeError::Truncated)?;
+        entries
+            .chunks_exact(palette_entry_length)
+            .map(|entry| [entry[2], entry[1], entry[0], 255])
+            .collect::<Vec<[u8; 4]>>()
+    } else {
+        Vec::new()
+    };
+
+    // rows are padded to a multiple of 4 bytes
+    let row_length = (width as usize * bits_per_pixel as usize).div_ceil(32) * 4;
+    let pixel_data = data
+        .get(pixels_offset..pixels_offset + row_length * height as usize)
+        .ok_or(DecodeError::Truncated)?;
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height as usize {
+        let stored_row = if is_top_to_bottom {
+            y
+        } else {
+            height as usize - 1 - y
+        };
+        let row = &pixel_data[stored_row * row_length..(stored_row + 1) * row_length];
+
+        for x in 0..width as usize {
+            let rgba = match bits_per_pixel {
+                1 | 4 | 8 => {
+                    let bit_offset = x * bits_per_pixel as usize;
+                    let byte = row[bit_offset / 8];
+                    let shift = 8 - bits_per_pixel as usize - bit_offset % 8;
+                    let index = (byte >> shift) & ((1_u16 << bits_per_pixel) - 1) as u8;
+                    *palette.get(index as usize).ok_or(DecodeError::InvalidData(
+                        "Palette index out of range".to_string(),
+                    ))?
+                }
+                16 | 24 | 32 => {
+                    let bytes = bits_per_pixel as usize / 8;
+                    let mut value = [0_u8; 4];
+                    value[..bytes].copy_from_slice(&row[x * bytes..(x + 1) * bytes]);
+                    let value = u32::from_le_bytes(value);
+                    [
+                        red.extract(value, 0),
+                        green.extract(value, 0),
+                        blue.extract(value, 0),
+                        alpha.extract(value, 255),
+                    ]
+                }
+                other => {
+                    return Err(DecodeError::Unsupported(format!(
+                        "{} bits per pixel",
+                        other
+                    )))
+                }
+            };
+            pixels.extend_from_slice(&rgba);
+        }
+    }
+
+    Ok(Image::new(width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(width: i32, height: i32, bits_per_pixel: u16, palette: &[u8], rows: &[u8]) -> Vec<u8> {
+        let pixels_offset = (FILE_HEADER_LENGTH + INFO_HEADER_LENGTH + palette.len()) as u32;
+        let mut file = Vec::new();
+        file.extend_from_slice(b"BM");
+        file.extend_from_slice(&(pixels_offset + rows.len() as u32).to_le_bytes());
+        file.extend_from_slice(&[0, 0, 0, 0]);
+        file.extend_from_slice(&pixels_offset.to_le_bytes());
+        file.extend_from_slice(&(INFO_HEADER_LENGTH as u32).to_le_bytes());
+        file.extend_from_slice(&width.to_le_bytes());
+        file.extend_from_slice(&height.to_le_bytes());
+        file.extend_from_slice(&1_u16.to_le_bytes());
+        file.extend_from_slice(&bits_per_pixel.to_le_bytes());
+        file.extend_from_slice(&[0; 24]);
+        file.extend_from_slice(palette);
+        file.extend_from_slice(rows);
+        file
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_24_bits_with_padding() {
+        let file = file(1, 2, 24, &[], &[
+            3, 2, 1, 0, // bottom row, padded to 4 bytes
+            6, 5, 4, 0, // top row
+        ]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.width, 1);
+        assert_eq!(result.height, 2);
+        assert_eq!(result.pixel(0, 0), [4, 5, 6, 255]);
+        assert_eq!(result.pixel(0, 1), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_top_to_bottom_32_bits() {
+        let file = file(2, -1, 32, &[], &[3, 2, 1, 0, 6, 5, 4, 0]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [1, 2, 3, 255]);
+        assert_eq!(result.pixel(1, 0), [4, 5, 6, 255]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_palette() {
+        let palette = [
+            0, 0, 255, 0, // red
+            255, 0, 0, 0, // blue
+        ];
+        let file = file(3, 1, 1, &palette, &[0b0100_0000, 0, 0, 0]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(result.pixel(1, 0), [0, 0, 255, 255]);
+        assert_eq!(result.pixel(2, 0), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_signature() {
+        let mut file = file(1, 1, 24, &[], &[0, 0, 0, 0]);
+        file[0] = b'X';
+
+        assert!(decode(&file).is_err());
+    }
+}
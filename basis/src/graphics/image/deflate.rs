@@ -0,0 +1,77 @@
+/// Biggest amount of bytes a stored block can hold
+const MAX_STORED_BLOCK_LENGTH: usize = 65535;
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+///
+/// Wraps the data into zlib stored blocks. Nothing is actually compressed, which keeps the
+/// encoder trivial, every decoder still understands it.
+///
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let blocks_count = data.len().div_ceil(MAX_STORED_BLOCK_LENGTH).max(1);
+    let mut output = Vec::with_capacity(data.len() + blocks_count * 5 + 6);
+
+    // deflate with a 32K window, no preset dictionary, fastest compression
+    output.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK_LENGTH).peekable();
+    if blocks.peek().is_none() {
+        output.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_last = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        output.push(is_last as u8);
+        output.extend_from_slice(&length.to_le_bytes());
+        output.extend_from_slice(&(!length).to_le_bytes());
+        output.extend_from_slice(block);
+    }
+
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::inflate;
+    use super::*;
+
+    #[test]
+    fn it_should_compress_like_zlib_level_0() {
+        // zlib.compress(b"hello", 0)
+        let expected = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+
+        assert_eq!(compress(b"hello"), expected);
+    }
+
+    #[test]
+    fn it_should_compress_data_bigger_than_a_block() {
+        let data = (0..150_000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+
+        let compressed = compress(&data);
+
+        assert_eq!(
+            inflate::decompress(&compressed).expect("This should work"),
+            data
+        );
+    }
+
+    #[test]
+    fn it_should_compress_empty_data() {
+        assert_eq!(
+            inflate::decompress(&compress(&[])).expect("This should work"),
+            Vec::<u8>::new()
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//!
+//! Minimal DEFLATE (RFC 1951) decoder wrapped by the zlib format (RFC 1950), enough to read the
+//! image data of PNG files.
+//!
+use super::DecodeError;
+
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which the code length code lengths are stored in dynamic blocks
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, DecodeError> {
+        let byte = *self.data.get(self.position).ok_or(DecodeError::Truncated)?;
+        let value = (byte as u32 >> self.bit) & 1;
+        self.bit += 1;
+        if self.bit == 8 {
+            self.bit = 0;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    /// Values that are not Huffman codes are stored starting from the least significant bit
+    fn bits(&mut self, count: u8) -> Result<u32, DecodeError> {
+        let mut value = 0;
+        for i in 0..count {
+            value |= self.bit()? << i;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+///
+/// Canonical Huffman code, stored as the amount of codes of each length and the symbols ordered
+/// by code.
+///
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Huffman, DecodeError> {
+        let mut counts = [0_u16; MAX_BITS + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0_u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, DecodeError> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for length in 1..=MAX_BITS {
+            code |= reader.bit()? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+
+        Err(DecodeError::InvalidData("Invalid Huffman code".to_string()))
+    }
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), DecodeError> {
+    let mut lengths = [0_u8; 288];
+    lengths[0..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..288].fill(8);
+
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), DecodeError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0_u8; 19];
+    for i in 0..code_length_count {
+        code_length_lengths[CODE_LENGTH_ORDER[i]] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or(DecodeError::InvalidData(
+                    "Repeated code length without a previous one".to_string(),
+                ))?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths.len() > literal_count + distance_count {
+        return Err(DecodeError::InvalidData(
+            "Too many code lengths".to_string(),
+        ));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), DecodeError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length =
+                    LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index])? as usize;
+
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(DecodeError::InvalidData(
+                        "Invalid distance code".to_string(),
+                    ));
+                }
+                let distance =
+                    DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index])? as usize;
+                if distance > output.len() {
+                    return Err(DecodeError::InvalidData(
+                        "Distance goes back past the start".to_string(),
+                    ));
+                }
+
+                // copied byte by byte because the ranges may overlap
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+            _ => return Err(DecodeError::InvalidData("Invalid literal code".to_string())),
+        }
+    }
+}
+
+///
+/// Decompresses raw DEFLATE data
+///
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        bit: 0,
+    };
+    let mut output = Vec::new();
+
+    loop {
+        let is_last = reader.bit()? == 1;
+
+        match reader.bits(2)? {
+            // stored
+            0 => {
+                reader.align_to_byte();
+                let header = data
+                    .get(reader.position..reader.position + 4)
+                    .ok_or(DecodeError::Truncated)?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                let complement = u16::from_le_bytes([header[2], header[3]]);
+                if length != !complement {
+                    return Err(DecodeError::InvalidData(
+                        "Stored block length mismatch".to_string(),
+                    ));
+                }
+                reader.position += 4;
+                let stored = data
+                    .get(reader.position..reader.position + length as usize)
+                    .ok_or(DecodeError::Truncated)?;
+                output.extend_from_slice(stored);
+                reader.position += length as usize;
+            }
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(DecodeError::InvalidData("Invalid block type".to_string())),
+        }
+
+        if is_last {
+            return Ok(output);
+        }
+    }
+}
+
+///
+/// Decompresses zlib data, which is DEFLATE data with a two bytes header
+///
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    match data {
+        [cmf, flg, compressed @ ..] => {
+            if cmf & 0x0f != 8 || !u16::from_be_bytes([*cmf, *flg]).is_multiple_of(31) {
+                return Err(DecodeError::InvalidHeader(
+                    "Invalid zlib header".to_string(),
+                ));
+            }
+            if flg & 0x20 != 0 {
+                return Err(DecodeError::Unsupported(
+                    "zlib preset dictionary".to_string(),
+                ));
+            }
+            inflate(compressed)
+        }
+        _ => Err(DecodeError::Truncated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_decompress_stored_blocks() {
+        // zlib.compress(b"hello", 0)
+        let data = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+
+        assert_eq!(decompress(&data).expect("This should work"), b"hello");
+    }
+
+    #[test]
+    fn it_should_decompress_fixed_huffman_blocks() {
+        // zlib.compress(b"abcabcabcabc")
+        let data = [
+            0x78, 0x9c, 0x4b, 0x4c, 0x4a, 0x4e, 0x84, 0x21, 0x00, 0x1d, 0xe0, 0x04, 0x99,
+        ];
+
+        assert_eq!(
+            decompress(&data).expect("This should work"),
+            b"abcabcabcabc"
+        );
+    }
+
+    #[test]
+    fn it_should_decompress_dynamic_huffman_blocks() {
+        let data = [
+            0x78, 0xda, 0x1d, 0x88, 0xc7, 0x11, 0x00, 0x00, 0x0c, 0x82, 0x66, 0xb5, 0xec, 0x3f,
+            0x43, 0x24, 0x3e, 0x90, 0x43, 0x4e, 0xa2, 0x6d, 0xb0, 0xde, 0x8a, 0x2e, 0x9a, 0x1b,
+            0x4b, 0x34, 0x4f, 0xd1, 0x01, 0xe7, 0x3f, 0x13, 0x18,
+        ];
+
+        assert_eq!(
+            decompress(&data).expect("This should work"),
+            b"abcccaaaacaabacaaaadcaabccabaabcabadaaaabbadabaaba"
+        );
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_header() {
+        assert!(decompress(&[0x78, 0x00, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+mod bmp;
+mod deflate;
+mod inflate;
+mod png;
+mod ppm;
+mod tga;
+
+///
+/// Decoded image, always stored as 8 bit RGBA with the first row being the top of the image.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Image {
+        debug_assert_eq!(pixels.len(), (width * height * 4) as usize);
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    ///
+    /// Image with a single opaque pixel of the given color, used when a texture is required but
+    /// none was provided
+    ///
+    pub fn splat(rgba: [u8; 4]) -> Image {
+        Image::new(1, 1, rgba.to_vec())
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), SaveImageError> {
+        save(self, file_path)
+    }
+
+    ///
+    /// OpenGL expects the first row to be the bottom of the image
+    ///
+    pub fn flip_vertically(&mut self) -> &mut Self {
+        let row_length = (self.width * 4) as usize;
+        let height = self.height as usize;
+
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - 1 - y) * row_length);
+            top[y * row_length..(y + 1) * row_length].swap_with_slice(&mut bottom[..row_length]);
+        }
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    UnknownFormat,
+    Truncated,
+    InvalidHeader(String),
+    InvalidData(String),
+    Unsupported(String),
+}
+impl std::error::Error for DecodeError {}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::UnknownFormat => write!(f, "Unknown image format"),
+            DecodeError::Truncated => write!(f, "Image data ended unexpectedly"),
+            DecodeError::InvalidHeader(message) => write!(f, "Invalid image header: {}", message),
+            DecodeError::InvalidData(message) => write!(f, "Invalid image data: {}", message),
+            DecodeError::Unsupported(message) => write!(f, "Unsupported image: {}", message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadImageError {
+    Io(std::io::Error),
+    Decode(DecodeError),
+}
+impl std::error::Error for LoadImageError {}
+
+impl From<std::io::Error> for LoadImageError {
+    fn from(err: std::io::Error) -> Self {
+        LoadImageError::Io(err)
+    }
+}
+
+impl From<DecodeError> for LoadImageError {
+    fn from(err: DecodeError) -> Self {
+        LoadImageError::Decode(err)
+    }
+}
+
+impl std::fmt::Display for LoadImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LoadImageError::Io(err) => write!(f, "IO error: {}", err),
+            LoadImageError::Decode(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveImageError {
+    Io(std::io::Error),
+    UnknownFormat(String),
+    Unsupported(Format),
+}
+impl std::error::Error for SaveImageError {}
+
+impl From<std::io::Error> for SaveImageError {
+    fn from(err: std::io::Error) -> Self {
+        SaveImageError::Io(err)
+    }
+}
+
+impl std::fmt::Display for SaveImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SaveImageError::Io(err) => write!(f, "IO error: {}", err),
+            SaveImageError::UnknownFormat(path) => {
+                write!(f, "Unknown image format for '{}'", path)
+            }
+            SaveImageError::Unsupported(format) => {
+                write!(f, "Saving {:?} images is not supported", format)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ppm,
+    Tga,
+    Bmp,
+    Png,
+}
+
+impl Format {
+    pub fn from_extension(file_path: &str) -> Option<Format> {
+        let extension = std::path::Path::new(file_path)
+            .extension()?
+            .to_str()?
+            .to_lowercase();
+
+        match extension.as_str() {
+            "ppm" | "pgm" | "pbm" | "pnm" => Some(Format::Ppm),
+            "tga" => Some(Format::Tga),
+            "bmp" => Some(Format::Bmp),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    ///
+    /// TGA files have no signature, so they can only be found through their extension
+    ///
+    pub fn from_signature(data: &[u8]) -> Option<Format> {
+        match data {
+            [b'P', b'1'..=b'6', ..] => Some(Format::Ppm),
+            [b'B', b'M', ..] => Some(Format::Bmp),
+            [0x89, b'P', b'N', b'G', ..] => Some(Format::Png),
+            _ => None,
+        }
+    }
+}
+
+pub fn decode(data: &[u8], format: Format) -> Result<Image, DecodeError> {
+    match format {
+        Format::Ppm => ppm::decode(data),
+        Format::Tga => tga::decode(data),
+        Format::Bmp => bmp::decode(data),
+        Format::Png => png::decode(data),
+    }
+}
+
+///
+/// Only PPM and PNG images can be encoded
+///
+pub fn encode(image: &Image, format: Format) -> Result<Vec<u8>, SaveImageError> {
+    match format {
+        Format::Ppm => Ok(ppm::encode(image)),
+        Format::Png => Ok(png::encode(image)),
+        Format::Tga | Format::Bmp => Err(SaveImageError::Unsupported(format)),
+    }
+}
+
+///
+/// Saves the image in the format matching the file extension
+///
+pub fn save(image: &Image, file_path: &str) -> Result<(), SaveImageError> {
+    let format = Format::from_extension(file_path)
+        .ok_or(SaveImageError::UnknownFormat(file_path.to_string()))?;
+
+    std::fs::write(file_path, encode(image, format)?)?;
+    Ok(())
+}
+
+pub fn load(file_path: &str) -> Result<Image, LoadImageError> {
+    let data = std::fs::read(file_path)?;
+
+    let format = Format::from_signature(&data)
+        .or(Format::from_extension(file_path))
+        .ok_or(DecodeError::UnknownFormat)?;
+
+    Ok(decode(&data, format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_flip_vertically() {
+        #[rustfmt::skip]
+        let mut image = Image::new(1, 3, vec![
+            1, 1, 1, 1,
+            2, 2, 2, 2,
+            3, 3, 3, 3,
+        ]);
+
+        image.flip_vertically();
+
+        assert_eq!(image.pixel(0, 0), [3, 3, 3, 3]);
+        assert_eq!(image.pixel(0, 1), [2, 2, 2, 2]);
+        assert_eq!(image.pixel(0, 2), [1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn it_should_find_format() {
+        assert_eq!(Format::from_extension("a/b/c.TGA"), Some(Format::Tga));
+        assert_eq!(Format::from_extension("a/b/c.pgm"), Some(Format::Ppm));
+        assert_eq!(Format::from_extension("a/b/c.jpg"), None);
+        assert_eq!(Format::from_signature(b"P6\n1 1\n255\n"), Some(Format::Ppm));
+        assert_eq!(Format::from_signature(b"BM...."), Some(Format::Bmp));
+        assert_eq!(Format::from_signature(b"\x00\x00\x02"), None);
+    }
+
+    #[test]
+    fn it_should_save_and_load_image() {
+        let path = std::env::temp_dir().join(format!("basis_save_{}.png", std::process::id()));
+        let path = path.to_str().expect("This should work");
+        let image = Image::new(1, 2, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        image.save(path).expect("This should work");
+        let result = load(path);
+        std::fs::remove_file(path).expect("This should work");
+
+        assert_eq!(result.expect("This should work"), image);
+        assert!(image.save("image.jpg").is_err());
+        assert!(image.save("image.tga").is_err());
+    }
+}
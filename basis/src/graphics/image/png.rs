@@ -0,0 +1,668 @@
+use super::{deflate, inflate, DecodeError, Image};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest width or height accepted, the pixels are allocated from the header before the data
+/// is read
+const MAX_DIMENSION: usize = 1 << 14;
+
+/// Starting column, starting row, column step and row step of each Adam7 pass
+const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColorType {
+    Grayscale,
+    TrueColor,
+    Indexed,
+    GrayscaleAlpha,
+    TrueColorAlpha,
+}
+
+impl ColorType {
+    fn channels(&self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::TrueColor => 3,
+            ColorType::TrueColorAlpha => 4,
+        }
+    }
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: ColorType,
+    is_interlaced: bool,
+}
+
+impl Header {
+    fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() * self.bit_depth as usize
+    }
+
+    fn row_length(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    ///
+    /// The passes that have pixels, a single one covering the whole image when not interlaced
+    ///
+    fn passes(&self) -> Vec<Pass> {
+        let passes = if self.is_interlaced {
+            ADAM7_PASSES.to_vec()
+        } else {
+            vec![(0, 0, 1, 1)]
+        };
+
+        passes
+            .into_iter()
+            .map(|(start_x, start_y, step_x, step_y)| Pass {
+                start_x,
+                start_y,
+                step_x,
+                step_y,
+                width: (self.width + step_x - 1 - start_x) / step_x,
+                height: (self.height + step_y - 1 - start_y) / step_y,
+            })
+            .filter(|pass| pass.width != 0 && pass.height != 0)
+            .collect()
+    }
+
+    ///
+    /// Length of the inflated data, each row starts with its filter type
+    ///
+    fn data_length(&self) -> usize {
+        self.passes()
+            .iter()
+            .map(|pass| (1 + self.row_length(pass.width)) * pass.height)
+            .sum()
+    }
+}
+
+///
+/// Pixels of the image read from one pass of the data, every `step_x` column and `step_y` row
+/// from `start_x` and `start_y`
+///
+struct Pass {
+    start_x: usize,
+    start_y: usize,
+    step_x: usize,
+    step_y: usize,
+    width: usize,
+    height: usize,
+}
+
+///
+/// Values that apply to the whole image and are needed to turn samples into RGBA
+///
+#[derive(Default)]
+struct Palette {
+    colors: Vec<[u8; 4]>,
+    transparent: Option<[u16; 3]>,
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, DecodeError> {
+    if data.len() != 13 {
+        return Err(DecodeError::InvalidHeader(
+            "Invalid IHDR length".to_string(),
+        ));
+    }
+
+    let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let bit_depth = data[8];
+    let color_type = match data[9] {
+        0 => ColorType::Grayscale,
+        2 => ColorType::TrueColor,
+        3 => ColorType::Indexed,
+        4 => ColorType::GrayscaleAlpha,
+        6 => ColorType::TrueColorAlpha,
+        other => {
+            return Err(DecodeError::InvalidHeader(format!(
+                "Invalid color type {}",
+                other
+            )))
+        }
+    };
+
+    let is_valid_depth = match color_type {
+        ColorType::Grayscale => [1, 2, 4, 8, 16].contains(&bit_depth),
+        ColorType::Indexed => [1, 2, 4, 8].contains(&bit_depth),
+        _ => [8, 16].contains(&bit_depth),
+    };
+    if !is_valid_depth {
+        return Err(DecodeError::InvalidHeader(format!(
+            "Invalid bit depth {} for color type {}",
+            bit_depth, data[9]
+        )));
+    }
+    if data[10] != 0 || data[11] != 0 {
+        return Err(DecodeError::Unsupported(
+            "Unknown compression or filter method".to_string(),
+        ));
+    }
+    if width == 0 || height == 0 {
+        return Err(DecodeError::InvalidHeader(
+            "Image has no pixels".to_string(),
+        ));
+    }
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(DecodeError::Unsupported(format!(
+            "Image of {}x{} pixels, the maximum is {}x{}",
+            width, height, MAX_DIMENSION, MAX_DIMENSION
+        )));
+    }
+    let is_interlaced = match data[12] {
+        0 => false,
+        1 => true,
+        other => {
+            return Err(DecodeError::InvalidHeader(format!(
+                "Invalid interlace method {}",
+                other
+            )))
+        }
+    };
+
+    Ok(Header {
+        width,
+        height,
+        bit_depth,
+        color_type,
+        is_interlaced,
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+///
+/// Reverses the filter applied to each scanline, returning the raw rows without the filter byte
+///
+fn unfilter(
+    data: &[u8],
+    row_length: usize,
+    height: usize,
+    pixel_bytes: usize,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut rows = vec![0_u8; row_length * height];
+
+    for y in 0..height {
+        let start = y * (row_length + 1);
+        let filter = *data.get(start).ok_or(DecodeError::Truncated)?;
+        let line = data
+            .get(start + 1..start + 1 + row_length)
+            .ok_or(DecodeError::Truncated)?;
+
+        let (previous_rows, current_rows) = rows.split_at_mut(y * row_length);
+        let previous = if y == 0 {
+            None
+        } else {
+            Some(&previous_rows[(y - 1) * row_length..])
+        };
+        let current = &mut current_rows[..row_length];
+
+        for x in 0..row_length {
+            let a = if x >= pixel_bytes {
+                current[x - pixel_bytes]
+            } else {
+                0
+            };
+            let b = previous.map_or(0, |row| row[x]);
+            let c = match previous {
+                Some(row) if x >= pixel_bytes => row[x - pixel_bytes],
+                _ => 0,
+            };
+
+            current[x] = match filter {
+                0 => line[x],
+                1 => line[x].wrapping_add(a),
+                2 => line[x].wrapping_add(b),
+                3 => line[x].wrapping_add(((a as u16 + b as u16) / 2) as u8),
+                4 => line[x].wrapping_add(paeth(a, b, c)),
+                other => {
+                    return Err(DecodeError::InvalidData(format!(
+                        "Invalid filter type {}",
+                        other
+                    )))
+                }
+            };
+        }
+    }
+
+    Ok(rows)
+}
+
+///
+/// Reads the `index`th sample of a row, samples with 16 bits are returned as is
+///
+fn sample(row: &[u8], index: usize, bit_depth: u8) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit_offset = index * bit_depth as usize;
+            let shift = 8 - bit_depth as usize - bit_offset % 8;
+            ((row[bit_offset / 8] >> shift) & ((1 << bit_depth) - 1)) as u16
+        }
+    }
+}
+
+fn to_rgba(
+    row: &[u8],
+    x: usize,
+    header: &Header,
+    palette: &Palette,
+) -> Result<[u8; 4], DecodeError> {
+    let channels = header.color_type.channels();
+    let depth = header.bit_depth;
+    let max = (1_u32 << depth) - 1;
+    let scale = |value: u16| (value as u32 * 255 / max) as u8;
+    let value = |channel: usize| sample(row, x * channels + channel, depth);
+
+    let rgba = match header.color_type {
+        ColorType::Indexed => {
+            *palette
+                .colors
+                .get(value(0) as usize)
+                .ok_or(DecodeError::InvalidData(
+                    "Palette index out of range".to_string(),
+                ))?
+        }
+        ColorType::Grayscale => {
+            let gray = value(0);
+            let alpha = match palette.transparent {
+                Some([transparent, _, _]) if transparent == gray => 0,
+                _ => 255,
+            };
+            [scale(gray), scale(gray), scale(gray), alpha]
+        }
+        ColorType::TrueColor => {
+            let rgb = [value(0), value(1), value(2)];
+            let alpha = if palette.transparent == Some(rgb) {
+                0
+            } else {
+                255
+            };
+            [scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), alpha]
+        }
+        ColorType::GrayscaleAlpha => {
+            let gray = scale(value(0));
+            [gray, gray, gray, scale(value(1))]
+        }
+        ColorType::TrueColorAlpha => [
+            scale(value(0)),
+            scale(value(1)),
+            scale(value(2)),
+            scale(value(3)),
+        ],
+    };
+
+    Ok(rgba)
+}
+
+///
+/// Decodes PNG files of every color type and bit depth, interlaced or not. Ancillary chunks other
+/// than transparency are ignored.
+///
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err(DecodeError::InvalidHeader(
+            "Invalid PNG signature".to_string(),
+        ));
+    }
+
+    let mut position = SIGNATURE.len();
+    let mut header = None;
+    let mut palette = Palette::default();
+    let mut compressed = Vec::new();
+
+    loop {
+        let length_bytes = data
+            .get(position..position + 4)
+            .ok_or(DecodeError::Truncated)?;
+        let length = u32::from_be_bytes([
+            length_bytes[0],
+            length_bytes[1],
+            length_bytes[2],
+            length_bytes[3],
+        ]) as usize;
+        let kind = data
+            .get(position + 4..position + 8)
+            .ok_or(DecodeError::Truncated)?;
+        let chunk = data
+            .get(position + 8..position + 8 + length)
+            .ok_or(DecodeError::Truncated)?;
+        // skip length, type, data and crc
+        position += 12 + length;
+
+        match kind {
+            b"IHDR" => header = Some(parse_header(chunk)?),
+            b"PLTE" => {
+                palette.colors = chunk
+                    .chunks_exact(3)
+                    .map(|color| [color[0], color[1], color[2], 255])
+                    .collect();
+            }
+            b"tRNS" => match header.as_ref().map(|header| header.color_type) {
+                Some(ColorType::Indexed) => {
+                    for (color, alpha) in palette.colors.iter_mut().zip(chunk) {
+                        color[3] = *alpha;
+                    }
+                }
+                Some(ColorType::Grayscale) if chunk.len() >= 2 => {
+                    let gray = u16::from_be_bytes([chunk[0], chunk[1]]);
+                    palette.transparent = Some([gray, 0, 0]);
+                }
+                Some(ColorType::TrueColor) if chunk.len() >= 6 => {
+                    palette.transparent = Some([
+                        u16::from_be_bytes([chunk[0], chunk[1]]),
+                        u16::from_be_bytes([chunk[2], chunk[3]]),
+                        u16::from_be_bytes([chunk[4], chunk[5]]),
+                    ]);
+                }
+                _ => {}
+            },
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(DecodeError::InvalidHeader("Missing IHDR chunk".to_string()))?;
+    if header.color_type == ColorType::Indexed && palette.colors.is_empty() {
+        return Err(DecodeError::InvalidData("Missing PLTE chunk".to_string()));
+    }
+
+    let decompressed = inflate::decompress(&compressed)?;
+    if decompressed.len() < header.data_length() {
+        return Err(DecodeError::Truncated);
+    }
+    if decompressed.len() > header.data_length() {
+        return Err(DecodeError::InvalidData(
+            "Image data is longer than the image".to_string(),
+        ));
+    }
+
+    let pixel_bytes = header.bits_per_pixel().div_ceil(8);
+    let length = header
+        .width
+        .checked_mul(header.height)
+        .and_then(|length| length.checked_mul(4))
+        .ok_or(DecodeError::Unsupported("Image is too large".to_string()))?;
+    let mut pixels = vec![0_u8; length];
+
+    let mut offset = 0;
+    for pass in header.passes() {
+        let row_length = header.row_length(pass.width);
+        let rows = unfilter(
+            decompressed.get(offset..).ok_or(DecodeError::Truncated)?,
+            row_length,
+            pass.height,
+            pixel_bytes,
+        )?;
+        offset += (row_length + 1) * pass.height;
+
+        for (pass_y, row) in rows.chunks_exact(row_length).enumerate() {
+            for pass_x in 0..pass.width {
+                let x = pass.start_x + pass_x * pass.step_x;
+                let y = pass.start_y + pass_y * pass.step_y;
+                let i = (y * header.width + x) * 4;
+                pixels[i..i + 4].copy_from_slice(&to_rgba(row, pass_x, &header, &palette)?);
+            }
+        }
+    }
+
+    Ok(Image::new(
+        header.width as u32,
+        header.height as u32,
+        pixels,
+    ))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let crc_start = output.len();
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    let crc = crc32(&output[crc_start..]);
+    output.extend_from_slice(&crc.to_be_bytes());
+}
+
+///
+/// Encodes the image as 8 bit RGBA, without filtering the rows
+///
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    // bit depth, color type, compression, filter and interlace methods
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let row_length = image.width as usize * 4;
+    let mut rows = Vec::with_capacity((row_length + 1) * image.height as usize);
+    for row in image.pixels.chunks(row_length.max(1)) {
+        rows.push(0); // filter type "None"
+        rows.extend_from_slice(row);
+    }
+
+    let mut output = SIGNATURE.to_vec();
+    write_chunk(&mut output, b"IHDR", &header);
+    write_chunk(&mut output, b"IDAT", &deflate::compress(&rows));
+    write_chunk(&mut output, b"IEND", &[]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        // the crc is not verified
+        chunk.extend_from_slice(&[0, 0, 0, 0]);
+        chunk
+    }
+
+    ///
+    /// zlib data using a single stored block
+    ///
+    fn stored(data: &[u8]) -> Vec<u8> {
+        let length = data.len() as u16;
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(data);
+        // adler32 is not verified
+        zlib.extend_from_slice(&[0, 0, 0, 0]);
+        zlib
+    }
+
+    fn file(
+        width: u32,
+        height: u32,
+        depth: u8,
+        color_type: u8,
+        extra: &[Vec<u8>],
+        rows: &[u8],
+    ) -> Vec<u8> {
+        let mut header = width.to_be_bytes().to_vec();
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+
+        let mut file = SIGNATURE.to_vec();
+        file.extend(chunk(b"IHDR", &header));
+        for chunk in extra {
+            file.extend_from_slice(chunk);
+        }
+        file.extend(chunk(b"IDAT", &stored(rows)));
+        file.extend(chunk(b"IEND", &[]));
+        file
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_rgba() {
+        let file = file(2, 1, 8, 6, &[], &[
+            0, 1, 2, 3, 4, 5, 6, 7, 8,
+        ]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.width, 2);
+        assert_eq!(result.height, 1);
+        assert_eq!(result.pixel(0, 0), [1, 2, 3, 4]);
+        assert_eq!(result.pixel(1, 0), [5, 6, 7, 8]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_reverse_filters() {
+        let file = file(2, 2, 8, 2, &[], &[
+            1, 10, 20, 30, 1, 1, 1, // sub
+            2, 5, 5, 5, 5, 5, 5, // up
+        ]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [10, 20, 30, 255]);
+        assert_eq!(result.pixel(1, 0), [11, 21, 31, 255]);
+        assert_eq!(result.pixel(0, 1), [15, 25, 35, 255]);
+        assert_eq!(result.pixel(1, 1), [16, 26, 36, 255]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_palette_with_transparency() {
+        let palette = chunk(b"PLTE", &[255, 0, 0, 0, 0, 255]);
+        let transparency = chunk(b"tRNS", &[0]);
+        let file = file(3, 1, 1, 3, &[palette, transparency], &[
+            0, 0b0100_0000,
+        ]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [255, 0, 0, 0]);
+        assert_eq!(result.pixel(1, 0), [0, 0, 255, 255]);
+        assert_eq!(result.pixel(2, 0), [255, 0, 0, 0]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_interlaced() {
+        let mut file = file(2, 2, 8, 0, &[], &[
+            0, 10, // pass 1: (0, 0)
+            0, 20, // pass 6: (1, 0)
+            0, 30, 40, // pass 7: (0, 1) and (1, 1)
+        ]);
+        // interlace method of the IHDR chunk
+        file[SIGNATURE.len() + 8 + 12] = 1;
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [10, 10, 10, 255]);
+        assert_eq!(result.pixel(1, 0), [20, 20, 20, 255]);
+        assert_eq!(result.pixel(0, 1), [30, 30, 30, 255]);
+        assert_eq!(result.pixel(1, 1), [40, 40, 40, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_16_bits_grayscale() {
+        let file = file(1, 1, 16, 0, &[], &[0, 0x80, 0xff]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_signature() {
+        let result = decode(b"\x89PNX\r\n\x1a\n");
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid PNG signature"));
+    }
+
+    #[test]
+    fn it_should_reject_huge_dimensions_before_allocating() {
+        let file = file(0x7fff_ffff, 0x7fff_ffff, 8, 6, &[], &[0, 1, 2, 3, 4]);
+
+        assert!(matches!(decode(&file), Err(DecodeError::Unsupported(_))));
+    }
+
+    #[test]
+    fn it_should_reject_data_of_another_size_than_the_image() {
+        let short = file(2, 2, 8, 0, &[], &[0, 1, 2, 0, 3]);
+        let long = file(1, 1, 8, 0, &[], &[0, 1, 2]);
+
+        assert!(matches!(decode(&short), Err(DecodeError::Truncated)));
+        assert!(matches!(decode(&long), Err(DecodeError::InvalidData(_))));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_interlace_methods() {
+        let mut file = file(1, 1, 8, 0, &[], &[0, 1]);
+        file[SIGNATURE.len() + 8 + 12] = 2;
+
+        assert!(matches!(decode(&file), Err(DecodeError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn it_should_compute_chunk_crc() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn it_should_encode_and_decode_the_same_image() {
+        #[rustfmt::skip]
+        let image = Image::new(2, 2, vec![
+            255, 0, 0, 255,     0, 255, 0, 128,
+            0, 0, 255, 0,       10, 20, 30, 40,
+        ]);
+
+        let encoded = encode(&image);
+
+        assert_eq!(&encoded[..8], &SIGNATURE);
+        assert_eq!(decode(&encoded).expect("This should work"), image);
+    }
+}
@@ -0,0 +1,285 @@
+use super::{DecodeError, Image};
+
+///
+/// Reads the header values, which are separated by whitespace and may be interleaved with
+/// comments starting with `#` until the end of the line.
+///
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(byte) = self.data.get(self.position) {
+            if *byte == b'#' {
+                while self.data.get(self.position).is_some_and(|b| *b != b'\n') {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<&'a [u8], DecodeError> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(DecodeError::Truncated);
+        }
+        Ok(&self.data[start..self.position])
+    }
+
+    fn next_number(&mut self) -> Result<u32, DecodeError> {
+        let token = self.next_token()?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .ok_or(DecodeError::InvalidData(format!(
+                "Expected a number, found '{}'",
+                String::from_utf8_lossy(token)
+            )))
+    }
+
+    ///
+    /// In plain bitmaps (P1) the bits may not be separated by whitespace
+    ///
+    fn next_bit(&mut self) -> Result<u32, DecodeError> {
+        self.skip_whitespace_and_comments();
+        match self.data.get(self.position) {
+            Some(b'0') => {
+                self.position += 1;
+                Ok(0)
+            }
+            Some(b'1') => {
+                self.position += 1;
+                Ok(1)
+            }
+            Some(_) => Err(DecodeError::InvalidData("Expected 0 or 1".to_string())),
+            None => Err(DecodeError::Truncated),
+        }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position.min(self.data.len())..]
+    }
+}
+
+fn scale(value: u32, max_value: u32) -> u8 {
+    ((value.min(max_value) * 255 + max_value / 2) / max_value) as u8
+}
+
+///
+/// Decodes the Netpbm family: PBM (P1, P4), PGM (P2, P5) and PPM (P3, P6)
+///
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    let mut reader = Reader { data, position: 0 };
+
+    let magic = reader.next_token()?;
+    let kind = match magic {
+        [b'P', kind @ b'1'..=b'6'] => *kind,
+        _ => {
+            return Err(DecodeError::InvalidHeader(
+                "Invalid Netpbm magic".to_string(),
+            ))
+        }
+    };
+
+    let width = reader.next_number()?;
+    let height = reader.next_number()?;
+    let max_value = match kind {
+        b'1' | b'4' => 1,
+        _ => reader.next_number()?,
+    };
+
+    if width == 0 || height == 0 {
+        return Err(DecodeError::InvalidHeader(
+            "Image has no pixels".to_string(),
+        ));
+    }
+    if max_value == 0 || max_value > 65535 {
+        return Err(DecodeError::InvalidHeader(format!(
+            "Invalid maximum value {}",
+            max_value
+        )));
+    }
+
+    let pixel_count = (width * height) as usize;
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+
+    match kind {
+        // plain
+        b'1' => {
+            for _ in 0..pixel_count {
+                // in bitmaps 1 means black
+                let value = (1 - reader.next_bit()?) as u8 * 255;
+                pixels.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        b'2' => {
+            for _ in 0..pixel_count {
+                let value = scale(reader.next_number()?, max_value);
+                pixels.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        b'3' => {
+            for _ in 0..pixel_count {
+                let r = scale(reader.next_number()?, max_value);
+                let g = scale(reader.next_number()?, max_value);
+                let b = scale(reader.next_number()?, max_value);
+                pixels.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+        // raw, a single whitespace separates the header from the data
+        b'4' => {
+            reader.position += 1;
+            let row_length = width.div_ceil(8) as usize;
+            let raw = reader.remaining();
+            if raw.len() < row_length * height as usize {
+                return Err(DecodeError::Truncated);
+            }
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let bit = (raw[y * row_length + x / 8] >> (7 - x % 8)) & 1;
+                    let value = (1 - bit) * 255;
+                    pixels.extend_from_slice(&[value, value, value, 255]);
+                }
+            }
+        }
+        _ => {
+            reader.position += 1;
+            let channels = if kind == b'5' { 1 } else { 3 };
+            let sample_length = if max_value > 255 { 2 } else { 1 };
+            let raw = reader.remaining();
+            if raw.len() < pixel_count * channels * sample_length {
+                return Err(DecodeError::Truncated);
+            }
+
+            let samples = raw
+                .chunks_exact(sample_length)
+                .take(pixel_count * channels)
+                .map(|sample| match sample {
+                    [high, low] => scale(u32::from_be_bytes([0, 0, *high, *low]), max_value),
+                    [value] => scale(*value as u32, max_value),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<u8>>();
+
+            for pixel in samples.chunks_exact(channels) {
+                match pixel {
+                    [value] => pixels.extend_from_slice(&[*value, *value, *value, 255]),
+                    [r, g, b] => pixels.extend_from_slice(&[*r, *g, *b, 255]),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    Ok(Image::new(width, height, pixels))
+}
+
+///
+/// Encodes the image as a binary PPM (P6), the alpha channel is dropped
+///
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut output = format!("P6\n{} {}\n255\n", image.width, image.height).into_bytes();
+    output.reserve(image.pixels.len() / 4 * 3);
+    for pixel in image.pixels.chunks(4) {
+        output.extend_from_slice(&pixel[..3]);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_decode_plain_ppm() {
+        let file = b"P3
+            # comment
+            2 1
+            255
+            255 0 0   0 0 255
+";
+
+        let result = decode(file).expect("This should work");
+
+        assert_eq!(result.width, 2);
+        assert_eq!(result.height, 1);
+        assert_eq!(result.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(result.pixel(1, 0), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_raw_ppm() {
+        let mut file = b"P6 1 2 255\n".to_vec();
+        file.extend_from_slice(&[10, 20, 30, 40, 50, 60]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [10, 20, 30, 255]);
+        assert_eq!(result.pixel(0, 1), [40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_pgm_scaling_max_value() {
+        let plain = decode(b"P2 2 1 15 0 15").expect("This should work");
+        let raw = decode(b"P5 2 1 15\n\x00\x0f").expect("This should work");
+
+        assert_eq!(plain.pixel(0, 0), [0, 0, 0, 255]);
+        assert_eq!(plain.pixel(1, 0), [255, 255, 255, 255]);
+        assert_eq!(plain, raw);
+    }
+
+    #[test]
+    fn it_should_decode_16_bits_samples() {
+        let result = decode(b"P5 1 1 65535\n\xff\xff").expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_bitmaps() {
+        let plain = decode(b"P1 3 1 101").expect("This should work");
+        let raw = decode(b"P4 3 1\n\xa0").expect("This should work");
+
+        assert_eq!(plain.pixel(0, 0), [0, 0, 0, 255]);
+        assert_eq!(plain.pixel(1, 0), [255, 255, 255, 255]);
+        assert_eq!(plain, raw);
+    }
+
+    #[test]
+    fn it_should_fail_on_truncated_data() {
+        let result = decode(b"P6 2 2 255\n\x00\x00\x00");
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("ended unexpectedly"));
+    }
+
+    #[test]
+    fn it_should_encode_binary_ppm() {
+        let image = Image::new(2, 1, vec![255, 0, 0, 255, 1, 2, 3, 0]);
+
+        let result = encode(&image);
+
+        assert_eq!(result, b"P6\n2 1\n255\n\xff\x00\x00\x01\x02\x03");
+        assert_eq!(
+            decode(&result).expect("This should work"),
+            Image::new(2, 1, vec![255, 0, 0, 255, 1, 2, 3, 255])
+        );
+    }
+}
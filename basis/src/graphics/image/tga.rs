@@ -0,0 +1,285 @@
+use super::{DecodeError, Image};
+
+const HEADER_LENGTH: usize = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImageType {
+    ColorMapped,
+    TrueColor,
+    Grayscale,
+}
+
+///
+/// Converts a single stored pixel into RGBA
+///
+fn to_rgba(
+    pixel: &[u8],
+    image_type: ImageType,
+    color_map: &[[u8; 4]],
+) -> Result<[u8; 4], DecodeError> {
+    match (image_type, pixel) {
+        (ImageType::ColorMapped, [index]) => {
+            color_map
+                .get(*index as usize)
+                .copied()
+                .ok_or(DecodeError::InvalidData(
+                    "Color map index out of range".to_string(),
+                ))
+        }
+        (ImageType::ColorMapped, [low, high]) => color_map
+            .get(u16::from_le_bytes([*low, *high]) as usize)
+            .copied()
+            .ok_or(DecodeError::InvalidData(
+                "Color map index out of range".to_string(),
+            )),
+        (ImageType::Grayscale, [value]) => Ok([*value, *value, *value, 255]),
+        (ImageType::Grayscale, [value, alpha]) => Ok([*value, *value, *value, *alpha]),
+        (_, color) => color_entry_to_rgba(color),
+    }
+}
+
+///
+/// True color pixels are stored as BGR(A), 16 bits pixels are packed as ARRRRRGG GGGBBBBB
+///
+fn color_entry_to_rgba(color: &[u8]) -> Result<[u8; 4], DecodeError> {
+    match color {
+        [low, high] => {
+            let packed = u16::from_le_bytes([*low, *high]);
+            let expand = |value: u16| ((value & 0x1f) * 255 / 31) as u8;
+            Ok([
+                expand(packed >> 10),
+                expand(packed >> 5),
+                expand(packed),
+                255,
+            ])
+        }
+        [b, g, r] => Ok([*r, *g, *b, 255]),
+        [b, g, r, a] => Ok([*r, *g, *b, *a]),
+        _ => Err(DecodeError::Unsupported(format!(
+            "{} bits color entries",
+            color.len() * 8
+        ))),
+    }
+}
+
+///
+/// Decodes uncompressed and run-length encoded TGA files, either color mapped, true color or
+/// grayscale.
+///
+pub fn decode(data: &[u8]) -> Result<Image, DecodeError> {
+    if data.len() < HEADER_LENGTH {
+        return Err(DecodeError::Truncated);
+    }
+
+    let id_length = data[0] as usize;
+    let color_map_type = data[1];
+    let (image_type, is_rle) = match data[2] {
+        1 => (ImageType::ColorMapped, false),
+        2 => (ImageType::TrueColor, false),
+        3 => (ImageType::Grayscale, false),
+        9 => (ImageType::ColorMapped, true),
+        10 => (ImageType::TrueColor, true),
+        11 => (ImageType::Grayscale, true),
+        0 => {
+            return Err(DecodeError::InvalidData(
+                "TGA file has no image data".to_string(),
+            ))
+        }
+        other => {
+            return Err(DecodeError::Unsupported(format!(
+                "TGA image type {}",
+                other
+            )))
+        }
+    };
+    let color_map_first_entry = u16::from_le_bytes([data[3], data[4]]) as usize;
+    let color_map_length = u16::from_le_bytes([data[5], data[6]]) as usize;
+    let color_map_entry_bytes = (data[7] as usize).div_ceil(8);
+    let width = u16::from_le_bytes([data[12], data[13]]) as u32;
+    let height = u16::from_le_bytes([data[14], data[15]]) as u32;
+    let pixel_bytes = (data[16] as usize).div_ceil(8);
+    let descriptor = data[17];
+    let is_right_to_left = descriptor & 0x10 != 0;
+    let is_top_to_bottom = descriptor & 0x20 != 0;
+
+    if width == 0 || height == 0 {
+        return Err(DecodeError::InvalidHeader(
+            "Image has no pixels".to_string(),
+        ));
+    }
+    if pixel_bytes == 0 || pixel_bytes > 4 {
+        return Err(DecodeError::InvalidHeader(format!(
+            "Invalid pixel depth {}",
+            data[16]
+        )));
+    }
+
+    if color_map_type == 1 && !(2..=4).contains(&color_map_entry_bytes) {
+        return Err(DecodeError::InvalidHeader(format!(
+            "Invalid color map entry depth {}",
+            data[7]
+        )));
+    }
+
+    let mut position = HEADER_LENGTH + id_length;
+
+    let mut color_map = vec![[0, 0, 0, 255]; color_map_first_entry];
+    if color_map_type == 1 {
+        let color_map_end = position + color_map_length * color_map_entry_bytes;
+        let entries = data
+            .get(position..color_map_end)
+            .ok_or(DecodeError::Truncated)?;
+        for entry in entries.chunks_exact(color_map_entry_bytes) {
+            color_map.push(color_entry_to_rgba(entry)?);
+        }
+        position = color_map_end;
+    } else if image_type == ImageType::ColorMapped {
+        return Err(DecodeError::InvalidHeader(
+            "Color mapped image without a color map".to_string(),
+        ));
+    }
+
+    let pixel_count = (width * height) as usize;
+    let mut stored = Vec::with_capacity(pixel_count);
+
+    if is_rle {
+        while stored.len() < pixel_count {
+            let packet = *data.get(position).ok_or(DecodeError::Truncated)?;
+            let count = (packet & 0x7f) as usize + 1;
+            position += 1;
+
+            if packet & 0x80 != 0 {
+                let pixel = data
+                    .get(position..position + pixel_bytes)
+                    .ok_or(DecodeError::Truncated)?;
+                let rgba = to_rgba(pixel, image_type, &color_map)?;
+                stored.extend(std::iter::repeat_n(rgba, count));
+                position += pixel_bytes;
+            } else {
+                let raw = data
+                    .get(position..position + count * pixel_bytes)
+                    .ok_or(DecodeError::Truncated)?;
+                for pixel in raw.chunks_exact(pixel_bytes) {
+                    stored.push(to_rgba(pixel, image_type, &color_map)?);
+                }
+                position += count * pixel_bytes;
+            }
+        }
+        stored.truncate(pixel_count);
+    } else {
+        let raw = data
+            .get(position..position + pixel_count * pixel_bytes)
+            .ok_or(DecodeError::Truncated)?;
+        for pixel in raw.chunks_exact(pixel_bytes) {
+            stored.push(to_rgba(pixel, image_type, &color_map)?);
+        }
+    }
+
+    // by default TGA images are stored bottom to top, left to right
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+    for y in 0..height as usize {
+        let row = if is_top_to_bottom {
+            y
+        } else {
+            height as usize - 1 - y
+        };
+        for x in 0..width as usize {
+            let column = if is_right_to_left {
+                width as usize - 1 - x
+            } else {
+                x
+            };
+            pixels.extend_from_slice(&stored[row * width as usize + column]);
+        }
+    }
+
+    Ok(Image::new(width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(image_type: u8, width: u16, height: u16, depth: u8, descriptor: u8) -> Vec<u8> {
+        let mut header = vec![0; HEADER_LENGTH];
+        header[2] = image_type;
+        header[12..14].copy_from_slice(&width.to_le_bytes());
+        header[14..16].copy_from_slice(&height.to_le_bytes());
+        header[16] = depth;
+        header[17] = descriptor;
+        header
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_decode_bottom_to_top_true_color() {
+        let mut file = header(2, 1, 2, 24, 0);
+        file.extend_from_slice(&[
+            3, 2, 1, // bottom
+            6, 5, 4, // top
+        ]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [4, 5, 6, 255]);
+        assert_eq!(result.pixel(0, 1), [1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_top_to_bottom_with_alpha() {
+        let mut file = header(2, 2, 1, 32, 0x28);
+        file.extend_from_slice(&[3, 2, 1, 128, 6, 5, 4, 0]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [1, 2, 3, 128]);
+        assert_eq!(result.pixel(1, 0), [4, 5, 6, 0]);
+    }
+
+    #[test]
+    fn it_should_decode_run_length_encoding() {
+        let mut file = header(11, 4, 1, 8, 0x20);
+        // 3 repeated pixels followed by 1 raw pixel
+        file.extend_from_slice(&[0x82, 200, 0x00, 7]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [200, 200, 200, 255]);
+        assert_eq!(result.pixel(2, 0), [200, 200, 200, 255]);
+        assert_eq!(result.pixel(3, 0), [7, 7, 7, 255]);
+    }
+
+    #[test]
+    fn it_should_decode_color_mapped() {
+        let mut file = header(1, 2, 1, 8, 0x20);
+        file[1] = 1;
+        file[5..7].copy_from_slice(&2_u16.to_le_bytes());
+        file[7] = 24;
+        file.extend_from_slice(&[0, 0, 255, 255, 0, 0]);
+        file.extend_from_slice(&[1, 0]);
+
+        let result = decode(&file).expect("This should work");
+
+        assert_eq!(result.pixel(0, 0), [0, 0, 255, 255]);
+        assert_eq!(result.pixel(1, 0), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn it_should_reject_empty_color_map_entries() {
+        let mut file = header(1, 2, 1, 8, 0x20);
+        file[1] = 1;
+        file[5..7].copy_from_slice(&2_u16.to_le_bytes());
+        file[7] = 0;
+        file.extend_from_slice(&[1, 0]);
+
+        assert!(matches!(decode(&file), Err(DecodeError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn it_should_fail_on_truncated_data() {
+        let mut file = header(2, 2, 2, 24, 0);
+        file.extend_from_slice(&[0, 0, 0]);
+
+        assert!(decode(&file).is_err());
+    }
+}
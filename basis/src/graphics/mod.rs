@@ -1,7 +1,15 @@
 pub mod aabb;
 pub mod glw; // OpenGL Wrapper
+#[cfg(test)]
+pub mod headless;
+pub mod image;
+pub mod null_window;
+pub mod shader_preprocessor;
+pub mod shader_program;
+pub mod std140;
 pub mod triangulation;
 pub mod wavefront;
 pub mod window;
+pub mod window_backend;
 
 pub use glfw;
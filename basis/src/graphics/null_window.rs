@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+
+use glfw::WindowEvent;
+
+use crate::graphics::{
+    window::{CursorMode, DisplayMode, GamepadInput, Vsync},
+    window_backend::WindowBackend,
+};
+
+///
+/// A window without display server, for the tools and tests driving the `Engine`. It plays
+/// scripted events, one list per frame, and its clock advances by `frame_duration` at each
+/// frame so runs are deterministic. There is no GL context unless one was made current before,
+/// e.g. a headless EGL one, the engine then skips the rendering.
+///
+#[derive(Debug, Clone)]
+pub struct NullWindow {
+    size: (i32, i32),
+    /// Seconds added to the clock at each frame
+    pub frame_duration: f64,
+    frames: usize,
+    close_after: Option<usize>,
+    events: VecDeque<Vec<WindowEvent>>,
+    gamepads: Vec<GamepadInput>,
+    has_gl_context: bool,
+    cursor_mode: CursorMode,
+    display_mode: DisplayMode,
+    monitor: usize,
+    clipboard: Option<String>,
+}
+
+impl NullWindow {
+    ///
+    /// Closes the window once this many frames were presented
+    ///
+    pub fn close_after(mut self, frames: usize) -> Self {
+        self.close_after = Some(frames);
+        self
+    }
+
+    ///
+    /// Events returned by the next calls to `poll_events`, a list per frame
+    ///
+    pub fn with_events(mut self, frames: Vec<Vec<WindowEvent>>) -> Self {
+        self.events = frames.into();
+        self
+    }
+
+    ///
+    /// Gamepads returned by every call to `poll_gamepads`
+    ///
+    pub fn with_gamepads(mut self, gamepads: Vec<GamepadInput>) -> Self {
+        self.gamepads = gamepads;
+        self
+    }
+
+    ///
+    /// A GL context of the size of the window is current, the engine renders into it
+    ///
+    pub fn with_current_gl_context(mut self) -> Self {
+        self.has_gl_context = true;
+        self
+    }
+
+    ///
+    /// Number of frames presented so far
+    ///
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+}
+
+impl WindowBackend for NullWindow {
+    fn create(width: u32, height: u32, _title: &str) -> NullWindow {
+        NullWindow {
+            size: (width as i32, height as i32),
+            frame_duration: 1.0 / 60.0,
+            frames: 0,
+            close_after: None,
+            events: VecDeque::new(),
+            gamepads: Vec::new(),
+            has_gl_context: false,
+            cursor_mode: CursorMode::Normal,
+            display_mode: DisplayMode::Windowed,
+            monitor: 0,
+            clipboard: None,
+        }
+    }
+
+    fn init_gl(&mut self) -> bool {
+        self.has_gl_context
+    }
+
+    fn should_close(&self) -> bool {
+        self.close_after.is_some_and(|frames| self.frames >= frames)
+    }
+
+    fn poll_events(&mut self) -> Vec<WindowEvent> {
+        self.events.pop_front().unwrap_or_default()
+    }
+
+    fn swap_buffers(&mut self) {
+        self.frames += 1;
+    }
+
+    fn set_vsync(&mut self, _vsync: Vsync) {}
+
+    fn get_size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn get_framebuffer_size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn get_time(&self) -> f64 {
+        self.frames as f64 * self.frame_duration
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    fn monitor(&self) -> usize {
+        self.monitor
+    }
+
+    fn set_display_mode(&mut self, mode: DisplayMode, monitor: usize) {
+        self.display_mode = mode;
+        self.monitor = monitor;
+    }
+
+    fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        self.cursor_mode = mode;
+    }
+
+    fn poll_gamepads(&self) -> Vec<GamepadInput> {
+        self.gamepads.clone()
+    }
+
+    fn get_clipboard_string(&self) -> Option<String> {
+        self.clipboard.clone()
+    }
+
+    fn set_clipboard_string(&mut self, text: &str) {
+        self.clipboard = Some(text.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glfw::{Action, Key, Modifiers};
+
+    use super::*;
+
+    #[test]
+    fn it_should_play_the_scripted_frames() {
+        let press = WindowEvent::Key(Key::Space, 0, Action::Press, Modifiers::empty());
+        let mut window = NullWindow::create(320, 240, "test")
+            .close_after(2)
+            .with_events(vec![vec![press.clone()]]);
+
+        assert!(!window.init_gl());
+        assert_eq!(window.poll_events(), vec![press]);
+        window.swap_buffers();
+        assert!(!window.should_close());
+        assert_eq!(window.poll_events(), vec![]);
+        window.swap_buffers();
+
+        assert!(window.should_close());
+        assert_eq!(window.get_time(), 2.0 / 60.0);
+    }
+}
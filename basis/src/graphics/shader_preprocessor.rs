@@ -0,0 +1,365 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+///
+/// GLSL source expanded by `preprocess`, ready to be compiled
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderSource {
+    pub code: String,
+    ///
+    /// Every file the source was built from, the root shader first. The index of a file is its
+    /// source string number in the `#line` directives, so it is the number in front of the
+    /// lines of the GL info log.
+    ///
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum PreprocessError {
+    Io(PathBuf, std::io::Error),
+    /// A file includes itself, directly or through other files
+    IncludeCycle(PathBuf),
+    InvalidDirective {
+        file: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+impl std::error::Error for PreprocessError {}
+
+impl std::fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PreprocessError::Io(file, err) => {
+                write!(f, "Failed to read '{}': {}", file.display(), err)
+            }
+            PreprocessError::IncludeCycle(file) => {
+                write!(f, "'{}' includes itself", file.display())
+            }
+            PreprocessError::InvalidDirective {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file.display(), line, message),
+        }
+    }
+}
+
+///
+/// Expands the `#include "file"` directives of a GLSL file and adds `defines` after its
+/// `#version`.
+///
+/// * Included paths are relative to the including file
+/// * A file is only included once, the following includes of it are ignored
+/// * `#line` directives keep the line numbers of the info log pointing into the right file
+///
+pub fn preprocess(
+    path: &str,
+    defines: &[(String, String)],
+) -> Result<ShaderSource, PreprocessError> {
+    let mut preprocessor = Preprocessor::default();
+    let root = PathBuf::from(path);
+    let contents = read(&root)?;
+
+    let mut lines = contents.lines().enumerate().peekable();
+    // the version must stay the first statement of the shader, the defines come right after
+    while let Some((index, line)) =
+        lines.next_if(|(_, line)| !line.trim_start().starts_with("#version"))
+    {
+        if !line.trim().is_empty() && !line.trim_start().starts_with("//") {
+            return Err(PreprocessError::InvalidDirective {
+                file: root,
+                line: index + 1,
+                message: "The shader must start with a #version directive".to_string(),
+            });
+        }
+    }
+    let Some((version_index, version)) = lines.next() else {
+        return Err(PreprocessError::InvalidDirective {
+            file: root,
+            line: 1,
+            message: "Missing #version directive".to_string(),
+        });
+    };
+    preprocessor.code.push_str(version);
+    preprocessor.code.push('\n');
+    for (name, value) in defines {
+        preprocessor
+            .code
+            .push_str(&format!("#define {} {}\n", name, value));
+    }
+    preprocessor
+        .code
+        .push_str(&format!("#line {} 0\n", version_index + 2));
+
+    preprocessor.files.push(root.clone());
+    preprocessor.stack.push(canonical(&root));
+    preprocessor.expand_lines(&root, 0, lines)?;
+
+    Ok(ShaderSource {
+        code: preprocessor.code,
+        files: preprocessor.files,
+    })
+}
+
+#[derive(Default)]
+struct Preprocessor {
+    code: String,
+    files: Vec<PathBuf>,
+    /// Files being expanded, from the root to the current one
+    stack: Vec<PathBuf>,
+}
+
+impl Preprocessor {
+    fn expand_lines<'a>(
+        &mut self,
+        file: &Path,
+        file_index: usize,
+        lines: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<(), PreprocessError> {
+        for (index, line) in lines {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("#version") {
+                return Err(PreprocessError::InvalidDirective {
+                    file: file.to_path_buf(),
+                    line: index + 1,
+                    message: "#version is only allowed at the start of the root shader".to_string(),
+                });
+            }
+
+            match trimmed.strip_prefix("#include") {
+                Some(argument) => {
+                    let included = parse_include(argument).ok_or_else(|| {
+                        PreprocessError::InvalidDirective {
+                            file: file.to_path_buf(),
+                            line: index + 1,
+                            message: "Expected #include \"file\"".to_string(),
+                        }
+                    })?;
+                    let included = file.parent().unwrap_or(Path::new("")).join(included);
+                    self.include(&included)?;
+                    // back to the line following the include
+                    self.code
+                        .push_str(&format!("#line {} {}\n", index + 2, file_index));
+                }
+                None => {
+                    self.code.push_str(line);
+                    self.code.push('\n');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, file: &Path) -> Result<(), PreprocessError> {
+        let key = canonical(file);
+        if self.stack.contains(&key) {
+            return Err(PreprocessError::IncludeCycle(file.to_path_buf()));
+        }
+        if self.files.iter().any(|included| canonical(included) == key) {
+            return Ok(());
+        }
+
+        let contents = read(file)?;
+        let file_index = self.files.len();
+        self.files.push(file.to_path_buf());
+        self.stack.push(key);
+
+        self.code.push_str(&format!("#line 1 {}\n", file_index));
+        self.expand_lines(file, file_index, contents.lines().enumerate())?;
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+fn parse_include(argument: &str) -> Option<&str> {
+    argument
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|path| !path.is_empty())
+}
+
+fn read(file: &Path) -> Result<String, PreprocessError> {
+    fs::read_to_string(file).map_err(|err| PreprocessError::Io(file.to_path_buf(), err))
+}
+
+///
+/// Identifies a file whatever the path it was included with
+///
+fn canonical(file: &Path) -> PathBuf {
+    fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Writes the files into a new temporary directory and returns it
+    ///
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("basis_preprocess_{}_{}", name, std::process::id()));
+        for (path, contents) in files {
+            let path = directory.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        directory
+    }
+
+    fn preprocess_in(
+        directory: &Path,
+        defines: &[(String, String)],
+    ) -> Result<ShaderSource, PreprocessError> {
+        preprocess(directory.join("main.glsl").to_str().unwrap(), defines)
+    }
+
+    #[test]
+    fn it_should_expand_includes_relative_to_the_including_file() {
+        let directory = write_files(
+            "include",
+            &[
+                (
+                    "main.glsl",
+                    "#version 330 core\n#include \"lib/light.glsl\"\nvoid main() {}\n",
+                ),
+                (
+                    "lib/light.glsl",
+                    "#include \"common.glsl\"\nfloat light() { return PI; }\n",
+                ),
+                ("lib/common.glsl", "const float PI = 3.14;\n"),
+            ],
+        );
+
+        let source = preprocess_in(&directory, &[]).expect("This should work");
+
+        #[rustfmt::skip]
+        let expected = [
+            "#version 330 core",
+            "#line 2 0",
+            "#line 1 1",
+            "#line 1 2",
+            "const float PI = 3.14;",
+            "#line 2 1",
+            "float light() { return PI; }",
+            "#line 3 0",
+            "void main() {}",
+            "",
+        ].join("\n");
+        assert_eq!(source.code, expected);
+        assert_eq!(
+            source.files,
+            vec![
+                directory.join("main.glsl"),
+                directory.join("lib/light.glsl"),
+                directory.join("lib/common.glsl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_add_defines_after_the_version() {
+        let directory = write_files(
+            "define",
+            &[(
+                "main.glsl",
+                "// header\n#version 330 core\nvoid main() {}\n",
+            )],
+        );
+        let defines = vec![
+            ("PCF_SIZE".to_string(), "3".to_string()),
+            ("USE_FOG".to_string(), "".to_string()),
+        ];
+
+        let source = preprocess_in(&directory, &defines).expect("This should work");
+
+        assert_eq!(
+            source.code,
+            "#version 330 core\n#define PCF_SIZE 3\n#define USE_FOG \n#line 3 0\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn it_should_include_a_file_only_once() {
+        let directory = write_files(
+            "once",
+            &[
+                (
+                    "main.glsl",
+                    "#version 330 core\n#include \"a.glsl\"\n#include \"./a.glsl\"\n",
+                ),
+                ("a.glsl", "float a;\n"),
+            ],
+        );
+
+        let source = preprocess_in(&directory, &[]).expect("This should work");
+
+        assert_eq!(source.code.matches("float a;").count(), 1);
+        assert_eq!(source.files.len(), 2);
+    }
+
+    #[test]
+    fn it_should_fail_on_include_cycles() {
+        let directory = write_files(
+            "cycle",
+            &[
+                ("main.glsl", "#version 330 core\n#include \"a.glsl\"\n"),
+                ("a.glsl", "#include \"b.glsl\"\n"),
+                ("b.glsl", "#include \"a.glsl\"\n"),
+            ],
+        );
+
+        let result = preprocess_in(&directory, &[]);
+
+        assert!(
+            matches!(result, Err(PreprocessError::IncludeCycle(file)) if file.ends_with("a.glsl"))
+        );
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_directives() {
+        let directory = write_files(
+            "invalid",
+            &[
+                ("main.glsl", "#version 330 core\n\n#include <a.glsl>\n"),
+                ("missing_version.glsl", "void main() {}\n"),
+            ],
+        );
+
+        let result = preprocess_in(&directory, &[]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "{}:3: Expected #include \"file\"",
+                directory.join("main.glsl").display()
+            )
+        );
+
+        let result = preprocess(
+            directory.join("missing_version.glsl").to_str().unwrap(),
+            &[],
+        );
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("The shader must start with a #version directive"));
+    }
+
+    #[test]
+    fn it_should_fail_on_missing_include() {
+        let directory = write_files(
+            "missing",
+            &[("main.glsl", "#version 330 core\n#include \"nope.glsl\"\n")],
+        );
+
+        let result = preprocess_in(&directory, &[]);
+
+        assert!(matches!(result, Err(PreprocessError::Io(file, _)) if file.ends_with("nope.glsl")));
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::{
+    glw,
+    shader_preprocessor::{self, PreprocessError},
+};
+
+///
+/// One GLSL file of a program and the stage it is compiled for, `gl::VERTEX_SHADER`,
+/// `gl::FRAGMENT_SHADER`, ...
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderStage {
+    pub path: String,
+    pub r#type: gl::types::GLenum,
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Preprocess(PreprocessError),
+    Compile {
+        path: String,
+        /// Info log of the compiler
+        log: String,
+        /// Files behind the source string numbers of the messages
+        files: Vec<PathBuf>,
+        messages: Vec<glw::ShaderMessage>,
+    },
+    Link {
+        paths: Vec<String>,
+        /// Info log of the linker
+        log: String,
+    },
+}
+impl std::error::Error for ShaderError {}
+
+impl From<PreprocessError> for ShaderError {
+    fn from(err: PreprocessError) -> Self {
+        ShaderError::Preprocess(err)
+    }
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ShaderError::Preprocess(err) => write!(f, "{}", err),
+            ShaderError::Compile {
+                path,
+                log,
+                files,
+                messages,
+            } => {
+                write!(f, "Failed to compile '{}':", path)?;
+                if messages.is_empty() {
+                    return write!(f, "\n{}", log.trim_end());
+                }
+                // the source string numbers are replaced with the files they stand for
+                for message in messages {
+                    match files.get(message.source as usize) {
+                        Some(file) => {
+                            write!(f, "\n{}:{}: {}", file.display(), message.line, message.text)?
+                        }
+                        None => {
+                            write!(f, "\n{}:{}: {}", message.source, message.line, message.text)?
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ShaderError::Link { paths, log } => write!(
+                f,
+                "Failed to link '{}':\n{}",
+                paths.join("', '"),
+                log.trim_end()
+            ),
+        }
+    }
+}
+
+///
+/// Shader program tracked by the paths of its sources, it can be rebuilt when one of them
+/// changes. The sources go through the preprocessor, so `#include` and the `defines` are
+/// available to them.
+///
+#[derive(Debug)]
+pub struct ShaderProgram {
+    program: glw::Shader,
+    stages: Vec<ShaderStage>,
+    defines: Vec<(String, String)>,
+    /// Uniform blocks and their binding point, bound again after each reload
+    uniform_blocks: Vec<(String, gl::types::GLuint)>,
+    /// Every file the program was built from, included ones too, with their last modification
+    watched: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ShaderProgram {
+    pub fn new(vertex: &str, fragment: &str) -> Result<ShaderProgram, ShaderError> {
+        ShaderProgram::with_defines(vertex, fragment, Vec::new())
+    }
+
+    pub fn with_defines(
+        vertex: &str,
+        fragment: &str,
+        defines: Vec<(String, String)>,
+    ) -> Result<ShaderProgram, ShaderError> {
+        ShaderProgram::from_stages(
+            vec![
+                ShaderStage {
+                    path: vertex.to_string(),
+                    r#type: gl::VERTEX_SHADER,
+                },
+                ShaderStage {
+                    path: fragment.to_string(),
+                    r#type: gl::FRAGMENT_SHADER,
+                },
+            ],
+            defines,
+        )
+    }
+
+    pub fn from_stages(
+        stages: Vec<ShaderStage>,
+        defines: Vec<(String, String)>,
+    ) -> Result<ShaderProgram, ShaderError> {
+        let (program, files) = build(&stages, &defines)?;
+
+        Ok(ShaderProgram {
+            program,
+            stages,
+            defines,
+            uniform_blocks: Vec::new(),
+            watched: modification_times(files),
+        })
+    }
+
+    ///
+    /// Reads the uniform block `name` from the `glw::UniformBuffer` bound to `binding`
+    ///
+    pub fn with_uniform_block(mut self, name: &str, binding: gl::types::GLuint) -> Self {
+        self.program.uniform_block_binding(name, binding);
+        self.uniform_blocks.push((name.to_string(), binding));
+        self
+    }
+
+    pub fn stages(&self) -> &[ShaderStage] {
+        &self.stages
+    }
+
+    ///
+    /// Whether one of the source files changed since the program was last built
+    ///
+    pub fn has_changed(&self) -> bool {
+        self.watched
+            .iter()
+            .any(|(file, modified)| modification_time(file) != *modified)
+    }
+
+    ///
+    /// Rebuilds the program from its sources. On error the previous program is kept, and the
+    /// sources are not considered changed until they are modified again.
+    ///
+    pub fn reload(&mut self) -> Result<(), ShaderError> {
+        let files = self.watched.drain(..).map(|(file, _)| file).collect();
+        self.watched = modification_times(files);
+
+        let (program, files) = build(&self.stages, &self.defines)?;
+        self.program = program;
+        for (name, binding) in &self.uniform_blocks {
+            self.program.uniform_block_binding(name, *binding);
+        }
+        self.watched = modification_times(files);
+        Ok(())
+    }
+
+    ///
+    /// Rebuilds the program when its sources changed, returns whether the program was rebuilt
+    ///
+    pub fn reload_if_changed(&mut self) -> Result<bool, ShaderError> {
+        if !self.has_changed() {
+            return Ok(false);
+        }
+        self.reload().map(|_| true)
+    }
+}
+
+impl Deref for ShaderProgram {
+    type Target = glw::Shader;
+
+    fn deref(&self) -> &Self::Target {
+        &self.program
+    }
+}
+
+///
+/// Preprocesses, compiles and links the stages, returns the program and every file it was
+/// built from
+///
+fn build(
+    stages: &[ShaderStage],
+    defines: &[(String, String)],
+) -> Result<(glw::Shader, Vec<PathBuf>), ShaderError> {
+    let mut files = Vec::new();
+    let mut shader_files = Vec::new();
+
+    for stage in stages {
+        let source = shader_preprocessor::preprocess(&stage.path, defines)?;
+        let shader_file = glw::ShaderFile::new_from_source(&source.code, stage.r#type).map_err(
+            |err| match err {
+                glw::Error::Compile { log, messages, .. } => ShaderError::Compile {
+                    path: stage.path.clone(),
+                    log,
+                    files: source.files.clone(),
+                    messages,
+                },
+                err => ShaderError::Compile {
+                    path: stage.path.clone(),
+                    log: err.to_string(),
+                    files: source.files.clone(),
+                    messages: Vec::new(),
+                },
+            },
+        )?;
+        shader_files.push(shader_file);
+        files.extend(source.files);
+    }
+
+    let program = glw::Shader::default();
+    shader_files.iter().for_each(|shader_file| {
+        program.attach_shader(shader_file);
+    });
+    program.link().map_err(|err| ShaderError::Link {
+        paths: stages.iter().map(|stage| stage.path.clone()).collect(),
+        log: match err {
+            glw::Error::Link { log } => log,
+            err => err.to_string(),
+        },
+    })?;
+
+    Ok((program, files))
+}
+
+fn modification_time(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn modification_times(files: Vec<PathBuf>) -> Vec<(PathBuf, Option<SystemTime>)> {
+    files
+        .into_iter()
+        .map(|file| {
+            let modified = modification_time(&file);
+            (file, modified)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::graphics::headless::HeadlessContext;
+
+    const VERTEX: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0); }\n";
+    const FRAGMENT: &str = "#version 330 core\n#include \"color.glsl\"\nout vec4 FragColor;\nvoid main() { FragColor = COLOR; }\n";
+
+    fn write(directory: &Path, name: &str, contents: &str) -> String {
+        let path = directory.join(name);
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    ///
+    /// Moves the modification time forward, the file system may not see two writes in a row
+    ///
+    fn touch(path: &str, seconds: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_reload_changed_shaders_and_keep_the_previous_program_on_error() {
+        let Some(_context) = HeadlessContext::for_test(1, 1) else {
+            return;
+        };
+        let directory = std::env::temp_dir().join(format!("basis_reload_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let vertex = write(&directory, "vertex.glsl", VERTEX);
+        let fragment = write(&directory, "fragment.glsl", FRAGMENT);
+        let include = write(&directory, "color.glsl", "#define COLOR vec4(1.0)\n");
+
+        let mut program = ShaderProgram::new(&vertex, &fragment).expect("This should work");
+        let first = program.id();
+        assert!(!program.has_changed());
+
+        // an included file is watched too
+        write(
+            &directory,
+            "color.glsl",
+            "#define COLOR vec4(1.0)\nfloat broken = ;\n",
+        );
+        touch(&include, 10);
+        assert!(program.has_changed());
+        let err = program.reload_if_changed().unwrap_err();
+        assert!(matches!(err, ShaderError::Compile { .. }));
+        // the message points into the included file
+        assert!(
+            err.to_string().contains(&format!("{}:2: ", include)),
+            "{}",
+            err
+        );
+        assert_eq!(program.id(), first);
+        assert!(!program.has_changed());
+
+        write(&directory, "color.glsl", "#define COLOR vec4(0.5)\n");
+        touch(&include, 20);
+        assert_eq!(program.reload_if_changed().ok(), Some(true));
+        assert_ne!(program.id(), first);
+        assert_eq!(program.reload_if_changed().ok(), Some(false));
+    }
+
+    #[test]
+    fn it_should_list_active_uniforms_and_attributes() {
+        let Some(_context) = HeadlessContext::for_test(1, 1) else {
+            return;
+        };
+        let shader =
+            |name: &str| format!("{}/src/assets/shaders/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+        let program = ShaderProgram::new(
+            &shader("vertex_perspective_shader.glsl"),
+            &shader("fragment_perspective_shader.glsl"),
+        )
+        .expect("This should work");
+
+        let uniforms = program.active_uniforms();
+        let texture = uniforms
+            .iter()
+            .find(|uniform| uniform.name == "object_texture")
+            .expect("object_texture to be used");
+        assert_eq!(texture.r#type, gl::SAMPLER_2D);
+        assert_eq!(
+            program.get_uniform_location("object_texture").id(),
+            texture.location
+        );
+        // stored in the `Frame` uniform block, not in the default block
+        let view = uniforms
+            .iter()
+            .find(|uniform| uniform.name == "view")
+            .expect("view to be used");
+        assert_eq!(view.location, -1);
+
+        let attributes = program.active_attributes();
+        let model = attributes
+            .iter()
+            .find(|attribute| attribute.name == "aModel")
+            .expect("aModel to be used");
+        assert_eq!((model.r#type, model.location), (gl::FLOAT_MAT4, 5));
+        assert!(program.uniform_block_binding("Frame", 0));
+        assert!(!program.uniform_block_binding("Missing", 0));
+    }
+
+    #[test]
+    fn it_should_report_missing_sources() {
+        let Some(_context) = HeadlessContext::for_test(1, 1) else {
+            return;
+        };
+
+        let result = ShaderProgram::new("nope/vertex.glsl", "nope/fragment.glsl");
+
+        assert!(matches!(
+            result,
+            Err(ShaderError::Preprocess(PreprocessError::Io(..)))
+        ));
+    }
+}
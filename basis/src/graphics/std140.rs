@@ -0,0 +1,165 @@
+use crate::math::prelude::*;
+
+///
+/// Data uploaded to a uniform buffer, written field by field in the order of the GLSL block
+/// declared with `layout(std140)`
+///
+pub trait UniformBlock {
+    fn write_std140(&self, writer: &mut Std140Writer);
+}
+
+///
+/// Lays values out with the std140 rules, every value is aligned to its base alignment:
+///
+/// * `float`, `int` and `bool`: 4 bytes
+/// * `vec2`: 8 bytes
+/// * `vec3` and `vec4`: 16 bytes, a scalar can fill the 4 bytes left after a `vec3`
+/// * `mat4`: four `vec4` columns
+/// * the block itself is padded to 16 bytes
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Std140Writer {
+    bytes: Vec<u8>,
+}
+
+impl Std140Writer {
+    pub fn float(&mut self, value: f32) -> &mut Self {
+        self.align(4);
+        self.bytes.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    pub fn int(&mut self, value: i32) -> &mut Self {
+        self.align(4);
+        self.bytes.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.int(value as i32)
+    }
+
+    pub fn vec2(&mut self, x: f32, y: f32) -> &mut Self {
+        self.align(8);
+        self.float(x).float(y)
+    }
+
+    pub fn vec3(&mut self, value: &Vec3) -> &mut Self {
+        self.align(16);
+        self.float(value.x).float(value.y).float(value.z)
+    }
+
+    pub fn vec4(&mut self, value: &Vec4) -> &mut Self {
+        self.align(16);
+        self.float(value.x)
+            .float(value.y)
+            .float(value.z)
+            .float(value.w)
+    }
+
+    pub fn mat4(&mut self, value: &Mat4) -> &mut Self {
+        self.vec4(&value.c0)
+            .vec4(&value.c1)
+            .vec4(&value.c2)
+            .vec4(&value.c3)
+    }
+
+    ///
+    /// The bytes of the block, padded to a multiple of 16
+    ///
+    pub fn finish(mut self) -> Vec<u8> {
+        self.align(16);
+        self.bytes
+    }
+
+    fn align(&mut self, alignment: usize) {
+        let padding = (alignment - self.bytes.len() % alignment) % alignment;
+        self.bytes.resize(self.bytes.len() + padding, 0);
+    }
+}
+
+///
+/// Lays `block` out with the std140 rules
+///
+pub fn to_bytes(block: &impl UniformBlock) -> Vec<u8> {
+    let mut writer = Std140Writer::default();
+    block.write_std140(&mut writer);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floats(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks(4)
+            .map(|chunk| f32::from_ne_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn it_should_pack_a_scalar_after_a_vec3() {
+        let mut writer = Std140Writer::default();
+        writer.vec3(&Vec3::new(1.0, 2.0, 3.0)).float(4.0);
+
+        let bytes = writer.finish();
+
+        assert_eq!(floats(&bytes), vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn it_should_align_vectors() {
+        let mut writer = Std140Writer::default();
+        writer
+            .float(1.0)
+            .vec2(2.0, 3.0)
+            .float(4.0)
+            .vec3(&Vec3::splat(5.0))
+            .vec4(&Vec4::splat(6.0));
+
+        let bytes = writer.finish();
+
+        #[rustfmt::skip]
+        assert_eq!(floats(&bytes), vec![
+            1.0, 0.0, 2.0, 3.0,
+            4.0, 0.0, 0.0, 0.0,
+            5.0, 5.0, 5.0, 0.0,
+            6.0, 6.0, 6.0, 6.0,
+        ]);
+    }
+
+    #[test]
+    fn it_should_write_matrices_column_by_column() {
+        struct Block {
+            value: f32,
+            matrix: Mat4,
+            enabled: bool,
+        }
+        impl UniformBlock for Block {
+            fn write_std140(&self, writer: &mut Std140Writer) {
+                writer
+                    .float(self.value)
+                    .mat4(&self.matrix)
+                    .bool(self.enabled);
+            }
+        }
+
+        let bytes = to_bytes(&Block {
+            value: 2.0,
+            matrix: Mat4::identity(),
+            enabled: true,
+        });
+
+        assert_eq!(bytes.len(), 96);
+        #[rustfmt::skip]
+        assert_eq!(floats(&bytes[..80]), vec![
+            2.0, 0.0, 0.0, 0.0,
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        assert_eq!(i32::from_ne_bytes(bytes[80..84].try_into().unwrap()), 1);
+    }
+}
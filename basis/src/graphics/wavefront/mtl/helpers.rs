@@ -1,6 +1,9 @@
 use std::{iter::Peekable, slice::Iter, vec::IntoIter};
 
-use super::structs::{DissolveFactor, IlluminationModel, Material, ParseError, Rgb};
+use super::structs::{
+    DissolveFactor, IlluminationModel, ImageChannel, Material, ParseError, Rgb, TextureMap,
+    TextureMapOptions, Uvw,
+};
 
 pub fn parse_material(
     name: &str,
@@ -60,6 +63,35 @@ pub fn parse_material(
             "Ni" => {
                 material.optical_density = parse_material_optical_density(&mut tokens, line_n)?;
             }
+            "map_Ka" => {
+                material.ambient_texture_map =
+                    Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "map_Kd" => {
+                material.diffuse_texture_map =
+                    Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "map_Ks" => {
+                material.specular_texture_map =
+                    Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "map_Ns" => {
+                material.specular_highlight_texture_map =
+                    Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "map_d" => {
+                material.dissolve_texture_map =
+                    Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "decal" => {
+                material.decal_texture_map = Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "disp" => {
+                material.displacement_map = Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
+            "map_bump" | "bump" => {
+                material.bump_map = Some(parse_material_texture_map(&mut tokens, line_n)?);
+            }
             "#" => {
                 continue;
             }
@@ -182,3 +214,129 @@ fn parse_material_optical_density(
 
     Ok(optical_density)
 }
+
+fn parse_on_off(token: Option<&str>, option: &str, line_n: usize) -> Result<bool, ParseError> {
+    match token {
+        Some("on") => Ok(true),
+        Some("off") => Ok(false),
+        _ => Err(ParseError::InvalidToken(
+            line_n,
+            format!("Invalid '{option}' value, expected 'on' or 'off'"),
+        )),
+    }
+}
+
+fn parse_texture_option_f32(
+    token: Option<&str>,
+    option: &str,
+    line_n: usize,
+) -> Result<f32, ParseError> {
+    token
+        .and_then(|token| token.parse::<f32>().ok())
+        .ok_or(ParseError::InvalidToken(
+            line_n,
+            format!("Invalid '{option}' value"),
+        ))
+}
+
+///
+/// "u" is required, "v" and "w" keep their default value when they are not given
+///
+fn parse_texture_option_uvw(
+    tokens: &mut Peekable<&mut IntoIter<&str>>,
+    default: Uvw,
+    option: &str,
+    line_n: usize,
+) -> Result<Uvw, ParseError> {
+    let u = parse_texture_option_f32(tokens.next(), option, line_n)?;
+    let v = tokens
+        .next_if(|token| token.parse::<f32>().is_ok())
+        .map_or(default.v, |token| token.parse::<f32>().unwrap());
+    let w = tokens
+        .next_if(|token| token.parse::<f32>().is_ok())
+        .map_or(default.w, |token| token.parse::<f32>().unwrap());
+
+    Ok(Uvw { u, v, w })
+}
+
+///
+/// Texture map statements are made of options starting with "-" followed by the file name,
+/// for example: "map_Kd -s 2 2 -clamp on textures/wall.tga"
+///
+fn parse_material_texture_map(
+    tokens: &mut IntoIter<&str>,
+    line_n: usize,
+) -> Result<TextureMap, ParseError> {
+    let mut options = TextureMapOptions::default();
+    let mut tokens = tokens.peekable();
+
+    while let Some(option) = tokens.next_if(|token| token.starts_with('-')) {
+        match option {
+            "-blendu" => options.blend_u = parse_on_off(tokens.next(), option, line_n)?,
+            "-blendv" => options.blend_v = parse_on_off(tokens.next(), option, line_n)?,
+            "-cc" => options.color_correction = parse_on_off(tokens.next(), option, line_n)?,
+            "-clamp" => options.clamp = parse_on_off(tokens.next(), option, line_n)?,
+            "-bm" => {
+                options.bump_multiplier = parse_texture_option_f32(tokens.next(), option, line_n)?
+            }
+            "-boost" => options.boost = parse_texture_option_f32(tokens.next(), option, line_n)?,
+            "-mm" => {
+                options.base = parse_texture_option_f32(tokens.next(), option, line_n)?;
+                options.gain = parse_texture_option_f32(tokens.next(), option, line_n)?;
+            }
+            "-o" => {
+                options.offset =
+                    parse_texture_option_uvw(&mut tokens, options.offset.clone(), option, line_n)?;
+            }
+            "-s" => {
+                options.scale =
+                    parse_texture_option_uvw(&mut tokens, options.scale.clone(), option, line_n)?;
+            }
+            "-t" => {
+                options.turbulence = parse_texture_option_uvw(
+                    &mut tokens,
+                    options.turbulence.clone(),
+                    option,
+                    line_n,
+                )?;
+            }
+            "-texres" => {
+                let resolution = tokens
+                    .next()
+                    .and_then(|token| token.parse::<u32>().ok())
+                    .ok_or(ParseError::InvalidToken(
+                        line_n,
+                        "Invalid '-texres' value".to_string(),
+                    ))?;
+                options.resolution = Some(resolution);
+            }
+            "-imfchan" => {
+                let channel = tokens
+                    .next()
+                    .and_then(|token| token.parse::<ImageChannel>().ok())
+                    .ok_or(ParseError::InvalidToken(
+                        line_n,
+                        "Invalid '-imfchan' value, expected r, g, b, m, l or z".to_string(),
+                    ))?;
+                options.channel = Some(channel);
+            }
+            unknown => {
+                return Err(ParseError::InvalidToken(
+                    line_n,
+                    format!("Unknown texture map option: '{unknown}'"),
+                ))
+            }
+        }
+    }
+
+    // file names may contain spaces
+    let path = tokens.collect::<Vec<&str>>().join(" ");
+    if path.is_empty() {
+        return Err(ParseError::InvalidToken(
+            line_n,
+            "Missing texture map file name".to_string(),
+        ));
+    }
+
+    Ok(TextureMap { path, options })
+}
@@ -2,10 +2,13 @@ mod helpers;
 mod parse_mtl;
 mod structs;
 
+use std::path::Path;
+
 use parse_mtl::parse_mtl;
-pub use structs::Material;
 use structs::ParseError;
-pub use structs::MTL;
+pub use structs::{ImageChannel, Material, TextureMap, TextureMapOptions, Uvw, MTL};
+
+use crate::graphics::image::{self, Image, LoadImageError};
 
 #[derive(Debug)]
 pub enum LoadMTLError {
@@ -35,20 +38,74 @@ impl std::fmt::Display for LoadMTLError {
     }
 }
 
+impl TextureMap {
+    pub fn load(&self) -> Result<Image, LoadImageError> {
+        image::load(self.path.as_str())
+    }
+}
+
+///
+/// Loads the MTL file, texture map paths are resolved relative to the MTL file location
+///
 pub fn load(file_path: &str) -> Result<MTL, LoadMTLError> {
     let file_content = std::fs::read_to_string(file_path)?;
 
-    let obj = parse_mtl(file_content.as_str())?;
+    let mut mtl = parse_mtl(file_content.as_str())?;
+
+    let directory = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    for material in mtl.values_mut() {
+        for texture_map in material.texture_maps_mut() {
+            texture_map.path = directory
+                .join(texture_map.path.as_str())
+                .to_string_lossy()
+                .to_string();
+        }
+    }
 
-    Ok(obj)
+    Ok(mtl)
 }
 
 pub fn load_files(file_path: Vec<String>) -> Result<Vec<MTL>, LoadMTLError> {
     let mut mtl_files = Vec::new();
 
     for file in file_path {
-        mtl_files.push(load(file.as_str())?);
+        let mtl = load(file.as_str()).map_err(|err| match err {
+            // keep track of which library could not be read
+            LoadMTLError::Io(err) => LoadMTLError::Io(std::io::Error::new(
+                err.kind(),
+                format!("{}: {}", file, err),
+            )),
+            err => err,
+        })?;
+        mtl_files.push(mtl);
     }
 
     Ok(mtl_files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_resolve_texture_maps_relative_to_the_file() {
+        let mtl = load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/models/cube_textured/cube.mtl"
+        ))
+        .expect("This should work");
+        let texture_map = mtl
+            .get("Material")
+            .and_then(|material| material.diffuse_texture_map.as_ref())
+            .expect("The material should have a diffuse texture map");
+
+        assert!(texture_map
+            .path
+            .ends_with("src/assets/models/cube_textured/cube.tga"));
+
+        let texture = texture_map.load().expect("This should work");
+
+        assert_eq!(texture.width, 32);
+        assert_eq!(texture.height, 32);
+    }
+}
@@ -84,7 +84,7 @@ pub fn parse_mtl(data: &str) -> Result<MTL, ParseError> {
 
 #[cfg(test)]
 mod tests {
-    use crate::graphics::wavefront::mtl::structs::IlluminationModel;
+    use crate::graphics::wavefront::mtl::structs::{IlluminationModel, ImageChannel, Uvw};
 
     use super::*;
 
@@ -469,4 +469,79 @@ mod tests {
         assert!(!material.dissolve_factor.halo);
         assert_eq!(material.illumination_model, IlluminationModel::HighlightOn);
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_resolve_texture_maps() {
+        let file = "
+            newmtl Wall
+            Kd 0.8 0.8 0.8
+            map_Ka ambient.tga
+            map_Kd textures/wall diffuse.png
+            map_Ks specular.bmp
+            map_d -imfchan m dissolve.ppm
+            bump -bm 0.5 bump.pgm
+";
+
+        let result = parse_mtl(file).expect("This should work");
+        let wall = result.get("Wall").unwrap();
+
+        assert_eq!(wall.ambient_texture_map.as_ref().unwrap().path, "ambient.tga");
+        assert_eq!(wall.diffuse_texture_map.as_ref().unwrap().path, "textures/wall diffuse.png");
+        assert_eq!(wall.specular_texture_map.as_ref().unwrap().path, "specular.bmp");
+        assert_eq!(wall.dissolve_texture_map.as_ref().unwrap().path, "dissolve.ppm");
+        assert_eq!(wall.dissolve_texture_map.as_ref().unwrap().options.channel, Some(ImageChannel::Matte));
+        assert_eq!(wall.bump_map.as_ref().unwrap().path, "bump.pgm");
+        assert_eq!(wall.bump_map.as_ref().unwrap().options.bump_multiplier, 0.5);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_resolve_texture_map_options() {
+        let file = "
+            newmtl Wall
+            map_Kd -s 2 3 -o -0.5 -clamp on -blendu off -mm 0.1 0.9 -t 1 1 1 -texres 256 wall.tga
+";
+
+        let result = parse_mtl(file).expect("This should work");
+        let options = &result.get("Wall").unwrap().diffuse_texture_map.as_ref().unwrap().options;
+
+        assert_eq!(options.scale, Uvw { u: 2.0, v: 3.0, w: 1.0 });
+        assert_eq!(options.offset, Uvw { u: -0.5, v: 0.0, w: 0.0 });
+        assert_eq!(options.turbulence, Uvw { u: 1.0, v: 1.0, w: 1.0 });
+        assert!(options.clamp);
+        assert!(!options.blend_u);
+        assert!(options.blend_v);
+        assert_eq!(options.base, 0.1);
+        assert_eq!(options.gain, 0.9);
+        assert_eq!(options.resolution, Some(256));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_not_resolve_texture_map_without_file_name() {
+        let file = "
+            newmtl Wall
+            map_Kd -clamp on
+";
+
+        let result = parse_mtl(file);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Missing texture map file name"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_not_resolve_unknown_texture_map_option() {
+        let file = "
+            newmtl Wall
+            map_Kd -unknown 1 wall.tga
+";
+
+        let result = parse_mtl(file);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Unknown texture map option: '-unknown'"));
+    }
 }
@@ -69,12 +69,126 @@ impl std::str::FromStr for IlluminationModel {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct DissolveFactor {
     pub factor: f32,
     pub halo: bool,
 }
 
+impl Default for DissolveFactor {
+    /// A material without "d" is fully opaque
+    fn default() -> Self {
+        DissolveFactor {
+            factor: 1.0,
+            halo: false,
+        }
+    }
+}
+
+/// "u v w" values used by the texture map options, "v" and "w" are optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uvw {
+    pub u: f32,
+    pub v: f32,
+    pub w: f32,
+}
+
+/// Channel of the image used to create a scalar or bump texture ("-imfchan")
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageChannel {
+    Red,
+    Green,
+    Blue,
+    Matte,
+    Luminance,
+    Depth,
+}
+
+impl std::str::FromStr for ImageChannel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "r" => Ok(ImageChannel::Red),
+            "g" => Ok(ImageChannel::Green),
+            "b" => Ok(ImageChannel::Blue),
+            "m" => Ok(ImageChannel::Matte),
+            "l" => Ok(ImageChannel::Luminance),
+            "z" => Ok(ImageChannel::Depth),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureMapOptions {
+    /// "-blendu on | off"
+    pub blend_u: bool,
+    /// "-blendv on | off"
+    pub blend_v: bool,
+    /// "-bm mult", only used by bump maps
+    pub bump_multiplier: f32,
+    /// "-boost value"
+    pub boost: f32,
+    /// "-cc on | off"
+    pub color_correction: bool,
+    /// "-clamp on | off", clamps the texture coordinates to the 0 to 1 range instead of repeating
+    pub clamp: bool,
+    /// "-imfchan r | g | b | m | l | z"
+    pub channel: Option<ImageChannel>,
+    /// "-mm base gain"
+    pub base: f32,
+    pub gain: f32,
+    /// "-o u v w"
+    pub offset: Uvw,
+    /// "-s u v w"
+    pub scale: Uvw,
+    /// "-t u v w"
+    pub turbulence: Uvw,
+    /// "-texres value"
+    pub resolution: Option<u32>,
+}
+
+impl Default for TextureMapOptions {
+    fn default() -> Self {
+        Self {
+            blend_u: true,
+            blend_v: true,
+            bump_multiplier: 1.0,
+            boost: 0.0,
+            color_correction: false,
+            clamp: false,
+            channel: None,
+            base: 0.0,
+            gain: 1.0,
+            offset: Uvw {
+                u: 0.0,
+                v: 0.0,
+                w: 0.0,
+            },
+            scale: Uvw {
+                u: 1.0,
+                v: 1.0,
+                w: 1.0,
+            },
+            turbulence: Uvw {
+                u: 0.0,
+                v: 0.0,
+                w: 0.0,
+            },
+            resolution: None,
+        }
+    }
+}
+
+/// Image used by a "map_*" statement. The path is relative to the MTL file until it is loaded
+/// through `mtl::load`, which resolves it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextureMap {
+    pub path: String,
+    pub options: TextureMapOptions,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Material {
     pub name: String,
@@ -87,6 +201,32 @@ pub struct Material {
     pub specular_highlight_exponent: f32,
     pub sharpness: f32,
     pub optical_density: f32,
+
+    pub ambient_texture_map: Option<TextureMap>,
+    pub diffuse_texture_map: Option<TextureMap>,
+    pub specular_texture_map: Option<TextureMap>,
+    pub specular_highlight_texture_map: Option<TextureMap>,
+    pub dissolve_texture_map: Option<TextureMap>,
+    pub decal_texture_map: Option<TextureMap>,
+    pub displacement_map: Option<TextureMap>,
+    pub bump_map: Option<TextureMap>,
+}
+
+impl Material {
+    pub fn texture_maps_mut(&mut self) -> impl Iterator<Item = &mut TextureMap> {
+        [
+            &mut self.ambient_texture_map,
+            &mut self.diffuse_texture_map,
+            &mut self.specular_texture_map,
+            &mut self.specular_highlight_texture_map,
+            &mut self.dissolve_texture_map,
+            &mut self.decal_texture_map,
+            &mut self.displacement_map,
+            &mut self.bump_map,
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
 
 /*
@@ -70,27 +70,11 @@ pub fn parse_vertice_texture(
     }
 }
 
-fn parse_usemtl(line: Option<&str>) -> Option<String> {
-    let mut tokens = line?
-        .split(" ")
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<&str>>()
-        .into_iter();
-
-    if tokens.next() == Some("usemtl") {
-        Some(tokens.next().unwrap().to_string().clone())
-    } else {
-        None
-    }
-}
-
 pub fn parse_face(
     tokens: &mut IntoIter<&str>,
-    previous_line: Option<&str>,
+    material_name: Option<String>,
     line_n: usize,
 ) -> Result<Face, ParseError> {
-    let material_name = parse_usemtl(previous_line);
     let mut face = Face::partial_new(Vec::new(), material_name);
     let mut is_tripplets_format = false;
     let mut is_twins_format = false;
@@ -127,6 +111,20 @@ pub fn parse_face(
     Ok(face)
 }
 
+pub fn parse_usemtl(tokens: &mut IntoIter<&str>, line_n: usize) -> Result<String, ParseError> {
+    match (tokens.next(), tokens.next()) {
+        (Some(name), None) => Ok(name.to_string()),
+        (None, _) => Err(ParseError::InvalidFaceMaterial(
+            line_n,
+            "Missing material name".to_string(),
+        )),
+        (Some(_), Some(_)) => Err(ParseError::InvalidFaceMaterial(
+            line_n,
+            "You can only specify one material".to_string(),
+        )),
+    }
+}
+
 pub fn parse_smoothing_group(
     tokens: &mut IntoIter<&str>,
     line_n: usize,
@@ -2,11 +2,16 @@ mod helpers;
 mod parse_obj;
 mod structs;
 
+use std::path::Path;
+
 use structs::ParseError;
 
+use crate::graphics::image::LoadImageError;
 use crate::graphics::wavefront;
 use crate::graphics::wavefront::obj::parse_obj::parse_obj;
 
+pub use structs::Face;
+pub use structs::MaterialGroup;
 pub use structs::VertexDataReference;
 pub use structs::OBJ;
 
@@ -15,6 +20,8 @@ pub enum LoadOBJError {
     Io(std::io::Error),
     Parse(ParseError),
     Material(wavefront::mtl::LoadMTLError),
+    MissingMaterial(String),
+    Texture(LoadImageError),
 }
 impl std::error::Error for LoadOBJError {}
 
@@ -43,35 +50,160 @@ impl From<wavefront::mtl::LoadMTLError> for LoadOBJError {
     }
 }
 
+impl From<LoadImageError> for LoadOBJError {
+    fn from(err: LoadImageError) -> Self {
+        LoadOBJError::Texture(err)
+    }
+}
+
 impl std::fmt::Display for LoadOBJError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             LoadOBJError::Io(err) => write!(f, "IO error: {}", err),
             LoadOBJError::Parse(err) => write!(f, "{}", err),
             LoadOBJError::Material(err) => write!(f, "{}", err),
+            LoadOBJError::MissingMaterial(name) => {
+                write!(f, "Material '{}' not found in any material library", name)
+            }
+            LoadOBJError::Texture(err) => write!(f, "{}", err),
         }
     }
 }
 
+///
+/// Loads the OBJ file along with the material libraries it references, which are resolved
+/// relative to the OBJ file location.
+///
 pub fn load(file_path: &str) -> Result<OBJ, LoadOBJError> {
     let file_content = std::fs::read_to_string(file_path)?;
 
-    let obj = parse_obj(file_content)?;
+    let mut obj = parse_obj(file_content)?;
+
+    let directory = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    let mtls_paths = obj
+        .mtls_identifiers
+        .iter()
+        .map(|name| directory.join(name).to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    let mtls = wavefront::mtl::load_files(mtls_paths)?;
+
+    obj.load_mtls(mtls)?.load_textures()?;
+
+    Ok(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_path(path: &str) -> String {
+        format!("{}/src/assets/models/{}", env!("CARGO_MANIFEST_DIR"), path)
+    }
+
+    #[test]
+    fn it_should_load_42_materials() {
+        let obj = load(model_path("42/42.obj").as_str()).expect("This should work");
 
-    // let mtls_paths = obj
-    //     .mtls_identifiers
-    //     .iter()
-    //     .map(|name| {
-    //         let mut path = file_path.split('/').collect::<Vec<&str>>();
-    //         path.pop();
-    //         path.push(name.as_str());
-    //         path.join("/")
-    //     })
-    //     .collect::<Vec<String>>();
+        assert_eq!(obj.mtls.len(), 1);
+        assert!(obj.faces.iter().all(|face| {
+            face.material_name == Some("Material".to_string())
+                && face
+                    .material
+                    .as_ref()
+                    .is_some_and(|material| material.diffuse_reflectivity.r == 0.64)
+        }));
+    }
+
+    #[test]
+    fn it_should_load_teapot2_materials() {
+        let obj = load(model_path("teapot2/teapot2.obj").as_str()).expect("This should work");
+        let material = obj.faces[0].material.as_ref().expect("This should work");
+
+        assert_eq!(material.name, "None");
+        assert_eq!(material.diffuse_reflectivity.r, 0.8);
+        assert!(obj.faces.iter().all(|face| face.material.is_some()));
+    }
+
+    #[test]
+    fn it_should_load_texture_from_material() {
+        let obj = load(model_path("cube_textured/cube.obj").as_str()).expect("This should work");
+        let texture = obj
+            .textures
+            .get("Material")
+            .expect("The texture should be loaded");
+
+        assert_eq!((texture.width, texture.height), (32, 32));
+    }
 
-    // let mtls = wavefront::mtl::load_files(mtls_paths)?;
+    #[test]
+    fn it_should_group_faces_by_material() {
+        let mut obj = parse_obj(
+            "
+            v 0.0 0.0 0.0
+            f 1 1 1
+            usemtl red
+            f 1 1 1
+            usemtl blue
+            f 1 1 1
+            usemtl red
+            f 1 1 1
+"
+            .to_string(),
+        )
+        .expect("This should work");
+        let red = wavefront::mtl::Material {
+            name: "red".to_string(),
+            ..Default::default()
+        };
+        let mut blue = wavefront::mtl::Material {
+            name: "blue".to_string(),
+            ..Default::default()
+        };
+        blue.dissolve_factor.factor = 0.5;
+        let mtl = wavefront::mtl::MTL::from([("red".to_string(), red), ("blue".to_string(), blue)]);
+        obj.load_mtls(vec![mtl]).expect("This should work");
 
-    // Ok(obj.load_mtls(mtls).clone())
+        let groups = obj.get_material_groups();
+        let names = groups
+            .iter()
+            .map(|group| group.material.map(|material| material.name.as_str()))
+            .collect::<Vec<Option<&str>>>();
+        let sizes = groups
+            .iter()
+            .map(|group| group.faces.len())
+            .collect::<Vec<usize>>();
 
-    Ok(obj.clone())
+        assert_eq!(names, vec![None, Some("red"), Some("blue")]);
+        assert_eq!(sizes, vec![1, 2, 1]);
+        assert_eq!(groups[2].material.unwrap().dissolve_factor.factor, 0.5);
+        assert_eq!(groups[1].material.unwrap().dissolve_factor.factor, 1.0);
+    }
+
+    #[test]
+    fn it_should_fail_to_load_missing_material() {
+        let obj = parse_obj(
+            "
+            v 0.0 0.0 0.0
+            usemtl missing
+            f 1 1 1
+"
+            .to_string(),
+        )
+        .expect("This should work")
+        .load_mtls(Vec::new())
+        .map(|_| ());
+
+        assert!(obj
+            .unwrap_err()
+            .to_string()
+            .contains("Material 'missing' not found in any material library"));
+    }
+
+    #[test]
+    fn it_should_fail_to_load_missing_material_library() {
+        let result = load(model_path("airboat.obj").as_str());
+
+        assert!(result.unwrap_err().to_string().contains("vp.mtl"));
+    }
 }
@@ -10,7 +10,7 @@ pub fn parse_obj(data: String) -> Result<OBJ, ParseError> {
         return Err(ParseError::EmptyFile(0, "Object file is empty".to_string()));
     }
 
-    let mut previous_line = Option::None;
+    let mut material_name: Option<String> = None;
     let mut current_line: usize = 1;
     let mut smoothing_group: usize = 0;
     let mut face_id: usize = 0;
@@ -91,7 +91,8 @@ pub fn parse_obj(data: String) -> Result<OBJ, ParseError> {
             }
             "f" => {
                 // Parse face
-                let mut result = helpers::parse_face(&mut tokens, previous_line, current_line)?;
+                let mut result =
+                    helpers::parse_face(&mut tokens, material_name.clone(), current_line)?;
                 if smoothing_group != 0 {
                     result.smoothing_group = Some(smoothing_group);
                 }
@@ -189,23 +190,14 @@ pub fn parse_obj(data: String) -> Result<OBJ, ParseError> {
                 todo("Implement lod")
             }
             "usemtl" => {
-                // Skip... This is done during the face parsing
-                if tokens.len() > 2 {
-                    return Err(ParseError::InvalidFaceMaterial(
-                        current_line,
-                        "You can only specify one material".to_string(),
-                    ));
-                }
+                // Parse material name, used by every face until the next "usemtl"
+                material_name = Some(helpers::parse_usemtl(&mut tokens, current_line)?);
                 Ok(())
             }
             "mtllib" => {
                 // Parse material library
-                let mtllib = tokens
-                    .clone()
-                    .filter(|s| s != &"mtllib")
-                    .map(|s| s.to_string())
-                    .collect();
-                obj.mtls_identifiers = mtllib;
+                obj.mtls_identifiers
+                    .extend(tokens.by_ref().map(|s| s.to_string()));
                 Ok(())
             }
             "shadow_obj" => {
@@ -240,7 +232,6 @@ pub fn parse_obj(data: String) -> Result<OBJ, ParseError> {
             }
         }?;
         current_line += 1;
-        previous_line = Some(line);
     }
 
     if obj.vertices.is_empty() {
@@ -513,8 +504,8 @@ mod tests {
         assert_eq!(result.faces.len(), 6);
         assert_eq!(result.faces[0].material_name, Some("4bed15".to_string()));
         assert_eq!(result.faces[1].material_name, Some("2daec2".to_string()));
-        assert_eq!(result.faces[2].material_name, None);
-        assert_eq!(result.faces[3].material_name, None);
+        assert_eq!(result.faces[2].material_name, Some("2daec2".to_string()));
+        assert_eq!(result.faces[3].material_name, Some("2daec2".to_string()));
         assert_eq!(result.faces[4].material_name, Some("4602e3".to_string()));
         assert_eq!(result.faces[5].material_name, Some("c41dde".to_string()));
     }
@@ -556,4 +547,61 @@ mod tests {
         assert_eq!(result.faces[4].smoothing_group, Some(2));
         assert_eq!(result.faces[4].smoothing_group, Some(2));
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_store_face_without_material_before_usemtl() {
+        let file = "
+            v 0.000000 2.000000 2.000000
+            v 0.000000 0.000000 2.000000
+            v 2.000000 0.000000 2.000000
+            f 1 2 3
+            usemtl red
+            f 1 2 3
+";
+
+        let result = parse_obj(file.to_string()).expect("This should work");
+
+        assert_eq!(result.faces[0].material_name, None);
+        assert_eq!(result.faces[1].material_name, Some("red".to_string()));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_fail_to_store_usemtl_without_exactly_one_material() {
+        let missing = "
+            v 0.000000 2.000000 2.000000
+            usemtl
+            f 1 1 1
+";
+        let multiple = "
+            v 0.000000 2.000000 2.000000
+            usemtl red blue
+            f 1 1 1
+";
+
+        let missing = parse_obj(missing.to_string());
+        let multiple = parse_obj(multiple.to_string());
+
+        assert!(missing.unwrap_err().to_string().contains("Missing material name"));
+        assert!(multiple.unwrap_err().to_string().contains("You can only specify one material"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn it_should_store_every_mtllib() {
+        let file = "
+            mtllib a.mtl
+            v 0.000000 2.000000 2.000000
+            mtllib b.mtl c.mtl
+            f 1 1 1
+";
+
+        let result = parse_obj(file.to_string()).expect("This should work");
+
+        assert_eq!(
+            result.mtls_identifiers,
+            vec!["a.mtl".to_string(), "b.mtl".to_string(), "c.mtl".to_string()]
+        );
+    }
 }
@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+
 use crate::{
     graphics::{
         aabb::AABB,
+        image::{Image, LoadImageError},
         wavefront::{
             self,
             mtl::{Material, MTL},
+            obj::LoadOBJError,
         },
     },
     math::prelude::*,
@@ -183,6 +187,16 @@ impl PartialEq for VertexDataReference {
     }
 }
 
+///
+/// Faces sharing the same material, faces without a material are grouped together with
+/// `material` set to `None`
+///
+#[derive(Debug, Clone)]
+pub struct MaterialGroup<'a> {
+    pub material: Option<&'a Material>,
+    pub faces: Vec<&'a Face>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Face {
     pub id: usize,
@@ -251,7 +265,10 @@ pub struct OBJ {
     //
     // Texture
     //
-    pub texture: (u32, u32, Vec<u8>),
+    /// Used instead of the material textures when set
+    pub texture: Option<Image>,
+    /// Diffuse texture of each material, by material name
+    pub textures: HashMap<String, Image>,
 }
 
 impl OBJ {
@@ -260,6 +277,13 @@ impl OBJ {
     }
 
     pub fn get_raw_vertices(&self, rgb: Vec3) -> Vec<f32> {
+        self.get_raw_vertices_of(&self.faces.iter().collect::<Vec<&Face>>(), rgb)
+    }
+
+    ///
+    /// Same as `get_raw_vertices` but only for the given faces, used to build each submesh
+    ///
+    pub fn get_raw_vertices_of(&self, faces: &[&Face], rgb: Vec3) -> Vec<f32> {
         let mut vertices_texture = self.vertices_texture.clone();
 
         let aabb = AABB::from(&self.vertices);
@@ -290,9 +314,9 @@ impl OBJ {
             }
         }
 
-        self.faces.iter().fold(
-            Vec::with_capacity(self.faces.len() * 12),
-            |mut acc, face| {
+        faces
+            .iter()
+            .fold(Vec::with_capacity(faces.len() * 12), |mut acc, face| {
                 for reference in &face.vertex_references {
                     let rvt = if self.vertices_texture.is_empty() {
                         // if the texture was generated, we use the vertice reference
@@ -332,8 +356,7 @@ impl OBJ {
                 }
 
                 acc
-            },
-        )
+            })
     }
 
     pub fn get_raw_indices(&self) -> Vec<u32> {
@@ -345,23 +368,68 @@ impl OBJ {
         })
     }
 
-    pub fn load_mtls(&mut self, mtls: Vec<MTL>) -> &Self {
+    ///
+    /// Resolves the material of every face that uses one, searching the libraries in order
+    ///
+    pub fn load_mtls(&mut self, mtls: Vec<MTL>) -> Result<&mut Self, LoadOBJError> {
         self.mtls = mtls;
 
         for face in self.faces.iter_mut() {
-            if face.material_name.is_none() {
-                continue;
+            if let Some(name) = face.material_name.as_ref() {
+                let material = self
+                    .mtls
+                    .iter()
+                    .find_map(|mtl| mtl.get(name))
+                    .ok_or(LoadOBJError::MissingMaterial(name.clone()))?;
+                face.set_material(Some(material.clone()));
             }
-            if let Some(name) = face.material_name.clone() {
-                for material in self.mtls.iter() {
-                    face.set_material(material.get(&name).cloned());
+        }
+        Ok(self)
+    }
+
+    ///
+    /// Loads the diffuse texture map of every material used by a face
+    ///
+    pub fn load_textures(&mut self) -> Result<&mut Self, LoadImageError> {
+        let mut textures = HashMap::new();
+
+        for material in self.faces.iter().filter_map(|face| face.material.as_ref()) {
+            if let Some(texture_map) = material.diffuse_texture_map.as_ref() {
+                if !textures.contains_key(&material.name) {
+                    textures.insert(material.name.clone(), texture_map.load()?);
                 }
             }
         }
-        self
+
+        self.textures = textures;
+        Ok(self)
+    }
+
+    ///
+    /// Splits the faces by material, keeping the order in which each material was first used
+    ///
+    pub fn get_material_groups(&self) -> Vec<MaterialGroup<'_>> {
+        let mut groups: Vec<MaterialGroup> = Vec::new();
+
+        for face in &self.faces {
+            let name = face.material.as_ref().map(|material| &material.name);
+
+            match groups
+                .iter_mut()
+                .find(|group| group.material.map(|material| &material.name) == name)
+            {
+                Some(group) => group.faces.push(face),
+                None => groups.push(MaterialGroup {
+                    material: face.material.as_ref(),
+                    faces: vec![face],
+                }),
+            }
+        }
+
+        groups
     }
 
-    pub fn get_smoothing_group_by_id(&self, id: usize) -> SmoothingGroup {
+    pub fn get_smoothing_group_by_id(&self, id: usize) -> SmoothingGroup<'_> {
         let faces = self
             .faces
             .iter()
@@ -1,24 +1,138 @@
-use glfw::{Context, GlfwReceiver, Key, Modifiers, WindowEvent};
+use glfw::{Context, GamepadAxis, GamepadButton, GlfwReceiver, JoystickId, WindowEvent};
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-pub struct KeyEvent {
-    pub key: Key,
-    pub modifiers: Modifiers,
+use crate::graphics::window_backend::WindowBackend;
+
+/// Gamepad buttons with the name they have in a bindings file, in the order of the `buttons`
+/// of a `GamepadInput`
+pub const GAMEPAD_BUTTON_NAMES: [(GamepadButton, &str); 15] = [
+    (GamepadButton::ButtonA, "A"),
+    (GamepadButton::ButtonB, "B"),
+    (GamepadButton::ButtonX, "X"),
+    (GamepadButton::ButtonY, "Y"),
+    (GamepadButton::ButtonLeftBumper, "LeftBumper"),
+    (GamepadButton::ButtonRightBumper, "RightBumper"),
+    (GamepadButton::ButtonBack, "Back"),
+    (GamepadButton::ButtonStart, "Start"),
+    (GamepadButton::ButtonGuide, "Guide"),
+    (GamepadButton::ButtonLeftThumb, "LeftThumb"),
+    (GamepadButton::ButtonRightThumb, "RightThumb"),
+    (GamepadButton::ButtonDpadUp, "DpadUp"),
+    (GamepadButton::ButtonDpadRight, "DpadRight"),
+    (GamepadButton::ButtonDpadDown, "DpadDown"),
+    (GamepadButton::ButtonDpadLeft, "DpadLeft"),
+];
+
+/// Gamepad axes with the name they have in a bindings file, in the order of the `axes` of a
+/// `GamepadInput`
+pub const GAMEPAD_AXIS_NAMES: [(GamepadAxis, &str); 6] = [
+    (GamepadAxis::AxisLeftX, "LeftX"),
+    (GamepadAxis::AxisLeftY, "LeftY"),
+    (GamepadAxis::AxisRightX, "RightX"),
+    (GamepadAxis::AxisRightY, "RightY"),
+    (GamepadAxis::AxisLeftTrigger, "LeftTrigger"),
+    (GamepadAxis::AxisRightTrigger, "RightTrigger"),
+];
+
+/// In the order of the `buttons` of a `GamepadInput`
+pub const GAMEPAD_BUTTONS: [GamepadButton; 15] = {
+    let mut buttons = [GamepadButton::ButtonA; 15];
+    let mut index = 0;
+    while index < buttons.len() {
+        buttons[index] = GAMEPAD_BUTTON_NAMES[index].0;
+        index += 1;
+    }
+    buttons
+};
+
+/// In the order of the `axes` of a `GamepadInput`
+pub const GAMEPAD_AXES: [GamepadAxis; 6] = {
+    let mut axes = [GamepadAxis::AxisLeftX; 6];
+    let mut index = 0;
+    while index < axes.len() {
+        axes[index] = GAMEPAD_AXIS_NAMES[index].0;
+        index += 1;
+    }
+    axes
+};
+
+///
+/// State of a connected gamepad when it was polled
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadInput {
+    /// Index of the joystick, stays the same while the gamepad is connected
+    pub id: usize,
+    pub name: String,
+    /// The buttons down
+    pub buttons: Vec<GamepadButton>,
+    /// Sticks from -1 to 1, `y` grows downwards, and triggers from -1 released to 1
+    pub axes: [f32; 6],
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMode {
+    #[default]
+    Normal,
+    /// Invisible over the window, it still leaves the window
+    Hidden,
+    /// Invisible and kept inside the window, for mouselook only the deltas of the cursor matter
+    Locked,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// Exclusive fullscreen in the current video mode of the monitor
+    Fullscreen,
+    /// An undecorated window covering the monitor, switching to another window is instant
+    Borderless,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Vsync {
+    /// Presents as soon as a frame is drawn, it may tear
+    Off,
+    #[default]
+    On,
+    /// Waits for the vertical blank unless the frame is late, then it presents at once and
+    /// tears instead of waiting for the next one. `On` when the driver does not support it.
+    Adaptive,
+}
+
+///
+/// A monitor connected when it was queried, in the order of GLFW: the primary one first
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: String,
+    /// Position on the virtual desktop, in screen coordinates
+    pub position: (i32, i32),
+    /// Resolution of the current video mode
+    pub size: (u32, u32),
+    pub refresh_rate: u32,
+    /// Ratio between the pixels and the screen coordinates, 2 on most HiDPI screens
+    pub content_scale: (f32, f32),
 }
 
 #[derive(Debug)]
 pub struct Window {
     pub glfw: glfw::Glfw,
-    pub deltatime: f32,
 
     window_handle: glfw::PWindow,
     raw_events: GlfwReceiver<(f64, WindowEvent)>,
-    last_frame: f32,
+    cursor_mode: CursorMode,
+    display_mode: DisplayMode,
+    monitor: usize,
+    /// Position and size to restore when going back to `DisplayMode::Windowed`
+    windowed_rect: (i32, i32, i32, i32),
 }
 
 impl Window {
     pub fn new(width: u32, height: u32, title: &str) -> Window {
         let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+        // lets the driver report errors through `glw::debug_message_callback`
+        glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(cfg!(debug_assertions)));
 
         let (mut window, events) = glfw
             .create_window(width, height, title, glfw::WindowMode::Windowed)
@@ -26,46 +140,217 @@ impl Window {
 
         window.set_framebuffer_size_polling(true);
         window.set_key_polling(true);
+        window.set_mouse_button_polling(true);
+        window.set_focus_polling(true);
+        window.set_cursor_pos_polling(true);
+        window.set_scroll_polling(true);
+        window.set_char_polling(true);
 
         Window {
             glfw,
-            deltatime: 0.0,
 
             window_handle: window,
             raw_events: events,
-            last_frame: 0.0,
+            cursor_mode: CursorMode::Normal,
+            display_mode: DisplayMode::Windowed,
+            monitor: 0,
+            windowed_rect: (0, 0, width as i32, height as i32),
         }
     }
+}
 
-    pub fn compute_deltatime(&mut self) {
-        let current_frame = self.glfw.get_time() as f32;
-        self.deltatime = current_frame - self.last_frame;
-        self.last_frame = current_frame;
+impl WindowBackend for Window {
+    fn create(width: u32, height: u32, title: &str) -> Window {
+        Window::new(width, height, title)
     }
 
-    pub fn init_gl(&mut self) {
+    fn init_gl(&mut self) -> bool {
         self.window_handle.make_current();
         gl::load_with(|s| self.window_handle.get_proc_address(s) as *const _);
+        true
     }
 
-    pub fn should_close(&self) -> bool {
+    fn should_close(&self) -> bool {
         self.window_handle.should_close()
     }
 
-    pub fn get_size(&self) -> (i32, i32) {
-        self.window_handle.get_size()
+    fn poll_events(&mut self) -> Vec<WindowEvent> {
+        self.glfw.poll_events();
+        glfw::flush_messages(&self.raw_events)
+            .map(|(_, event)| event)
+            .collect::<Vec<WindowEvent>>()
     }
 
-    pub fn update(&mut self) -> Vec<WindowEvent> {
-        let events = self.process_events();
-        self.glfw.poll_events();
+    fn swap_buffers(&mut self) {
         self.window_handle.swap_buffers();
-        events
     }
 
-    fn process_events(&mut self) -> Vec<WindowEvent> {
-        glfw::flush_messages(&self.raw_events)
-            .map(|(_, event)| event)
-            .collect::<Vec<WindowEvent>>()
+    fn set_vsync(&mut self, vsync: Vsync) {
+        let adaptive_supported = self.glfw.extension_supported("GLX_EXT_swap_control_tear")
+            || self.glfw.extension_supported("WGL_EXT_swap_control_tear");
+        self.glfw.set_swap_interval(match vsync {
+            Vsync::Off => glfw::SwapInterval::None,
+            Vsync::Adaptive if adaptive_supported => glfw::SwapInterval::Adaptive,
+            Vsync::On | Vsync::Adaptive => glfw::SwapInterval::Sync(1),
+        });
+    }
+
+    fn get_size(&self) -> (i32, i32) {
+        self.window_handle.get_size()
+    }
+
+    fn get_framebuffer_size(&self) -> (i32, i32) {
+        self.window_handle.get_framebuffer_size()
+    }
+
+    fn get_content_scale(&self) -> (f32, f32) {
+        self.window_handle.get_content_scale()
+    }
+
+    fn get_time(&self) -> f64 {
+        self.glfw.get_time()
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        self.glfw.with_connected_monitors(|_, monitors| {
+            monitors
+                .iter()
+                .map(|monitor| {
+                    let video_mode = monitor.get_video_mode();
+                    MonitorInfo {
+                        name: monitor.get_name().unwrap_or_default(),
+                        position: monitor.get_pos(),
+                        size: video_mode.map_or((0, 0), |mode| (mode.width, mode.height)),
+                        refresh_rate: video_mode.map_or(0, |mode| mode.refresh_rate),
+                        content_scale: monitor.get_content_scale(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    fn monitor(&self) -> usize {
+        self.monitor
+    }
+
+    ///
+    /// Moves the window to the monitor at `monitor` in `monitors`, the primary one when it is
+    /// not connected. Going back to `DisplayMode::Windowed` restores the last windowed position
+    /// and size.
+    ///
+    fn set_display_mode(&mut self, mode: DisplayMode, monitor: usize) {
+        if self.display_mode == mode && self.monitor == monitor {
+            return;
+        }
+        if self.display_mode == DisplayMode::Windowed {
+            let (x, y) = self.window_handle.get_pos();
+            let (width, height) = self.window_handle.get_size();
+            self.windowed_rect = (x, y, width, height);
+        }
+        self.display_mode = mode;
+        self.monitor = monitor;
+
+        let window = &mut self.window_handle;
+        let (x, y, width, height) = self.windowed_rect;
+        self.glfw.with_connected_monitors(|_, monitors| {
+            let monitor = monitors.get(monitor).or(monitors.first());
+            let video_mode = monitor.and_then(|monitor| monitor.get_video_mode());
+            match (mode, monitor, video_mode) {
+                (DisplayMode::Fullscreen, Some(monitor), Some(video_mode)) => {
+                    window.set_decorated(true);
+                    window.set_monitor(
+                        glfw::WindowMode::FullScreen(monitor),
+                        0,
+                        0,
+                        video_mode.width,
+                        video_mode.height,
+                        Some(video_mode.refresh_rate),
+                    );
+                }
+                (DisplayMode::Borderless, Some(monitor), Some(video_mode)) => {
+                    let (x, y) = monitor.get_pos();
+                    window.set_decorated(false);
+                    window.set_monitor(
+                        glfw::WindowMode::Windowed,
+                        x,
+                        y,
+                        video_mode.width,
+                        video_mode.height,
+                        None,
+                    );
+                }
+                _ => {
+                    window.set_decorated(true);
+                    window.set_monitor(
+                        glfw::WindowMode::Windowed,
+                        x,
+                        y,
+                        width as u32,
+                        height as u32,
+                        None,
+                    );
+                }
+            }
+        });
+    }
+
+    fn get_clipboard_string(&self) -> Option<String> {
+        self.window_handle.get_clipboard_string()
+    }
+
+    fn set_clipboard_string(&mut self, text: &str) {
+        self.window_handle.set_clipboard_string(text);
+    }
+
+    fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    ///
+    /// Locking the cursor also enables the raw mouse motion when it is supported, the deltas
+    /// are then not affected by the acceleration of the desktop
+    ///
+    fn set_cursor_mode(&mut self, mode: CursorMode) {
+        if self.cursor_mode == mode {
+            return;
+        }
+        self.cursor_mode = mode;
+
+        self.window_handle.set_cursor_mode(match mode {
+            CursorMode::Normal => glfw::CursorMode::Normal,
+            CursorMode::Hidden => glfw::CursorMode::Hidden,
+            CursorMode::Locked => glfw::CursorMode::Disabled,
+        });
+        if self.glfw.supports_raw_motion() {
+            self.window_handle
+                .set_raw_mouse_motion(mode == CursorMode::Locked);
+        }
+    }
+
+    ///
+    /// Reads the joysticks GLFW has a gamepad mapping for, the other joysticks are ignored
+    ///
+    fn poll_gamepads(&self) -> Vec<GamepadInput> {
+        (0..16)
+            .filter_map(JoystickId::from_i32)
+            .map(|id| self.glfw.get_joystick(id))
+            .filter(|joystick| joystick.is_gamepad())
+            .filter_map(|joystick| {
+                let state = joystick.get_gamepad_state()?;
+                Some(GamepadInput {
+                    id: joystick.id as usize,
+                    name: joystick.get_gamepad_name().unwrap_or_default(),
+                    buttons: GAMEPAD_BUTTONS
+                        .into_iter()
+                        .filter(|button| state.get_button_state(*button) == glfw::Action::Press)
+                        .collect(),
+                    axes: GAMEPAD_AXES.map(|axis| state.get_axis(axis)),
+                })
+            })
+            .collect()
     }
 }
@@ -0,0 +1,84 @@
+use glfw::WindowEvent;
+
+use crate::graphics::window::{CursorMode, DisplayMode, GamepadInput, MonitorInfo, Vsync};
+
+///
+/// What the `Engine` needs from a window: its events, its size, a clock and somewhere to draw.
+/// `Window` is the GLFW implementation, `NullWindow` runs without a display server.
+///
+pub trait WindowBackend {
+    fn create(width: u32, height: u32, title: &str) -> Self
+    where
+        Self: Sized;
+
+    ///
+    /// Makes the OpenGL context of the window current and loads the GL functions, returns
+    /// false when there is no context to render with
+    ///
+    fn init_gl(&mut self) -> bool;
+
+    fn should_close(&self) -> bool;
+
+    ///
+    /// The events received since the last call
+    ///
+    fn poll_events(&mut self) -> Vec<WindowEvent>;
+
+    ///
+    /// Presents the frame drawn into the default framebuffer
+    ///
+    fn swap_buffers(&mut self);
+
+    ///
+    /// Whether `swap_buffers` waits for the vertical blank, the GL context must be current
+    ///
+    fn set_vsync(&mut self, vsync: Vsync);
+
+    ///
+    /// Size of the window in screen coordinates
+    ///
+    fn get_size(&self) -> (i32, i32);
+
+    ///
+    /// Size in pixels of the default framebuffer, may differ from `get_size` on high DPI screens
+    ///
+    fn get_framebuffer_size(&self) -> (i32, i32);
+
+    ///
+    /// Ratio between the framebuffer pixels and the screen coordinates of the monitor the
+    /// window is on
+    ///
+    fn get_content_scale(&self) -> (f32, f32) {
+        (1.0, 1.0)
+    }
+
+    ///
+    /// Seconds since the window was created
+    ///
+    fn get_time(&self) -> f64;
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        Vec::new()
+    }
+
+    fn display_mode(&self) -> DisplayMode;
+
+    ///
+    /// Index in `monitors` of the monitor used when not windowed
+    ///
+    fn monitor(&self) -> usize;
+
+    fn set_display_mode(&mut self, mode: DisplayMode, monitor: usize);
+
+    fn cursor_mode(&self) -> CursorMode;
+
+    fn set_cursor_mode(&mut self, mode: CursorMode);
+
+    fn poll_gamepads(&self) -> Vec<GamepadInput> {
+        Vec::new()
+    }
+
+    fn get_clipboard_string(&self) -> Option<String>;
+
+    fn set_clipboard_string(&mut self, text: &str);
+}
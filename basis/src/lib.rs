@@ -3,6 +3,8 @@ pub mod graphics;
 pub mod math;
 pub mod prelude;
 
+pub use rand;
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
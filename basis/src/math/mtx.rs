@@ -92,6 +92,19 @@ impl ops::Mul<Mat4> for Mat4 {
     }
 }
 
+impl ops::Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, rhs: Vec4) -> Vec4 {
+        Vec4::new(
+            self.c0.x * rhs.x + self.c1.x * rhs.y + self.c2.x * rhs.z + self.c3.x * rhs.w,
+            self.c0.y * rhs.x + self.c1.y * rhs.y + self.c2.y * rhs.z + self.c3.y * rhs.w,
+            self.c0.z * rhs.x + self.c1.z * rhs.y + self.c2.z * rhs.z + self.c3.z * rhs.w,
+            self.c0.w * rhs.x + self.c1.w * rhs.y + self.c2.w * rhs.z + self.c3.w * rhs.w,
+        )
+    }
+}
+
 impl Mat4 {
     pub fn new(vec: Vec4) -> Self {
         Self {
@@ -111,19 +124,23 @@ impl Mat4 {
         }
     }
 
+    ///
+    /// Orthographic projection, the box delimited by the planes is mapped to the clip space
+    /// cube without any perspective division.
+    ///
     pub fn ortho(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
         let mut r = Mat4::identity();
-        // left    - 0 --- 500 right
-        // bottom  - 0 --- 500 top
-        // near    - 0 --- 100 far
 
         let range_x = right - left;
         let range_y = top - bottom;
         let range_z = far - near;
 
-        r.c0.x = (2. / range_x) - 1.;
-        r.c1.y = (2. / range_y) - 1.;
-        r.c2.z = -(2. / range_z) - 1.;
+        r.c0.x = 2. / range_x;
+        r.c1.y = 2. / range_y;
+        r.c2.z = -2. / range_z;
+        r.c3.x = -(right + left) / range_x;
+        r.c3.y = -(top + bottom) / range_y;
+        r.c3.z = -(far + near) / range_z;
 
         r
     }
@@ -378,30 +395,48 @@ mod tests {
 
         println!("res: {}", result);
         // line 1
-        assert_eq!(result.c0.x, -0.9975);
+        assert_eq!(result.c0.x, 0.0025);
         assert_eq!(result.c0.y, 0.0);
         assert_eq!(result.c0.z, 0.0);
         assert_eq!(result.c0.w, 0.0);
 
         // line 2
         assert_eq!(result.c1.x, 0.0);
-        assert_eq!(result.c1.y, -0.9975);
+        assert_eq!(result.c1.y, 0.0025);
         assert_eq!(result.c1.z, 0.0);
         assert_eq!(result.c1.w, 0.0);
 
         // line 3
         assert_eq!(result.c2.x, 0.0);
         assert_eq!(result.c2.y, 0.0);
-        assert_eq!(result.c2.z, -1.02002);
+        assert_eq!(result.c2.z, -0.02002002);
         assert_eq!(result.c2.w, 0.0);
 
         // line 4
-        assert_eq!(result.c3.x, 0.0);
-        assert_eq!(result.c3.y, 0.0);
-        assert_eq!(result.c3.z, 0.0);
+        assert_eq!(result.c3.x, -1.0);
+        assert_eq!(result.c3.y, -1.0);
+        assert_eq!(result.c3.z, -1.002002);
         assert_eq!(result.c3.w, 1.0);
     }
 
+    #[test]
+    fn it_should_map_ortho_box_to_clip_space() {
+        let ortho = Mat4::ortho(-10., 10., -5., 5., 1., 17.);
+
+        assert_eq!(
+            ortho * Vec4::new(-10., -5., -1., 1.),
+            Vec4::new(-1., -1., -1., 1.)
+        );
+        assert_eq!(
+            ortho * Vec4::new(10., 5., -17., 1.),
+            Vec4::new(1., 1., 1., 1.)
+        );
+        assert_eq!(
+            ortho * Vec4::new(0., 0., -9., 1.),
+            Vec4::new(0., 0., 0., 1.)
+        );
+    }
+
     #[test]
     fn it_should_be_able_to_multiply_mtx4() {
         let mut first = Mat4::splat(0.0);
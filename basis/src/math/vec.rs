@@ -139,6 +139,14 @@ impl Vec3 {
     pub fn splat(n: f32) -> Self {
         Self { x: n, y: n, z: n }
     }
+
+    pub fn length(&self) -> f32 {
+        f32::sqrt((self.x * self.x) + (self.y * self.y) + (self.z * self.z))
+    }
+
+    pub fn distance(&self, other: Vec3) -> f32 {
+        (*self - other).length()
+    }
 }
 
 impl ops::Add<Vec3> for Vec3 {
@@ -233,4 +241,14 @@ mod tests {
         assert_eq!(result.z, 3.0);
         assert_eq!(result.w, 4.0);
     }
+
+    #[test]
+    fn it_should_compute_distance_between_vec3() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(4.0, 6.0, 3.0);
+
+        assert_eq!(a.distance(b), 5.0);
+        assert_eq!(b.distance(a), 5.0);
+        assert_eq!(a.distance(a), 0.0);
+    }
 }
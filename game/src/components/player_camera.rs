@@ -24,6 +24,10 @@ impl Camerable for PlayerCamera {
     fn get_view_matrix(&self) -> Mat4 {
         Mat4::look_at(self.position, self.position + self.front, self.up)
     }
+
+    fn get_position(&self) -> Vec3 {
+        self.position
+    }
 }
 
 impl Controllable for PlayerCamera {
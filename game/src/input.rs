@@ -0,0 +1,103 @@
+use std::{fmt, str::FromStr};
+
+use basis::prelude::*;
+
+/// Read when the game starts, edit it to rebind the actions
+pub const BINDINGS_PATH: &str = "game/bindings.cfg";
+
+/// Used when the bindings file is missing, e.g. when the game is not started from the repository
+const DEFAULT_BINDINGS: &str = include_str!("../bindings.cfg");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveForward,
+    MoveBackward,
+    Jump,
+    Slide,
+    Pause,
+    /// Turns the debug camera with the mouse while held
+    Look,
+    /// Switches between windowed and borderless fullscreen
+    Fullscreen,
+}
+
+const ACTIONS: [(Action, &str); 11] = [
+    (Action::MoveUp, "MoveUp"),
+    (Action::MoveDown, "MoveDown"),
+    (Action::MoveLeft, "MoveLeft"),
+    (Action::MoveRight, "MoveRight"),
+    (Action::MoveForward, "MoveForward"),
+    (Action::MoveBackward, "MoveBackward"),
+    (Action::Jump, "Jump"),
+    (Action::Slide, "Slide"),
+    (Action::Pause, "Pause"),
+    (Action::Look, "Look"),
+    (Action::Fullscreen, "Fullscreen"),
+];
+
+impl FromStr for Action {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTIONS
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(action, _)| *action)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (_, name) = ACTIONS
+            .iter()
+            .find(|(action, _)| action == self)
+            .expect("Every action to be named");
+        write!(f, "{}", name)
+    }
+}
+
+pub fn load_bindings() -> InputMap<Action> {
+    InputMap::load(BINDINGS_PATH).unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to load '{}', using the default bindings: {}",
+            BINDINGS_PATH, err
+        );
+        InputMap::parse(DEFAULT_BINDINGS).expect("The default bindings to be valid")
+    })
+}
+
+///
+/// Moves a camera with the shared movement actions
+///
+pub fn move_controllable(
+    controllable: &mut impl Controllable,
+    input_map: &InputMap<Action>,
+    resources: &ResourcesManager,
+    deltatime: f32,
+) {
+    let held = |action| input_map.action_held(action, resources);
+
+    if held(Action::MoveUp) {
+        controllable.move_up(deltatime)
+    }
+    if held(Action::MoveDown) {
+        controllable.move_down(deltatime)
+    }
+    if held(Action::MoveLeft) {
+        controllable.move_left(deltatime)
+    }
+    if held(Action::MoveRight) {
+        controllable.move_right(deltatime)
+    }
+    if held(Action::MoveForward) {
+        controllable.move_forward(deltatime)
+    }
+    if held(Action::MoveBackward) {
+        controllable.move_backward(deltatime)
+    }
+}
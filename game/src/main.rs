@@ -4,10 +4,15 @@ use basis::{
 };
 mod behaviours;
 mod components;
+mod input;
 
 use std::process::ExitCode;
 
 use crate::components::PlayerCamera;
+use crate::input::{load_bindings, move_controllable, Action};
+
+/// Degrees the debug camera turns per pixel the mouse moves
+const MOUSE_SENSITIVITY: f32 = 0.1;
 
 #[derive(Default)]
 struct SystemDebugWireframe {
@@ -30,59 +35,99 @@ impl System for SystemDebugWireframe {
     }
 }
 
+#[derive(Default)]
+struct SystemDebugCapture {}
+impl System for SystemDebugCapture {
+    fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+        //
+        // F12 saves a screenshot, F11 records the next 120 frames
+        //
+        let event_handler = resources.get::<EventHandler>();
+        let screenshot =
+            event_handler.pressed(graphics::glfw::Key::F12, graphics::glfw::Modifiers::empty());
+        let record =
+            event_handler.pressed(graphics::glfw::Key::F11, graphics::glfw::Modifiers::empty());
+
+        let frame_capture = resources.get_mut::<FrameCapture>();
+        if screenshot {
+            frame_capture.screenshot("screenshot.png");
+        }
+        if record && !frame_capture.is_recording() {
+            frame_capture.record("recording/frame.png", 120);
+        }
+    }
+}
+
+#[derive(Default)]
+struct SystemDebugFrameStats {}
+impl System for SystemDebugFrameStats {
+    fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+        //
+        // F3 prints the frame rate of the last frames
+        //
+        let event_handler = resources.get::<EventHandler>();
+        if event_handler.pressed(graphics::glfw::Key::F3, graphics::glfw::Modifiers::empty()) {
+            let stats = resources.get::<FrameStats>();
+            println!(
+                "{:.0} fps, 1% low {:.0} fps, frame time min {:.2} ms avg {:.2} ms max {:.2} ms",
+                stats.fps(),
+                stats.one_percent_low(),
+                stats.frame_time_min() * 1000.0,
+                stats.frame_time_avg() * 1000.0,
+                stats.frame_time_max() * 1000.0,
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct SystemFullscreen {}
+impl System for SystemFullscreen {
+    fn run(&mut self, _world: &mut World, resources: &mut ResourcesManager) {
+        let input_map = resources.get::<InputMap<Action>>();
+        if input_map.action_pressed(Action::Fullscreen, resources) {
+            resources.get_mut::<WindowSettings>().toggle_fullscreen();
+        }
+    }
+}
+
 #[derive(Default)]
 struct SystemDebugCamera {}
 impl System for SystemDebugCamera {
     fn run(&mut self, world: &mut World, resources: &mut ResourcesManager) {
         let event_handler = resources.get::<EventHandler>();
+        let input_map = resources.get::<InputMap<Action>>();
         let deltatime = resources.get::<Deltatime>();
-        let shader = resources.get::<RenderShader>();
+        let looking = input_map.action_held(Action::Look, resources);
+        let mut camera = None;
 
         for entity in world.entity_manager.active_entities() {
             world.with_components_mut_1::<DebugCamera, _>(entity, |debug_camera| {
                 if let Some(debug_camera) = debug_camera {
-                    if event_handler
-                        .hold(graphics::glfw::Key::W, graphics::glfw::Modifiers::empty())
-                    {
-                        debug_camera.move_up(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::S, graphics::glfw::Modifiers::empty())
-                    {
-                        debug_camera.move_down(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::A, graphics::glfw::Modifiers::empty())
-                    {
-                        debug_camera.move_left(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::D, graphics::glfw::Modifiers::empty())
-                    {
-                        debug_camera.move_right(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::W, graphics::glfw::Modifiers::Control)
-                    {
-                        debug_camera.move_forward(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::S, graphics::glfw::Modifiers::Control)
-                    {
-                        debug_camera.move_backward(**deltatime)
-                    }
-                    if let Some((x, y)) = event_handler.mouse_move() {
-                        println!("{x} {y}");
+                    move_controllable(debug_camera, input_map, resources, **deltatime);
+                    if looking {
+                        let (x, y) = event_handler.mouse_delta();
+                        // the cursor moves down the screen when the camera should look down
+                        debug_camera.rotate(
+                            **deltatime,
+                            x as f32 * MOUSE_SENSITIVITY,
+                            -y as f32 * MOUSE_SENSITIVITY,
+                        );
                     }
 
-                    shader.bind();
-                    shader
-                        .get_uniform_location("view")
-                        .uniform_matrix4fv(&debug_camera.get_view_matrix());
-                    shader.unbind();
+                    camera = Some(Camera::from_camerable(debug_camera));
                 }
             });
         }
+
+        if let Some(camera) = camera {
+            *resources.get_mut::<Camera>() = camera;
+        }
+        resources.get_mut::<Cursor>().mode = if looking {
+            CursorMode::Locked
+        } else {
+            CursorMode::Normal
+        };
     }
 }
 
@@ -90,52 +135,23 @@ impl System for SystemDebugCamera {
 struct SystemPlayerCamera {}
 impl System for SystemPlayerCamera {
     fn run(&mut self, world: &mut World, resources: &mut ResourcesManager) {
-        let event_handler = resources.get::<EventHandler>();
-        let shader = resources.get::<RenderShader>();
+        let input_map = resources.get::<InputMap<Action>>();
         let deltatime = resources.get::<Deltatime>();
+        let mut camera = None;
 
         for entity in world.entity_manager.active_entities() {
             world.with_components_mut_1::<PlayerCamera, _>(entity, |player_camera| {
                 if let Some(player_camera) = player_camera {
-                    if event_handler
-                        .hold(graphics::glfw::Key::W, graphics::glfw::Modifiers::empty())
-                    {
-                        player_camera.move_up(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::S, graphics::glfw::Modifiers::empty())
-                    {
-                        player_camera.move_down(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::A, graphics::glfw::Modifiers::empty())
-                    {
-                        player_camera.move_left(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::D, graphics::glfw::Modifiers::empty())
-                    {
-                        player_camera.move_right(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::W, graphics::glfw::Modifiers::Control)
-                    {
-                        player_camera.move_forward(**deltatime)
-                    }
-                    if event_handler
-                        .hold(graphics::glfw::Key::S, graphics::glfw::Modifiers::Control)
-                    {
-                        player_camera.move_backward(**deltatime)
-                    }
+                    move_controllable(player_camera, input_map, resources, **deltatime);
 
-                    shader.bind();
-                    shader
-                        .get_uniform_location("view")
-                        .uniform_matrix4fv(&player_camera.get_view_matrix());
-                    shader.unbind();
+                    camera = Some(Camera::from_camerable(player_camera));
                 }
             });
         }
+
+        if let Some(camera) = camera {
+            *resources.get_mut::<Camera>() = camera;
+        }
     }
 }
 
@@ -146,13 +162,18 @@ impl System for SystemCubeSetup {
         Schedule::Setup
     }
 
-    fn run(&mut self, world: &mut World, _resources: &mut ResourcesManager) {
+    fn run(&mut self, world: &mut World, resources: &mut ResourcesManager) {
+        resources.add(load_bindings());
+        resources.get_mut::<Background>().skybox =
+            Some(Skybox::load(DEFAULT_SKYBOX).expect("Skybox faces are expected to exist."));
+
         world.add_component(DebugCamera::new(
             Vec3::new(0.0, 0.0, 10.0),
             Vec3::new(0.0, 0.0, -1.0),
             Vec3::new(0.0, 1.0, 0.0),
             30.,
         ));
+        world.add_component(Light::directional(Vec3::new(-0.5, -1.0, -0.3)));
 
         //
         // Create Cube
@@ -278,11 +299,34 @@ impl System for SystemCubeMovement {
     }
 }
 
+///
+/// `--record <file>` saves the input of the run, `--replay <file>` plays it again
+///
+fn replay_from_args() -> Result<Replay, String> {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    match args.as_slice() {
+        [] => Ok(Replay::Off),
+        [flag, path] if flag == "--record" => Ok(Replay::Record(path.clone())),
+        [flag, path] if flag == "--replay" => Ok(Replay::Play(path.clone())),
+        _ => Err("Usage: 42run [--record <file> | --replay <file>]".to_string()),
+    }
+}
+
 fn main() -> ExitCode {
-    let mut engine = Engine::new(800, 800, "42run");
+    let replay = match replay_from_args() {
+        Ok(replay) => replay,
+        Err(usage) => {
+            eprintln!("{}", usage);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut engine = Engine::new(800, 800, "42run").with_replay(replay);
     let mut systems: Vec<Box<dyn System>> = vec![
         Box::new(SystemCubeSetup::default()),
         Box::new(SystemDebugWireframe::default()),
+        Box::new(SystemDebugCapture::default()),
+        Box::new(SystemDebugFrameStats::default()),
+        Box::new(SystemFullscreen::default()),
         Box::new(SystemDebugCamera::default()),
         Box::new(SystemCubeMovement::default()),
         // Box::new(SystemPlayerCamera::default()),